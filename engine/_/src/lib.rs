@@ -103,6 +103,8 @@ pub mod prelude {
     pub use oxygengine_animation::prelude::*;
     #[cfg(feature = "oxygengine-audio")]
     pub use oxygengine_audio::prelude::*;
+    #[cfg(feature = "oxygengine-audio-backend-desktop")]
+    pub use oxygengine_audio_backend_desktop::prelude::*;
     #[cfg(feature = "oxygengine-audio-backend-web")]
     pub use oxygengine_audio_backend_web::prelude::*;
     #[cfg(feature = "oxygengine-backend-desktop")]
@@ -0,0 +1,254 @@
+use lewton::inside_ogg::OggStreamReader;
+use std::{io::Cursor, sync::Arc};
+
+const WAV_CHUNK_FRAMES: usize = 4096;
+
+pub type AudioBytes = Arc<[u8]>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioDecoderError {
+    UnknownFormat,
+    /// (channels, sample rate)
+    InvalidSpec(usize, u32),
+    Wav(String),
+    Ogg(String),
+    Mp3(String),
+}
+
+impl std::fmt::Display for AudioDecoderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "Unknown audio format"),
+            Self::InvalidSpec(channels, sample_rate) => write!(
+                f,
+                "Invalid audio spec - channels: {}, sample rate: {}",
+                channels, sample_rate
+            ),
+            Self::Wav(error) => write!(f, "WAV decoding error: {}", error),
+            Self::Ogg(error) => write!(f, "OGG decoding error: {}", error),
+            Self::Mp3(error) => write!(f, "MP3 decoding error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for AudioDecoderError {}
+
+/// Incremental decoder producing interleaved samples in range of -1.0 to 1.0.
+pub trait AudioDecoder: Send {
+    fn channels(&self) -> usize;
+
+    fn sample_rate(&self) -> u32;
+
+    /// Appends next chunk of samples to `output`. Returns `false` when there is nothing more to
+    /// decode.
+    fn decode_chunk(&mut self, output: &mut Vec<f32>) -> Result<bool, AudioDecoderError>;
+
+    fn rewind(&mut self) -> Result<(), AudioDecoderError>;
+}
+
+/// Creates decoder for WAV, OGG or MP3 audio data, detected by its header.
+pub fn audio_decoder(bytes: AudioBytes) -> Result<Box<dyn AudioDecoder>, AudioDecoderError> {
+    let result: Box<dyn AudioDecoder> = if bytes.starts_with(b"RIFF") {
+        Box::new(WavDecoder::new(bytes)?)
+    } else if bytes.starts_with(b"OggS") {
+        Box::new(OggDecoder::new(bytes)?)
+    } else if bytes.starts_with(b"ID3")
+        || (bytes.len() >= 2 && bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0)
+    {
+        Box::new(Mp3Decoder::new(bytes)?)
+    } else {
+        return Err(AudioDecoderError::UnknownFormat);
+    };
+    if result.channels() == 0 || result.sample_rate() == 0 {
+        return Err(AudioDecoderError::InvalidSpec(
+            result.channels(),
+            result.sample_rate(),
+        ));
+    }
+    Ok(result)
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct DecodedAudio {
+    pub channels: usize,
+    pub sample_rate: u32,
+    /// Interleaved samples.
+    pub samples: Vec<f32>,
+}
+
+impl DecodedAudio {
+    pub fn decode(bytes: AudioBytes) -> Result<Self, AudioDecoderError> {
+        let mut decoder = audio_decoder(bytes)?;
+        let mut samples = vec![];
+        while decoder.decode_chunk(&mut samples)? {}
+        Ok(Self {
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+            samples,
+        })
+    }
+
+    pub fn frames(&self) -> usize {
+        if self.channels > 0 {
+            self.samples.len() / self.channels
+        } else {
+            0
+        }
+    }
+
+    pub fn frame(&self, index: usize) -> &[f32] {
+        let start = index * self.channels;
+        &self.samples[start..(start + self.channels)]
+    }
+
+    pub fn duration(&self) -> f64 {
+        if self.sample_rate > 0 {
+            self.frames() as f64 / self.sample_rate as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+struct WavDecoder {
+    reader: hound::WavReader<Cursor<AudioBytes>>,
+    spec: hound::WavSpec,
+}
+
+impl WavDecoder {
+    fn new(bytes: AudioBytes) -> Result<Self, AudioDecoderError> {
+        let reader = hound::WavReader::new(Cursor::new(bytes))
+            .map_err(|error| AudioDecoderError::Wav(error.to_string()))?;
+        let spec = reader.spec();
+        Ok(Self { reader, spec })
+    }
+}
+
+impl AudioDecoder for WavDecoder {
+    fn channels(&self) -> usize {
+        self.spec.channels as usize
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.spec.sample_rate
+    }
+
+    fn decode_chunk(&mut self, output: &mut Vec<f32>) -> Result<bool, AudioDecoderError> {
+        let count = WAV_CHUNK_FRAMES * self.channels();
+        let before = output.len();
+        match self.spec.sample_format {
+            hound::SampleFormat::Float => {
+                for sample in self.reader.samples::<f32>().take(count) {
+                    output.push(sample.map_err(|error| AudioDecoderError::Wav(error.to_string()))?);
+                }
+            }
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1_i64 << (self.spec.bits_per_sample - 1)) as f32;
+                for sample in self.reader.samples::<i32>().take(count) {
+                    let sample =
+                        sample.map_err(|error| AudioDecoderError::Wav(error.to_string()))?;
+                    output.push(sample as f32 * scale);
+                }
+            }
+        }
+        Ok(output.len() - before == count)
+    }
+
+    fn rewind(&mut self) -> Result<(), AudioDecoderError> {
+        self.reader
+            .seek(0)
+            .map_err(|error| AudioDecoderError::Wav(error.to_string()))
+    }
+}
+
+struct OggDecoder {
+    bytes: AudioBytes,
+    reader: OggStreamReader<Cursor<AudioBytes>>,
+}
+
+impl OggDecoder {
+    fn new(bytes: AudioBytes) -> Result<Self, AudioDecoderError> {
+        let reader = OggStreamReader::new(Cursor::new(bytes.clone()))
+            .map_err(|error| AudioDecoderError::Ogg(error.to_string()))?;
+        Ok(Self { bytes, reader })
+    }
+}
+
+impl AudioDecoder for OggDecoder {
+    fn channels(&self) -> usize {
+        self.reader.ident_hdr.audio_channels as usize
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.reader.ident_hdr.audio_sample_rate
+    }
+
+    fn decode_chunk(&mut self, output: &mut Vec<f32>) -> Result<bool, AudioDecoderError> {
+        match self.reader.read_dec_packet_itl() {
+            Ok(Some(packet)) => {
+                output.extend(packet.into_iter().map(|sample| sample as f32 / 32768.0));
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(error) => Err(AudioDecoderError::Ogg(error.to_string())),
+        }
+    }
+
+    fn rewind(&mut self) -> Result<(), AudioDecoderError> {
+        *self = Self::new(self.bytes.clone())?;
+        Ok(())
+    }
+}
+
+struct Mp3Decoder {
+    bytes: AudioBytes,
+    decoder: minimp3::Decoder<Cursor<AudioBytes>>,
+    pending: Option<minimp3::Frame>,
+    channels: usize,
+    sample_rate: u32,
+}
+
+impl Mp3Decoder {
+    fn new(bytes: AudioBytes) -> Result<Self, AudioDecoderError> {
+        let mut decoder = minimp3::Decoder::new(Cursor::new(bytes.clone()));
+        let frame = decoder
+            .next_frame()
+            .map_err(|error| AudioDecoderError::Mp3(error.to_string()))?;
+        Ok(Self {
+            bytes,
+            decoder,
+            channels: frame.channels,
+            sample_rate: frame.sample_rate as u32,
+            pending: Some(frame),
+        })
+    }
+}
+
+impl AudioDecoder for Mp3Decoder {
+    fn channels(&self) -> usize {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn decode_chunk(&mut self, output: &mut Vec<f32>) -> Result<bool, AudioDecoderError> {
+        let frame = match self.pending.take() {
+            Some(frame) => frame,
+            None => match self.decoder.next_frame() {
+                Ok(frame) => frame,
+                Err(minimp3::Error::Eof) => return Ok(false),
+                Err(error) => return Err(AudioDecoderError::Mp3(error.to_string())),
+            },
+        };
+        output.extend(frame.data.into_iter().map(|sample| sample as f32 / 32768.0));
+        Ok(true)
+    }
+
+    fn rewind(&mut self) -> Result<(), AudioDecoderError> {
+        self.decoder = minimp3::Decoder::new(Cursor::new(self.bytes.clone()));
+        self.pending = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,289 @@
+use audio::bus::AudioEffect;
+use std::f32::consts::PI;
+
+const REVERB_COMBS: [usize; 4] = [1116, 1188, 1277, 1356];
+const REVERB_ALLPASSES: [usize; 2] = [556, 441];
+const REVERB_STEREO_SPREAD: usize = 23;
+const REVERB_INPUT_GAIN: f32 = 0.015;
+const REVERB_WET_SCALE: f32 = 3.0;
+
+#[derive(Debug, Default, Copy, Clone)]
+struct BiquadState {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    states: Vec<BiquadState>,
+}
+
+impl Biquad {
+    fn new(
+        high_pass: bool,
+        cutoff: f32,
+        resonance: f32,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let nyquist = sample_rate as f32 * 0.49;
+        let omega = 2.0 * PI * cutoff.max(1.0).min(nyquist) / sample_rate as f32;
+        let alpha = omega.sin() / (2.0 * resonance.max(0.01));
+        let cos = omega.cos();
+        let a0 = 1.0 + alpha;
+        let b = if high_pass {
+            [(1.0 + cos) * 0.5, -(1.0 + cos), (1.0 + cos) * 0.5]
+        } else {
+            [(1.0 - cos) * 0.5, 1.0 - cos, (1.0 - cos) * 0.5]
+        };
+        Self {
+            b: [b[0] / a0, b[1] / a0, b[2] / a0],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            states: vec![Default::default(); channels],
+        }
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        let channels = self.states.len();
+        for frame in buffer.chunks_exact_mut(channels) {
+            for (sample, state) in frame.iter_mut().zip(self.states.iter_mut()) {
+                let input = *sample;
+                let output = self.b[0] * input + self.b[1] * state.x1 + self.b[2] * state.x2
+                    - self.a[0] * state.y1
+                    - self.a[1] * state.y2;
+                state.x2 = state.x1;
+                state.x1 = input;
+                state.y2 = state.y1;
+                state.y1 = output;
+                *sample = output;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    filter_store: f32,
+}
+
+impl Comb {
+    fn new(size: usize) -> Self {
+        Self {
+            buffer: vec![0.0; size.max(1)],
+            index: 0,
+            filter_store: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.index];
+        self.filter_store = output * (1.0 - damping) + self.filter_store * damping;
+        self.buffer[self.index] = input + self.filter_store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(size: usize) -> Self {
+        Self {
+            buffer: vec![0.0; size.max(1)],
+            index: 0,
+        }
+    }
+
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// Freeverb-like reverb with separate comb and allpass filters set per channel.
+#[derive(Debug, Clone)]
+pub(crate) struct Reverb {
+    feedback: f32,
+    damping: f32,
+    mix: f32,
+    channels: Vec<(Vec<Comb>, Vec<Allpass>)>,
+}
+
+impl Reverb {
+    fn new(room_size: f32, damping: f32, mix: f32, sample_rate: u32, channels: usize) -> Self {
+        let scale = sample_rate as f32 / 44100.0;
+        let size = |size: usize, channel: usize| {
+            ((size + REVERB_STEREO_SPREAD * (channel % 2)) as f32 * scale) as usize
+        };
+        Self {
+            feedback: room_size.clamp(0.0, 1.0) * 0.28 + 0.7,
+            damping: damping.clamp(0.0, 1.0) * 0.4,
+            mix: mix.clamp(0.0, 1.0),
+            channels: (0..channels)
+                .map(|channel| {
+                    (
+                        REVERB_COMBS
+                            .iter()
+                            .map(|s| Comb::new(size(*s, channel)))
+                            .collect(),
+                        REVERB_ALLPASSES
+                            .iter()
+                            .map(|s| Allpass::new(size(*s, channel)))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        let count = self.channels.len();
+        for frame in buffer.chunks_exact_mut(count) {
+            for (sample, (combs, allpasses)) in frame.iter_mut().zip(self.channels.iter_mut()) {
+                let input = *sample * REVERB_INPUT_GAIN;
+                let mut wet = combs
+                    .iter_mut()
+                    .map(|comb| comb.process(input, self.feedback, self.damping))
+                    .sum::<f32>();
+                for allpass in allpasses.iter_mut() {
+                    wet = allpass.process(wet);
+                }
+                *sample = *sample * (1.0 - self.mix) + wet * REVERB_WET_SCALE * self.mix;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Compressor {
+    threshold: f32,
+    ratio: f32,
+    attack: f32,
+    release: f32,
+    makeup_gain: f32,
+    channels: usize,
+    /// Current gain reduction in dB.
+    envelope: f32,
+}
+
+impl Compressor {
+    fn new(
+        threshold: f32,
+        ratio: f32,
+        attack: f32,
+        release: f32,
+        makeup_gain: f32,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let coefficient = |time: f32| {
+            if time > 0.0 {
+                (-1.0 / (time * sample_rate as f32)).exp()
+            } else {
+                0.0
+            }
+        };
+        Self {
+            threshold,
+            ratio: ratio.max(1.0),
+            attack: coefficient(attack),
+            release: coefficient(release),
+            makeup_gain,
+            channels,
+            envelope: 0.0,
+        }
+    }
+
+    fn process(&mut self, buffer: &mut [f32]) {
+        for frame in buffer.chunks_exact_mut(self.channels) {
+            let level = frame.iter().fold(0.0_f32, |a, b| a.max(b.abs()));
+            let level = 20.0 * level.max(1.0e-6).log10();
+            let target = (level - self.threshold).max(0.0) * (1.0 - 1.0 / self.ratio);
+            let coefficient = if target > self.envelope {
+                self.attack
+            } else {
+                self.release
+            };
+            self.envelope = target + (self.envelope - target) * coefficient;
+            let gain = 10.0_f32.powf((self.makeup_gain - self.envelope) / 20.0);
+            for sample in frame {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum MixerEffect {
+    Biquad(Biquad),
+    Reverb(Reverb),
+    Compressor(Compressor),
+}
+
+impl MixerEffect {
+    pub fn new(effect: &AudioEffect, sample_rate: u32, channels: usize) -> Self {
+        match effect {
+            AudioEffect::LowPass { cutoff, resonance } => Self::Biquad(Biquad::new(
+                false,
+                *cutoff as f32,
+                *resonance as f32,
+                sample_rate,
+                channels,
+            )),
+            AudioEffect::HighPass { cutoff, resonance } => Self::Biquad(Biquad::new(
+                true,
+                *cutoff as f32,
+                *resonance as f32,
+                sample_rate,
+                channels,
+            )),
+            AudioEffect::Reverb {
+                room_size,
+                damping,
+                mix,
+            } => Self::Reverb(Reverb::new(
+                *room_size as f32,
+                *damping as f32,
+                *mix as f32,
+                sample_rate,
+                channels,
+            )),
+            AudioEffect::Compressor {
+                threshold,
+                ratio,
+                attack,
+                release,
+                makeup_gain,
+            } => Self::Compressor(Compressor::new(
+                *threshold as f32,
+                *ratio as f32,
+                *attack as f32,
+                *release as f32,
+                *makeup_gain as f32,
+                sample_rate,
+                channels,
+            )),
+        }
+    }
+
+    /// Processes interleaved samples in place.
+    pub fn process(&mut self, buffer: &mut [f32]) {
+        match self {
+            Self::Biquad(effect) => effect.process(buffer),
+            Self::Reverb(effect) => effect.process(buffer),
+            Self::Compressor(effect) => effect.process(buffer),
+        }
+    }
+}
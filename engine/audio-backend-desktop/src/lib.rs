@@ -0,0 +1,505 @@
+extern crate oxygengine_audio as audio;
+extern crate oxygengine_core as core;
+
+pub mod decoder;
+mod effects;
+mod mixer;
+
+pub mod prelude {
+    pub use crate::{decoder::*, *};
+}
+
+use crate::{
+    decoder::{audio_decoder, DecodedAudio},
+    mixer::{AudioMixer, MixerSource, MixerSourceData, MixerStream},
+};
+use audio::{bus::AudioBusConfig, resource::*};
+use core::{
+    assets::{asset::AssetId, database::AssetsDatabase},
+    ecs::Entity,
+    Scalar,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+#[cfg(feature = "device")]
+use std::{
+    sync::mpsc::{sync_channel, SyncSender},
+    thread::JoinHandle,
+};
+
+pub const DEFAULT_OFFLINE_SAMPLE_RATE: u32 = 44100;
+pub const DEFAULT_OFFLINE_CHANNELS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DesktopAudioError {
+    NoOutputDevice,
+    Device(String),
+    UnsupportedSampleFormat(String),
+}
+
+impl std::fmt::Display for DesktopAudioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoOutputDevice => write!(f, "There is no audio output device"),
+            Self::Device(error) => write!(f, "Audio output device error: {}", error),
+            Self::UnsupportedSampleFormat(format) => {
+                write!(f, "Unsupported audio output sample format: {}", format)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DesktopAudioError {}
+
+/// Output stream lives on its own thread because `cpal::Stream` cannot be moved between
+/// threads - dropping this handle stops the thread together with the stream it owns.
+#[cfg(feature = "device")]
+struct DeviceThread {
+    stop: Option<SyncSender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[cfg(feature = "device")]
+impl Drop for DeviceThread {
+    fn drop(&mut self) {
+        // Dropping sender wakes up audio thread.
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Software mixer audio backend, playing either on system output device or on offline device
+/// that has to be manually driven with [`DesktopAudio::render`].
+pub struct DesktopAudio {
+    mixer: Arc<Mutex<AudioMixer>>,
+    #[cfg(feature = "device")]
+    device: Option<DeviceThread>,
+    table_forward: HashMap<String, AssetId>,
+    table_backward: HashMap<AssetId, String>,
+}
+
+impl Default for DesktopAudio {
+    fn default() -> Self {
+        #[cfg(feature = "device")]
+        {
+            match Self::new() {
+                Ok(result) => return result,
+                Err(error) => oxygengine_core::warn!(
+                    "Could not open audio output device, falling back to offline device: {}",
+                    error
+                ),
+            }
+        }
+        Self::offline(DEFAULT_OFFLINE_SAMPLE_RATE, DEFAULT_OFFLINE_CHANNELS)
+    }
+}
+
+impl DesktopAudio {
+    /// Opens default system audio output device.
+    #[cfg(feature = "device")]
+    pub fn new() -> Result<Self, DesktopAudioError> {
+        let (ready_sender, ready_receiver) = sync_channel(1);
+        let (stop_sender, stop_receiver) = sync_channel::<()>(0);
+        let handle = std::thread::Builder::new()
+            .name("oxygengine-audio".to_owned())
+            .spawn(move || match Self::open_device() {
+                Ok((mixer, stream)) => {
+                    if ready_sender.send(Ok(mixer)).is_ok() {
+                        // Keeps stream alive until device handle gets dropped.
+                        let _ = stop_receiver.recv();
+                    }
+                    drop(stream);
+                }
+                Err(error) => {
+                    let _ = ready_sender.send(Err(error));
+                }
+            })
+            .map_err(|error| DesktopAudioError::Device(error.to_string()))?;
+        let device = DeviceThread {
+            stop: Some(stop_sender),
+            handle: Some(handle),
+        };
+        let mixer = ready_receiver.recv().map_err(|_| {
+            DesktopAudioError::Device("Audio thread stopped unexpectedly".to_owned())
+        })??;
+        Ok(Self {
+            mixer,
+            device: Some(device),
+            table_forward: Default::default(),
+            table_backward: Default::default(),
+        })
+    }
+
+    #[cfg(feature = "device")]
+    fn open_device() -> Result<(Arc<Mutex<AudioMixer>>, cpal::Stream), DesktopAudioError> {
+        use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or(DesktopAudioError::NoOutputDevice)?;
+        let config = device
+            .default_output_config()
+            .map_err(|error| DesktopAudioError::Device(error.to_string()))?;
+        let sample_format = config.sample_format();
+        let config = config.config();
+        let mixer = Arc::new(Mutex::new(AudioMixer::new(
+            config.sample_rate.0,
+            config.channels as usize,
+        )));
+        let stream = match sample_format {
+            cpal::SampleFormat::F32 => Self::build_stream::<f32>(&device, &config, mixer.clone()),
+            cpal::SampleFormat::I16 => Self::build_stream::<i16>(&device, &config, mixer.clone()),
+            cpal::SampleFormat::U16 => Self::build_stream::<u16>(&device, &config, mixer.clone()),
+            format => {
+                return Err(DesktopAudioError::UnsupportedSampleFormat(
+                    format.to_string(),
+                ))
+            }
+        }?;
+        stream
+            .play()
+            .map_err(|error| DesktopAudioError::Device(error.to_string()))?;
+        Ok((mixer, stream))
+    }
+
+    /// Creates offline device that produces samples only when [`DesktopAudio::render`] gets
+    /// called, useful for headless runs and tests.
+    pub fn offline(sample_rate: u32, channels: usize) -> Self {
+        Self {
+            mixer: Arc::new(Mutex::new(AudioMixer::new(sample_rate, channels))),
+            #[cfg(feature = "device")]
+            device: None,
+            table_forward: Default::default(),
+            table_backward: Default::default(),
+        }
+    }
+
+    #[cfg(feature = "device")]
+    fn build_stream<T>(
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        mixer: Arc<Mutex<AudioMixer>>,
+    ) -> Result<cpal::Stream, DesktopAudioError>
+    where
+        T: cpal::SizedSample + cpal::FromSample<f32>,
+    {
+        use cpal::traits::DeviceTrait;
+
+        let mut buffer = vec![];
+        device
+            .build_output_stream(
+                config,
+                move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                    buffer.resize(data.len(), 0.0);
+                    if let Ok(mut mixer) = mixer.lock() {
+                        mixer.render(&mut buffer);
+                    } else {
+                        buffer.fill(0.0);
+                    }
+                    for (output, sample) in data.iter_mut().zip(buffer.iter()) {
+                        *output = T::from_sample(*sample);
+                    }
+                },
+                |error| oxygengine_core::error!("Audio output stream error: {}", error),
+                None,
+            )
+            .map_err(|error| DesktopAudioError::Device(error.to_string()))
+    }
+
+    pub fn is_offline(&self) -> bool {
+        #[cfg(feature = "device")]
+        {
+            self.device.is_none()
+        }
+        #[cfg(not(feature = "device"))]
+        {
+            true
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.mixer().sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.mixer().channels
+    }
+
+    /// Mixes next `output.len() / channels` frames of interleaved samples from playing sources.
+    /// Meant to drive offline device - when used on system device it steals its samples.
+    pub fn render(&self, output: &mut [f32]) {
+        self.mixer().render(output);
+    }
+
+    /// Mixes next `frames` frames of interleaved samples from playing sources.
+    pub fn render_frames(&self, frames: usize) -> Vec<f32> {
+        let mut mixer = self.mixer();
+        let mut result = vec![0.0; frames * mixer.channels];
+        mixer.render(&mut result);
+        result
+    }
+
+    fn mixer(&self) -> MutexGuard<AudioMixer> {
+        self.mixer.lock().expect("Could not lock audio mixer")
+    }
+}
+
+impl Audio for DesktopAudio {
+    fn create_source(
+        &mut self,
+        entity: Entity,
+        data: &[u8],
+        streaming: bool,
+        looped: bool,
+        playback_rate: Scalar,
+        volume: Scalar,
+        play: bool,
+        notify_ready: Arc<AtomicBool>,
+    ) {
+        let bytes = Arc::from(data);
+        let data = if streaming {
+            audio_decoder(bytes)
+                .map(|decoder| MixerSourceData::Streaming(MixerStream::new(decoder)))
+        } else {
+            DecodedAudio::decode(bytes).map(|audio| MixerSourceData::Buffered(Arc::new(audio)))
+        };
+        let mut source = match data {
+            Ok(data) => {
+                notify_ready.store(true, Ordering::Relaxed);
+                MixerSource::new(data)
+            }
+            Err(error) => {
+                oxygengine_core::error!("Could not decode audio source data: {}", error);
+                MixerSource::new(MixerSourceData::Buffered(Default::default()))
+            }
+        };
+        source.looped = looped;
+        source.playback_rate = playback_rate as f64;
+        source.volume = volume as f32;
+        if play {
+            source.play();
+        }
+        self.mixer().sources.insert(entity, source);
+    }
+
+    fn destroy_source(&mut self, entity: Entity) {
+        self.mixer().sources.remove(&entity);
+    }
+
+    fn has_source(&mut self, entity: Entity) -> bool {
+        self.mixer().sources.contains_key(&entity)
+    }
+
+    fn update_source(
+        &mut self,
+        entity: Entity,
+        looped: bool,
+        playback_rate: Scalar,
+        volume: Scalar,
+        play: Option<bool>,
+    ) {
+        if let Some(source) = self.mixer().sources.get_mut(&entity) {
+            source.looped = looped;
+            source.playback_rate = playback_rate as f64;
+            source.volume = volume as f32;
+            match play {
+                Some(true) => source.play(),
+                Some(false) => source.stop(),
+                None => {}
+            }
+        }
+    }
+
+    fn get_source_state(&self, entity: Entity) -> Option<AudioState> {
+        self.mixer().sources.get_mut(&entity).map(|source| {
+            let is_playing = match source.data {
+                MixerSourceData::Buffered(_) => {
+                    AudioPlayState::Ended(std::mem::take(&mut source.ended))
+                }
+                MixerSourceData::Streaming(_) => AudioPlayState::State(source.playing),
+            };
+            AudioState {
+                current_time: Some(source.current_time() as Scalar),
+                is_playing,
+            }
+        })
+    }
+
+    fn set_source_bus(&mut self, entity: Entity, bus: &str) {
+        if let Some(source) = self.mixer().sources.get_mut(&entity) {
+            if source.bus != bus {
+                source.bus = bus.to_owned();
+            }
+        }
+    }
+
+    fn set_source_spatial(&mut self, entity: Entity, gain: Scalar, pan: Scalar) {
+        if let Some(source) = self.mixer().sources.get_mut(&entity) {
+            source.spatial_gain = gain as f32;
+            source.pan = pan as f32;
+        }
+    }
+
+    fn update_bus(&mut self, name: &str, config: &AudioBusConfig) {
+        self.mixer().update_bus(name, config);
+    }
+
+    fn destroy_bus(&mut self, name: &str) {
+        self.mixer().destroy_bus(name);
+    }
+
+    fn get_asset_id(&self, path: &str) -> Option<AssetId> {
+        self.table_forward.get(path).copied()
+    }
+
+    fn update_cache(&mut self, assets: &AssetsDatabase) {
+        for id in assets.lately_loaded_protocol("audio") {
+            let id = *id;
+            let asset = assets
+                .asset_by_id(id)
+                .expect("trying to use not loaded audio asset");
+            let path = asset.path().to_owned();
+            self.table_forward.insert(path.clone(), id);
+            self.table_backward.insert(id, path);
+        }
+        for id in assets.lately_unloaded_protocol("audio") {
+            if let Some(path) = self.table_backward.remove(id) {
+                self.table_forward.remove(&path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use audio::bus::*;
+    use core::ecs::World;
+    use std::io::Cursor;
+
+    fn wav(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let mut result = vec![];
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::new(Cursor::new(&mut result), spec).unwrap();
+        for sample in samples {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        result
+    }
+
+    #[test]
+    fn test_desktop_audio() {
+        let mut world = World::default();
+        let entity = world.spawn(());
+        let data = wav(10, &[16384; 10]);
+        let mut audio = DesktopAudio::offline(10, 2);
+        assert!(audio.is_offline());
+        let ready = Arc::new(AtomicBool::new(false));
+        audio.create_source(entity, &data, false, false, 1.0, 0.5, true, ready.clone());
+        assert!(ready.load(Ordering::Relaxed));
+        assert!(audio.has_source(entity));
+
+        let samples = audio.render_frames(4);
+        assert!(samples.iter().all(|sample| (*sample - 0.25).abs() < 1.0e-4));
+        let state = audio.get_source_state(entity).unwrap();
+        assert_eq!(state.current_time, Some(0.4));
+        assert!(matches!(state.is_playing, AudioPlayState::Ended(false)));
+
+        let samples = audio.render_frames(10);
+        assert!(samples[..12].iter().all(|sample| *sample > 0.0));
+        assert!(samples[12..].iter().all(|sample| *sample == 0.0));
+        let state = audio.get_source_state(entity).unwrap();
+        assert!(matches!(state.is_playing, AudioPlayState::Ended(true)));
+        let state = audio.get_source_state(entity).unwrap();
+        assert!(matches!(state.is_playing, AudioPlayState::Ended(false)));
+
+        audio.update_source(entity, true, 2.0, 1.0, Some(true));
+        let samples = audio.render_frames(20);
+        assert!(samples.iter().all(|sample| (*sample - 0.5).abs() < 1.0e-4));
+        let state = audio.get_source_state(entity).unwrap();
+        assert!(matches!(state.is_playing, AudioPlayState::Ended(false)));
+
+        audio.set_source_spatial(entity, 0.5, -0.5);
+        let samples = audio.render_frames(2);
+        assert!(samples
+            .chunks_exact(2)
+            .all(|frame| (frame[0] - 0.25).abs() < 1.0e-4 && (frame[1] - 0.125).abs() < 1.0e-4));
+
+        audio.destroy_source(entity);
+        assert!(!audio.has_source(entity));
+    }
+
+    #[test]
+    fn test_desktop_audio_buses() {
+        let mut world = World::default();
+        let music = world.spawn(());
+        let sfx = world.spawn(());
+        let data = wav(10, &[16384; 10]);
+        let mut audio = DesktopAudio::offline(10, 1);
+        let ready = Arc::new(AtomicBool::new(false));
+        audio.create_source(music, &data, false, true, 1.0, 1.0, true, ready.clone());
+        audio.create_source(sfx, &data, false, true, 1.0, 1.0, true, ready);
+        audio.set_source_bus(music, AUDIO_BUS_MUSIC);
+        audio.set_source_bus(sfx, AUDIO_BUS_SFX);
+
+        // not existing buses route to master.
+        let samples = audio.render_frames(2);
+        assert!(samples.iter().all(|sample| (*sample - 1.0).abs() < 1.0e-4));
+
+        audio.update_bus(AUDIO_BUS_MUSIC, &AudioBusConfig::default().volume(0.5));
+        audio.update_bus(AUDIO_BUS_SFX, &AudioBusConfig::default().muted(true));
+        audio.update_bus(AUDIO_BUS_MASTER, &AudioBusConfig::default().volume(0.5));
+        let samples = audio.render_frames(2);
+        assert!(samples
+            .iter()
+            .all(|sample| (*sample - 0.125).abs() < 1.0e-4));
+
+        audio.update_bus(
+            AUDIO_BUS_MUSIC,
+            &AudioBusConfig::default().effect(AudioEffect::high_pass(1.0)),
+        );
+        audio.destroy_bus(AUDIO_BUS_MASTER);
+        let samples = audio.render_frames(100);
+        assert!(samples[99].abs() < 1.0e-2);
+
+        audio.destroy_bus(AUDIO_BUS_SFX);
+        let samples = audio.render_frames(2);
+        assert!(samples.iter().all(|sample| *sample > 0.4));
+    }
+
+    #[test]
+    fn test_desktop_audio_streaming() {
+        let mut world = World::default();
+        let entity = world.spawn(());
+        let data = wav(10, &[-16384; 5]);
+        let mut audio = DesktopAudio::offline(10, 1);
+        let ready = Arc::new(AtomicBool::new(false));
+        audio.create_source(entity, &data, true, true, 1.0, 1.0, true, ready);
+
+        let samples = audio.render_frames(12);
+        assert!(samples.iter().all(|sample| (*sample + 0.5).abs() < 1.0e-4));
+        let state = audio.get_source_state(entity).unwrap();
+        assert_eq!(state.current_time, Some(0.2));
+        assert!(matches!(state.is_playing, AudioPlayState::State(true)));
+
+        audio.update_source(entity, false, 1.0, 1.0, None);
+        let samples = audio.render_frames(10);
+        assert!(samples[..3].iter().all(|sample| *sample < 0.0));
+        assert!(samples[3..].iter().all(|sample| *sample == 0.0));
+        let state = audio.get_source_state(entity).unwrap();
+        assert!(matches!(state.is_playing, AudioPlayState::State(false)));
+    }
+}
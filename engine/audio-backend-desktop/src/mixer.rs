@@ -0,0 +1,377 @@
+use crate::{
+    decoder::{AudioDecoder, DecodedAudio},
+    effects::MixerEffect,
+};
+use audio::bus::{AudioBusConfig, AudioEffect, AUDIO_BUS_MASTER};
+use core::ecs::Entity;
+use std::{collections::HashMap, sync::Arc};
+
+pub(crate) enum MixerSourceData {
+    Buffered(Arc<DecodedAudio>),
+    Streaming(MixerStream),
+}
+
+pub(crate) struct MixerStream {
+    decoder: Box<dyn AudioDecoder>,
+    /// Interleaved samples of decoded frames starting at `buffer_start` frame.
+    buffer: Vec<f32>,
+    buffer_start: usize,
+    /// (previous, current) frames where decoding started from the beginning.
+    loop_start: (usize, usize),
+    exhausted: bool,
+}
+
+impl MixerStream {
+    pub fn new(decoder: Box<dyn AudioDecoder>) -> Self {
+        Self {
+            decoder,
+            buffer: vec![],
+            buffer_start: 0,
+            loop_start: (0, 0),
+            exhausted: false,
+        }
+    }
+
+    fn end(&self) -> usize {
+        self.buffer_start + self.buffer.len() / self.decoder.channels()
+    }
+
+    fn frame(&self, index: usize) -> &[f32] {
+        let channels = self.decoder.channels();
+        let start = (index - self.buffer_start) * channels;
+        &self.buffer[start..(start + channels)]
+    }
+
+    /// Decodes frames until `frame` is available, returns end of decoded frames.
+    fn fill(&mut self, frame: usize, looped: bool) -> usize {
+        while self.end() <= frame {
+            if self.exhausted {
+                if !looped || self.end() <= self.loop_start.1 {
+                    break;
+                }
+                if let Err(error) = self.decoder.rewind() {
+                    oxygengine_core::error!("Could not rewind audio stream: {}", error);
+                    break;
+                }
+                self.loop_start = (self.loop_start.1, self.end());
+                self.exhausted = false;
+            }
+            match self.decoder.decode_chunk(&mut self.buffer) {
+                Ok(true) => {}
+                Ok(false) => self.exhausted = true,
+                Err(error) => {
+                    oxygengine_core::error!("Could not decode audio stream: {}", error);
+                    self.exhausted = true;
+                    break;
+                }
+            }
+        }
+        self.end()
+    }
+
+    /// Removes frames that were already played.
+    fn compact(&mut self, position: usize) {
+        let count = position
+            .saturating_sub(self.buffer_start)
+            .min(self.end() - self.buffer_start);
+        if count > 0 {
+            self.buffer.drain(..(count * self.decoder.channels()));
+            self.buffer_start += count;
+        }
+    }
+
+    fn restart(&mut self) {
+        if let Err(error) = self.decoder.rewind() {
+            oxygengine_core::error!("Could not rewind audio stream: {}", error);
+        }
+        self.buffer.clear();
+        self.buffer_start = 0;
+        self.loop_start = (0, 0);
+        self.exhausted = false;
+    }
+}
+
+pub(crate) struct MixerSource {
+    pub data: MixerSourceData,
+    pub looped: bool,
+    pub playback_rate: f64,
+    pub volume: f32,
+    pub playing: bool,
+    pub ended: bool,
+    pub bus: String,
+    pub spatial_gain: f32,
+    /// Ranges from -1.0 (left) to 1.0 (right).
+    pub pan: f32,
+    /// Position in source frames.
+    position: f64,
+}
+
+impl MixerSource {
+    pub fn new(data: MixerSourceData) -> Self {
+        Self {
+            data,
+            looped: false,
+            playback_rate: 1.0,
+            volume: 1.0,
+            playing: false,
+            ended: false,
+            bus: AUDIO_BUS_MASTER.to_owned(),
+            spatial_gain: 1.0,
+            pan: 0.0,
+            position: 0.0,
+        }
+    }
+
+    pub fn play(&mut self) {
+        self.position = 0.0;
+        self.playing = true;
+        if let MixerSourceData::Streaming(stream) = &mut self.data {
+            stream.restart();
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.playing = false;
+        if let MixerSourceData::Buffered(_) = &self.data {
+            self.position = 0.0;
+        }
+    }
+
+    pub fn current_time(&self) -> f64 {
+        match &self.data {
+            MixerSourceData::Buffered(audio) => self.position / audio.sample_rate as f64,
+            MixerSourceData::Streaming(stream) => {
+                let (previous, current) = stream.loop_start;
+                let position = self.position as usize;
+                let start = if position >= current {
+                    current
+                } else {
+                    previous
+                };
+                (self.position - start as f64).max(0.0) / stream.decoder.sample_rate() as f64
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        self.playing = false;
+        self.ended = true;
+        self.position = 0.0;
+    }
+
+    fn mix(&mut self, output: &mut [f32], channels: usize, sample_rate: u32) {
+        if !self.playing {
+            return;
+        }
+        let volume = self.volume.max(0.0) * self.spatial_gain.max(0.0);
+        let pan = self.pan.clamp(-1.0, 1.0);
+        match &mut self.data {
+            MixerSourceData::Buffered(audio) => {
+                let frames = audio.frames();
+                let step =
+                    self.playback_rate.max(0.0) * audio.sample_rate as f64 / sample_rate as f64;
+                let mut finished = false;
+                for output in output.chunks_exact_mut(channels) {
+                    if self.position >= frames as f64 {
+                        if self.looped && frames > 0 {
+                            self.position %= frames as f64;
+                        } else {
+                            finished = true;
+                            break;
+                        }
+                    }
+                    let index = self.position as usize;
+                    let next = if index + 1 < frames {
+                        index + 1
+                    } else if self.looped {
+                        0
+                    } else {
+                        index
+                    };
+                    mix_frame(
+                        output,
+                        audio.frame(index),
+                        audio.frame(next),
+                        self.position.fract() as f32,
+                        volume,
+                        pan,
+                    );
+                    self.position += step;
+                }
+                if finished {
+                    self.finish();
+                }
+            }
+            MixerSourceData::Streaming(stream) => {
+                let step = self.playback_rate.max(0.0) * stream.decoder.sample_rate() as f64
+                    / sample_rate as f64;
+                let mut finished = false;
+                for output in output.chunks_exact_mut(channels) {
+                    let index = self.position as usize;
+                    let end = stream.fill(index + 1, self.looped);
+                    if index >= end {
+                        finished = true;
+                        break;
+                    }
+                    mix_frame(
+                        output,
+                        stream.frame(index),
+                        stream.frame((index + 1).min(end - 1)),
+                        self.position.fract() as f32,
+                        volume,
+                        pan,
+                    );
+                    self.position += step;
+                }
+                if finished {
+                    stream.restart();
+                    self.finish();
+                } else {
+                    stream.compact(self.position as usize);
+                }
+            }
+        }
+    }
+}
+
+fn channel_sample(frame: &[f32], channel: usize, channels: usize) -> f32 {
+    if channels == 1 && frame.len() > 1 {
+        frame.iter().sum::<f32>() / frame.len() as f32
+    } else {
+        frame[channel.min(frame.len() - 1)]
+    }
+}
+
+fn mix_frame(output: &mut [f32], from: &[f32], to: &[f32], factor: f32, volume: f32, pan: f32) {
+    let channels = output.len();
+    for (channel, sample) in output.iter_mut().enumerate() {
+        let from = channel_sample(from, channel, channels);
+        let to = channel_sample(to, channel, channels);
+        let balance = match (channels, channel) {
+            (2, 0) => (1.0 - pan).min(1.0),
+            (2, _) => (1.0 + pan).min(1.0),
+            _ => 1.0,
+        };
+        *sample += (from + (to - from) * factor) * volume * balance;
+    }
+}
+
+pub(crate) struct MixerBus {
+    pub volume: f32,
+    pub muted: bool,
+    /// Config of effects currently built, used to detect changes.
+    effects_config: Vec<AudioEffect>,
+    effects: Vec<MixerEffect>,
+    buffer: Vec<f32>,
+}
+
+impl Default for MixerBus {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            effects_config: vec![],
+            effects: vec![],
+            buffer: vec![],
+        }
+    }
+}
+
+impl MixerBus {
+    /// Updates bus settings in place - effects are rebuilt only when their list has changed, so
+    /// their state (filter history, delay lines) survives volume or mute changes.
+    pub fn apply(&mut self, config: &AudioBusConfig, sample_rate: u32, channels: usize) {
+        self.volume = config.volume as f32;
+        self.muted = config.muted;
+        if self.effects_config != config.effects {
+            self.effects = config
+                .effects
+                .iter()
+                .map(|effect| MixerEffect::new(effect, sample_rate, channels))
+                .collect();
+            self.effects_config = config.effects.to_owned();
+        }
+    }
+
+    fn reset(&mut self, size: usize) {
+        self.buffer.clear();
+        self.buffer.resize(size, 0.0);
+    }
+
+    fn process(&mut self) {
+        for effect in &mut self.effects {
+            effect.process(&mut self.buffer);
+        }
+        let gain = if self.muted {
+            0.0
+        } else {
+            self.volume.max(0.0)
+        };
+        for sample in &mut self.buffer {
+            *sample *= gain;
+        }
+    }
+}
+
+pub(crate) struct AudioMixer {
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub sources: HashMap<Entity, MixerSource>,
+    pub master: MixerBus,
+    /// Buses other than master, all of them output to master bus.
+    pub buses: HashMap<String, MixerBus>,
+}
+
+impl AudioMixer {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        Self {
+            sample_rate,
+            channels: channels.max(1),
+            sources: Default::default(),
+            master: Default::default(),
+            buses: Default::default(),
+        }
+    }
+
+    pub fn update_bus(&mut self, name: &str, config: &AudioBusConfig) {
+        let bus = if name == AUDIO_BUS_MASTER {
+            &mut self.master
+        } else {
+            self.buses.entry(name.to_owned()).or_default()
+        };
+        bus.apply(config, self.sample_rate, self.channels);
+    }
+
+    pub fn destroy_bus(&mut self, name: &str) {
+        if name == AUDIO_BUS_MASTER {
+            self.master = Default::default();
+        } else {
+            self.buses.remove(name);
+        }
+    }
+
+    /// Fills `output` interleaved samples with mix of all playing sources.
+    pub fn render(&mut self, output: &mut [f32]) {
+        self.master.reset(output.len());
+        for bus in self.buses.values_mut() {
+            bus.reset(output.len());
+        }
+        for source in self.sources.values_mut() {
+            let bus = match self.buses.get_mut(&source.bus) {
+                Some(bus) => bus,
+                None => &mut self.master,
+            };
+            source.mix(&mut bus.buffer, self.channels, self.sample_rate);
+        }
+        for bus in self.buses.values_mut() {
+            bus.process();
+            for (output, sample) in self.master.buffer.iter_mut().zip(bus.buffer.iter()) {
+                *output += *sample;
+            }
+        }
+        self.master.process();
+        for (output, sample) in output.iter_mut().zip(self.master.buffer.iter()) {
+            *output = sample.clamp(-1.0, 1.0);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use core::{prefab::Prefab, Scalar};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub const AUDIO_BUS_MASTER: &str = "master";
+pub const AUDIO_BUS_MUSIC: &str = "music";
+pub const AUDIO_BUS_SFX: &str = "sfx";
+pub const AUDIO_BUS_VOICE: &str = "voice";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AudioEffect {
+    LowPass {
+        /// Cutoff frequency in Hz.
+        cutoff: Scalar,
+        #[serde(default = "AudioEffect::default_resonance")]
+        resonance: Scalar,
+    },
+    HighPass {
+        /// Cutoff frequency in Hz.
+        cutoff: Scalar,
+        #[serde(default = "AudioEffect::default_resonance")]
+        resonance: Scalar,
+    },
+    Reverb {
+        /// Range of 0.0 to 1.0.
+        #[serde(default = "AudioEffect::default_room_size")]
+        room_size: Scalar,
+        /// Range of 0.0 to 1.0.
+        #[serde(default = "AudioEffect::default_damping")]
+        damping: Scalar,
+        /// Range of 0.0 (dry only) to 1.0 (wet only).
+        #[serde(default = "AudioEffect::default_mix")]
+        mix: Scalar,
+    },
+    Compressor {
+        /// Level in dB above which signal gets compressed.
+        threshold: Scalar,
+        #[serde(default = "AudioEffect::default_ratio")]
+        ratio: Scalar,
+        /// Time in seconds.
+        #[serde(default = "AudioEffect::default_attack")]
+        attack: Scalar,
+        /// Time in seconds.
+        #[serde(default = "AudioEffect::default_release")]
+        release: Scalar,
+        /// Gain in dB applied after compression.
+        #[serde(default)]
+        makeup_gain: Scalar,
+    },
+}
+
+impl AudioEffect {
+    fn default_resonance() -> Scalar {
+        std::f64::consts::FRAC_1_SQRT_2 as Scalar
+    }
+
+    fn default_room_size() -> Scalar {
+        0.5
+    }
+
+    fn default_damping() -> Scalar {
+        0.5
+    }
+
+    fn default_mix() -> Scalar {
+        0.3
+    }
+
+    fn default_ratio() -> Scalar {
+        4.0
+    }
+
+    fn default_attack() -> Scalar {
+        0.01
+    }
+
+    fn default_release() -> Scalar {
+        0.1
+    }
+
+    pub fn low_pass(cutoff: Scalar) -> Self {
+        Self::LowPass {
+            cutoff,
+            resonance: Self::default_resonance(),
+        }
+    }
+
+    pub fn high_pass(cutoff: Scalar) -> Self {
+        Self::HighPass {
+            cutoff,
+            resonance: Self::default_resonance(),
+        }
+    }
+
+    pub fn reverb(room_size: Scalar, damping: Scalar, mix: Scalar) -> Self {
+        Self::Reverb {
+            room_size,
+            damping,
+            mix,
+        }
+    }
+
+    pub fn compressor(threshold: Scalar, ratio: Scalar) -> Self {
+        Self::Compressor {
+            threshold,
+            ratio,
+            attack: Self::default_attack(),
+            release: Self::default_release(),
+            makeup_gain: 0.0,
+        }
+    }
+}
+
+impl Prefab for AudioEffect {}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioBusConfig {
+    #[serde(default = "AudioBusConfig::default_volume")]
+    pub volume: Scalar,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub effects: Vec<AudioEffect>,
+}
+
+impl Default for AudioBusConfig {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            effects: vec![],
+        }
+    }
+}
+
+impl AudioBusConfig {
+    fn default_volume() -> Scalar {
+        1.0
+    }
+
+    pub fn volume(mut self, value: Scalar) -> Self {
+        self.volume = value;
+        self
+    }
+
+    pub fn muted(mut self, value: bool) -> Self {
+        self.muted = value;
+        self
+    }
+
+    pub fn effect(mut self, value: AudioEffect) -> Self {
+        self.effects.push(value);
+        self
+    }
+
+    pub fn effects(mut self, value: Vec<AudioEffect>) -> Self {
+        self.effects = value;
+        self
+    }
+}
+
+impl Prefab for AudioBusConfig {}
+
+/// Named mixer buses that audio sources route to. Every bus other than master outputs to the
+/// master bus.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioBuses {
+    #[serde(default)]
+    buses: HashMap<String, AudioBusConfig>,
+    #[serde(skip)]
+    dirty: HashSet<String>,
+    #[serde(skip)]
+    removed: HashSet<String>,
+}
+
+impl Default for AudioBuses {
+    fn default() -> Self {
+        let mut result = Self {
+            buses: [
+                AUDIO_BUS_MASTER,
+                AUDIO_BUS_MUSIC,
+                AUDIO_BUS_SFX,
+                AUDIO_BUS_VOICE,
+            ]
+            .into_iter()
+            .map(|name| (name.to_owned(), Default::default()))
+            .collect(),
+            dirty: Default::default(),
+            removed: Default::default(),
+        };
+        result.post_from_prefab();
+        result
+    }
+}
+
+impl AudioBuses {
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.buses.keys().map(|name| name.as_str())
+    }
+
+    pub fn bus(&self, name: &str) -> Option<&AudioBusConfig> {
+        self.buses.get(name)
+    }
+
+    pub fn bus_mut(&mut self, name: &str) -> Option<&mut AudioBusConfig> {
+        let result = self.buses.get_mut(name)?;
+        self.dirty.insert(name.to_owned());
+        Some(result)
+    }
+
+    pub fn set_bus(&mut self, name: impl ToString, config: AudioBusConfig) {
+        let name = name.to_string();
+        self.removed.remove(&name);
+        self.dirty.insert(name.clone());
+        self.buses.insert(name, config);
+    }
+
+    pub fn remove_bus(&mut self, name: &str) -> Option<AudioBusConfig> {
+        let result = self.buses.remove(name)?;
+        self.dirty.remove(name);
+        self.removed.insert(name.to_owned());
+        Some(result)
+    }
+
+    pub fn volume(&self, name: &str) -> Option<Scalar> {
+        self.bus(name).map(|bus| bus.volume)
+    }
+
+    pub fn set_volume(&mut self, name: &str, value: Scalar) {
+        if let Some(bus) = self.bus_mut(name) {
+            bus.volume = value;
+        }
+    }
+
+    pub fn is_muted(&self, name: &str) -> Option<bool> {
+        self.bus(name).map(|bus| bus.muted)
+    }
+
+    pub fn set_muted(&mut self, name: &str, value: bool) {
+        if let Some(bus) = self.bus_mut(name) {
+            bus.muted = value;
+        }
+    }
+
+    pub fn effects(&self, name: &str) -> Option<&[AudioEffect]> {
+        self.bus(name).map(|bus| bus.effects.as_slice())
+    }
+
+    pub fn set_effects(&mut self, name: &str, value: Vec<AudioEffect>) {
+        if let Some(bus) = self.bus_mut(name) {
+            bus.effects = value;
+        }
+    }
+
+    /// Returns (removed, changed) bus names since last call.
+    pub(crate) fn take_changes(&mut self) -> (HashSet<String>, HashSet<String>) {
+        (
+            std::mem::take(&mut self.removed),
+            std::mem::take(&mut self.dirty),
+        )
+    }
+}
+
+impl Prefab for AudioBuses {
+    fn post_from_prefab(&mut self) {
+        self.dirty = self.buses.keys().cloned().collect();
+    }
+}
@@ -1,6 +1,7 @@
+use crate::{bus::AUDIO_BUS_MASTER, spatial::AudioSpatialConfig};
 use core::{
     ecs::Entity,
-    prefab::{Prefab, PrefabError, PrefabProxy},
+    prefab::{Prefab, PrefabComponent, PrefabError, PrefabProxy},
     state::StateToken,
     Scalar,
 };
@@ -40,6 +41,10 @@ pub struct AudioSourceConfig {
     pub volume: Scalar,
     #[serde(default)]
     pub play: bool,
+    #[serde(default = "AudioSourceConfig::default_bus")]
+    pub bus: Cow<'static, str>,
+    #[serde(default)]
+    pub spatial: Option<AudioSpatialConfig>,
 }
 
 impl AudioSourceConfig {
@@ -51,6 +56,10 @@ impl AudioSourceConfig {
         1.0
     }
 
+    fn default_bus() -> Cow<'static, str> {
+        AUDIO_BUS_MASTER.into()
+    }
+
     pub fn new(audio: Cow<'static, str>) -> Self {
         Self {
             audio,
@@ -59,6 +68,8 @@ impl AudioSourceConfig {
             playback_rate: 1.0,
             volume: 1.0,
             play: false,
+            bus: Self::default_bus(),
+            spatial: None,
         }
     }
 
@@ -91,6 +102,16 @@ impl AudioSourceConfig {
         self.play = value;
         self
     }
+
+    pub fn bus(mut self, value: Cow<'static, str>) -> Self {
+        self.bus = value;
+        self
+    }
+
+    pub fn spatial(mut self, value: Option<AudioSpatialConfig>) -> Self {
+        self.spatial = value;
+        self
+    }
 }
 
 impl Prefab for AudioSourceConfig {}
@@ -103,6 +124,11 @@ pub struct AudioSource {
     playback_rate: Scalar,
     volume: Scalar,
     play: bool,
+    bus: Cow<'static, str>,
+    spatial: Option<AudioSpatialConfig>,
+    /// (gain, pan) last sent to audio backend.
+    #[cfg_attr(not(feature = "spatial"), allow(dead_code))]
+    pub(crate) spatial_state: Option<(Scalar, Scalar)>,
     pub(crate) current_time: Option<Scalar>,
 
     pub(crate) ready: Arc<AtomicBool>,
@@ -119,6 +145,9 @@ impl Default for AudioSource {
             playback_rate: 1.0,
             volume: 1.0,
             play: false,
+            bus: AUDIO_BUS_MASTER.into(),
+            spatial: None,
+            spatial_state: None,
             current_time: None,
             ready: Arc::new(AtomicBool::new(false)),
             dirty: AudioSourceDirtyMode::None,
@@ -128,14 +157,17 @@ impl Default for AudioSource {
 
 impl From<AudioSourceConfig> for AudioSource {
     fn from(config: AudioSourceConfig) -> Self {
-        Self::new_complex(
+        let mut result = Self::new_complex(
             config.audio,
             config.streaming,
             config.looped,
             config.playback_rate,
             config.volume,
             config.play,
-        )
+        );
+        result.bus = config.bus;
+        result.spatial = config.spatial;
+        result
     }
 }
 
@@ -148,6 +180,9 @@ impl AudioSource {
             playback_rate: 1.0,
             volume: 1.0,
             play: false,
+            bus: AUDIO_BUS_MASTER.into(),
+            spatial: None,
+            spatial_state: None,
             current_time: None,
             ready: Arc::new(AtomicBool::new(false)),
             dirty: AudioSourceDirtyMode::All,
@@ -162,6 +197,9 @@ impl AudioSource {
             playback_rate: 1.0,
             volume: 1.0,
             play,
+            bus: AUDIO_BUS_MASTER.into(),
+            spatial: None,
+            spatial_state: None,
             current_time: None,
             ready: Arc::new(AtomicBool::new(false)),
             dirty: AudioSourceDirtyMode::All,
@@ -183,6 +221,9 @@ impl AudioSource {
             playback_rate,
             volume,
             play,
+            bus: AUDIO_BUS_MASTER.into(),
+            spatial: None,
+            spatial_state: None,
             current_time: None,
             ready: Arc::new(AtomicBool::new(false)),
             dirty: AudioSourceDirtyMode::All,
@@ -224,6 +265,23 @@ impl AudioSource {
         self.dirty = self.dirty.max(AudioSourceDirtyMode::Param);
     }
 
+    pub fn bus(&self) -> &str {
+        &self.bus
+    }
+
+    pub fn set_bus(&mut self, bus: Cow<'static, str>) {
+        self.bus = bus;
+        self.dirty = self.dirty.max(AudioSourceDirtyMode::Param);
+    }
+
+    pub fn spatial(&self) -> Option<&AudioSpatialConfig> {
+        self.spatial.as_ref()
+    }
+
+    pub fn set_spatial(&mut self, spatial: Option<AudioSpatialConfig>) {
+        self.spatial = spatial;
+    }
+
     pub fn current_time(&self) -> Option<Scalar> {
         self.current_time
     }
@@ -257,4 +315,44 @@ impl PrefabProxy<AudioSourcePrefabProxy> for AudioSource {
     ) -> Result<Self, PrefabError> {
         Ok(proxy.into())
     }
+
+    fn into_proxy_with_extras(
+        &self,
+        _: &HashMap<Entity, String>,
+    ) -> Result<AudioSourcePrefabProxy, PrefabError> {
+        Ok(AudioSourceConfig {
+            audio: self.audio.clone(),
+            streaming: self.streaming,
+            looped: self.looped,
+            playback_rate: self.playback_rate,
+            volume: self.volume,
+            play: self.play,
+            bus: self.bus.clone(),
+            spatial: self.spatial.clone(),
+        })
+    }
 }
+
+/// Spatial audio is computed only with `spatial` feature enabled, which requires `HaTransform`
+/// on both listener and source entities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioListener {
+    /// First enabled listener found is the one used to spatialize audio sources.
+    #[serde(default = "AudioListener::default_enabled")]
+    pub enabled: bool,
+}
+
+impl Default for AudioListener {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+impl AudioListener {
+    fn default_enabled() -> bool {
+        true
+    }
+}
+
+impl Prefab for AudioListener {}
+impl PrefabComponent for AudioListener {}
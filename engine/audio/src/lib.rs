@@ -1,16 +1,21 @@
 extern crate oxygengine_core as core;
 
 pub mod audio_asset_protocol;
+pub mod bus;
 pub mod component;
 pub mod resource;
+pub mod spatial;
 pub mod system;
 
 pub mod prelude {
-    pub use crate::{audio_asset_protocol::*, component::*, resource::*, system::*};
+    pub use crate::{
+        audio_asset_protocol::*, bus::*, component::*, resource::*, spatial::*, system::*,
+    };
 }
 
 use crate::{
-    component::{AudioSource, AudioSourcePrefabProxy},
+    bus::AudioBuses,
+    component::{AudioListener, AudioSource, AudioSourcePrefabProxy},
     resource::Audio,
     system::{audio_system, AudioSystemResources},
 };
@@ -30,6 +35,7 @@ where
     A: Audio + 'static,
 {
     builder.install_resource(data);
+    builder.install_resource(AudioBuses::default());
     builder.install_system::<AudioSystemResources<A>>("audio", audio_system::<A>, &[])?;
     Ok(())
 }
@@ -40,4 +46,5 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
 
 pub fn prefabs_installer(prefabs: &mut PrefabManager) {
     prefabs.register_component_factory_proxy::<AudioSource, AudioSourcePrefabProxy>("AudioSource");
+    prefabs.register_component_factory::<AudioListener>("AudioListener");
 }
@@ -1,3 +1,4 @@
+use crate::bus::AudioBusConfig;
 use core::{
     assets::{asset::AssetId, database::AssetsDatabase},
     ecs::Entity,
@@ -43,4 +44,12 @@ pub trait Audio: Send + Sync {
     fn get_source_state(&self, entity: Entity) -> Option<AudioState>;
     fn get_asset_id(&self, path: &str) -> Option<AssetId>;
     fn update_cache(&mut self, _assets: &AssetsDatabase) {}
+    /// Routes source to named bus. Sources of not existing buses are routed to master bus.
+    fn set_source_bus(&mut self, _entity: Entity, _bus: &str) {}
+    /// Creates or updates named bus volume, mute state and effects chain.
+    fn update_bus(&mut self, _name: &str, _config: &AudioBusConfig) {}
+    fn destroy_bus(&mut self, _name: &str) {}
+    /// Applies positional gain and stereo pan (-1.0 is left, 1.0 is right) on top of source
+    /// volume.
+    fn set_source_spatial(&mut self, _entity: Entity, _gain: Scalar, _pan: Scalar) {}
 }
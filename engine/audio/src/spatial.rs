@@ -0,0 +1,157 @@
+use core::{prefab::Prefab, Scalar};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AudioAttenuation {
+    /// No fading with distance.
+    None,
+    /// Fades linearly from full volume at min distance to silence at max distance.
+    #[default]
+    Linear,
+    /// Fades as `min / (min + rolloff * (distance - min))`.
+    Inverse,
+    /// Fades as `(distance / min) ^ -rolloff`.
+    Exponential,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioSpatialConfig {
+    #[serde(default)]
+    pub attenuation: AudioAttenuation,
+    /// Distance below which source plays at full volume.
+    #[serde(default = "AudioSpatialConfig::default_min_distance")]
+    pub min_distance: Scalar,
+    /// Distance above which source volume does not change anymore.
+    #[serde(default = "AudioSpatialConfig::default_max_distance")]
+    pub max_distance: Scalar,
+    #[serde(default = "AudioSpatialConfig::default_rolloff")]
+    pub rolloff: Scalar,
+    /// Stereo panning strength in range of 0.0 (none) to 1.0 (full).
+    #[serde(default = "AudioSpatialConfig::default_panning")]
+    pub panning: Scalar,
+}
+
+impl Default for AudioSpatialConfig {
+    fn default() -> Self {
+        Self {
+            attenuation: Default::default(),
+            min_distance: Self::default_min_distance(),
+            max_distance: Self::default_max_distance(),
+            rolloff: Self::default_rolloff(),
+            panning: Self::default_panning(),
+        }
+    }
+}
+
+impl AudioSpatialConfig {
+    fn default_min_distance() -> Scalar {
+        1.0
+    }
+
+    fn default_max_distance() -> Scalar {
+        1000.0
+    }
+
+    fn default_rolloff() -> Scalar {
+        1.0
+    }
+
+    fn default_panning() -> Scalar {
+        1.0
+    }
+
+    pub fn attenuation(mut self, value: AudioAttenuation) -> Self {
+        self.attenuation = value;
+        self
+    }
+
+    pub fn min_distance(mut self, value: Scalar) -> Self {
+        self.min_distance = value;
+        self
+    }
+
+    pub fn max_distance(mut self, value: Scalar) -> Self {
+        self.max_distance = value;
+        self
+    }
+
+    pub fn rolloff(mut self, value: Scalar) -> Self {
+        self.rolloff = value;
+        self
+    }
+
+    pub fn panning(mut self, value: Scalar) -> Self {
+        self.panning = value;
+        self
+    }
+
+    pub fn gain(&self, distance: Scalar) -> Scalar {
+        let min = self.min_distance.max(0.0);
+        let max = self.max_distance.max(min);
+        let distance = distance.clamp(min, max);
+        match self.attenuation {
+            AudioAttenuation::None => 1.0,
+            AudioAttenuation::Linear => {
+                if max > min {
+                    1.0 - (distance - min) / (max - min)
+                } else {
+                    1.0
+                }
+            }
+            AudioAttenuation::Inverse => {
+                if min > 0.0 {
+                    min / (min + self.rolloff * (distance - min))
+                } else {
+                    1.0
+                }
+            }
+            AudioAttenuation::Exponential => {
+                if min > 0.0 {
+                    (distance / min).powf(-self.rolloff)
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    /// Returns (gain, pan) of source at `x` and `y` position relative to listener, where pan
+    /// ranges from -1.0 (left) to 1.0 (right).
+    pub fn compute(&self, x: Scalar, y: Scalar) -> (Scalar, Scalar) {
+        let distance = x.hypot(y);
+        let pan = if distance > 0.0 {
+            (x / distance.max(self.min_distance)).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        (self.gain(distance), pan * self.panning.clamp(0.0, 1.0))
+    }
+}
+
+impl Prefab for AudioSpatialConfig {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_spatial() {
+        let config = AudioSpatialConfig::default()
+            .min_distance(10.0)
+            .max_distance(110.0);
+        assert_eq!(config.compute(0.0, 0.0), (1.0, 0.0));
+        assert_eq!(config.compute(5.0, 0.0), (1.0, 0.5));
+        assert_eq!(config.compute(-60.0, 0.0), (0.5, -1.0));
+        assert_eq!(config.compute(0.0, 200.0), (0.0, 0.0));
+
+        let config = config.attenuation(AudioAttenuation::Inverse).panning(0.5);
+        assert_eq!(config.compute(0.0, 20.0), (0.5, 0.0));
+        assert_eq!(config.compute(20.0, 0.0), (0.5, 0.5));
+
+        let config = config
+            .attenuation(AudioAttenuation::Exponential)
+            .rolloff(2.0);
+        assert_eq!(config.compute(0.0, -20.0), (0.25, 0.0));
+        assert_eq!(config.attenuation(AudioAttenuation::None).gain(100.0), 1.0);
+    }
+}
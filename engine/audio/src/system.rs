@@ -1,30 +1,60 @@
 use crate::{
     audio_asset_protocol::AudioAsset,
-    component::{AudioSource, AudioSourceDirtyMode},
+    bus::AudioBuses,
+    component::{AudioListener, AudioSource, AudioSourceDirtyMode},
     resource::{Audio, AudioPlayState},
 };
+#[cfg(feature = "spatial")]
+use core::ecs::World;
 use core::{
     assets::database::AssetsDatabase,
     ecs::{life_cycle::EntityChanges, Comp, Universe, WorldRef},
 };
+#[cfg(feature = "spatial")]
+use oxygengine_ha_renderer::components::transform::HaTransform;
 
+#[cfg(feature = "spatial")]
 pub type AudioSystemResources<'a, A> = (
     WorldRef,
     &'a EntityChanges,
     &'a AssetsDatabase,
     &'a mut A,
+    &'a mut AudioBuses,
     Comp<&'a mut AudioSource>,
+    Comp<&'a AudioListener>,
+    Comp<&'a HaTransform>,
+);
+
+#[cfg(not(feature = "spatial"))]
+pub type AudioSystemResources<'a, A> = (
+    WorldRef,
+    &'a EntityChanges,
+    &'a AssetsDatabase,
+    &'a mut A,
+    &'a mut AudioBuses,
+    Comp<&'a mut AudioSource>,
+    Comp<&'a AudioListener>,
 );
 
 pub fn audio_system<A>(universe: &mut Universe)
 where
     A: Audio + 'static,
 {
-    let (world, changes, assets, mut audio, ..) =
+    let (world, changes, assets, mut audio, mut buses, ..) =
         universe.query_resources::<AudioSystemResources<A>>();
 
     audio.update_cache(&assets);
 
+    let (removed, changed) = buses.take_changes();
+    for name in removed {
+        audio.destroy_bus(&name);
+    }
+    for name in changed {
+        if let Some(config) = buses.bus(&name) {
+            audio.update_bus(&name, config);
+        }
+    }
+
     for entity in changes.despawned() {
         audio.destroy_source(entity);
     }
@@ -45,6 +75,7 @@ where
                                 source.is_playing(),
                                 source.ready.clone(),
                             );
+                            audio.set_source_bus(entity, source.bus());
                             source.dirty = AudioSourceDirtyMode::None;
                         }
                     }
@@ -61,6 +92,7 @@ where
                         None
                     },
                 );
+                audio.set_source_bus(entity, source.bus());
                 source.dirty = AudioSourceDirtyMode::None;
             }
         }
@@ -84,4 +116,40 @@ where
             }
         }
     }
+
+    #[cfg(feature = "spatial")]
+    update_spatial(&world, &mut *audio);
+}
+
+#[cfg(feature = "spatial")]
+fn update_spatial<A>(world: &World, audio: &mut A)
+where
+    A: Audio,
+{
+    let listener = world
+        .query::<(&AudioListener, &HaTransform)>()
+        .iter()
+        .find(|(_, (listener, _))| listener.enabled)
+        .map(|(_, (_, transform))| transform.inverse_world_matrix());
+
+    for (entity, (source, transform)) in world
+        .query::<(&mut AudioSource, Option<&HaTransform>)>()
+        .iter()
+    {
+        if !audio.has_source(entity) {
+            continue;
+        }
+        let spatial_state = match (source.spatial(), transform, listener) {
+            (Some(spatial), Some(transform), Some(listener)) => {
+                let position = listener.mul_point(transform.get_world_origin());
+                Some(spatial.compute(position.x, position.y))
+            }
+            _ => None,
+        };
+        if spatial_state != source.spatial_state {
+            source.spatial_state = spatial_state;
+            let (gain, pan) = spatial_state.unwrap_or((1.0, 0.0));
+            audio.set_source_spatial(entity, gain, pan);
+        }
+    }
 }
@@ -64,12 +64,34 @@ impl AssetPipelinePlugin {
     where
         T: for<'de> Deserialize<'de> + ParamsFromArgs,
     {
+        if Self::validation_requested() {
+            Self::validate::<T>();
+            return Ok(());
+        }
         let output = f(AssetPipelineInput::<T>::consume())?;
         serde_json::to_writer(std::io::stdout(), &output)
             .expect("Could not serialize output content");
         let _ = std::io::stdout().flush();
         Ok(())
     }
+
+    /// Plugin is asked to only validate params with: `plugin -- -- -- validate`.
+    fn validation_requested() -> bool {
+        std::env::args().skip(1).eq(["--", "--", "--", "validate"])
+    }
+
+    /// Reads params from input stream and writes `Result<(), String>` with deserialization error.
+    fn validate<T>()
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let result = serde_json::from_reader::<_, T>(std::io::stdin())
+            .map(|_| ())
+            .map_err(|error| error.to_string());
+        serde_json::to_writer(std::io::stdout(), &result)
+            .expect("Could not serialize validation result");
+        let _ = std::io::stdout().flush();
+    }
 }
 
 pub struct AssetPipelineInput<T> {
@@ -70,4 +70,18 @@ impl Asset {
     {
         replace(&mut self.data, Box::new(data))
     }
+
+    pub fn set_boxed(&mut self, data: Box<dyn Any + Send + Sync>) -> Box<dyn Any + Send + Sync> {
+        replace(&mut self.data, data)
+    }
+
+    /// Swaps data and returns previous data as asset with the same id, so it can be unloaded.
+    pub(crate) fn replace_boxed(&mut self, data: Box<dyn Any + Send + Sync>) -> Self {
+        Self {
+            id: self.id,
+            protocol: self.protocol.to_owned(),
+            path: self.path.to_owned(),
+            data: replace(&mut self.data, data),
+        }
+    }
 }
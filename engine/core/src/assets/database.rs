@@ -1,11 +1,21 @@
 use crate::{
     assets::{
         asset::{Asset, AssetId},
+        handle::AssetHandle,
         protocol::{AssetLoadResult, AssetProtocol, AssetVariant, Meta},
     },
-    fetch::{FetchEngine, FetchProcess, FetchStatus},
+    fetch::{FetchCancelReason, FetchEngine, FetchProcess, FetchStatus},
+    Scalar,
 };
-use std::{any::TypeId, collections::HashMap};
+use std::{
+    any::{Any, TypeId},
+    cmp::Reverse,
+    collections::HashMap,
+    sync::Arc,
+};
+
+/// Assets with higher priority are fetched first when loading queue is limited.
+pub type AssetLoadPriority = i32;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LoadStatus {
@@ -33,15 +43,28 @@ impl AssetsDatabaseErrorReporter for LoggerAssetsDatabaseErrorReporter {
 
 pub struct AssetsDatabase {
     pub max_bytes_per_frame: Option<usize>,
+    /// Limit of assets being fetched at the same time, rest of them waits in loading queue.
+    pub max_loading_count: Option<usize>,
     fetch_engines: Vec<Box<dyn FetchEngine>>,
     protocols: HashMap<String, Box<dyn AssetProtocol>>,
     assets: HashMap<AssetId, (String, Asset)>,
     table: HashMap<String, AssetId>,
+    /// [(asset path, priority)]
+    queue: Vec<(String, AssetLoadPriority)>,
+    /// {asset path: priority} of requested assets that are not yet done.
+    pending: HashMap<String, AssetLoadPriority>,
+    pending_total: usize,
+    pending_done: usize,
     loading: HashMap<String, (String, Box<FetchProcess>)>,
     #[allow(clippy::type_complexity)]
     yielded: HashMap<String, (String, Meta, Vec<(String, String)>)>,
     lately_loaded: Vec<(String, AssetId)>,
     lately_unloaded: Vec<(String, AssetId)>,
+    lately_reloaded: Vec<(String, AssetId)>,
+    /// {asset path: [paths of assets it yielded on]}
+    dependencies: HashMap<String, Vec<String>>,
+    /// {asset path: handles counter}
+    references: HashMap<String, Arc<String>>,
     error_reporters: HashMap<TypeId, Box<dyn AssetsDatabaseErrorReporter>>,
     defer_lately_cleanup: bool,
 }
@@ -53,14 +76,22 @@ impl AssetsDatabase {
     {
         Self {
             max_bytes_per_frame: None,
+            max_loading_count: None,
             fetch_engines: vec![Box::new(fetch_engine)],
             protocols: Default::default(),
             assets: Default::default(),
             table: Default::default(),
+            queue: Default::default(),
+            pending: Default::default(),
+            pending_total: 0,
+            pending_done: 0,
             loading: Default::default(),
             yielded: Default::default(),
             lately_loaded: vec![],
             lately_unloaded: vec![],
+            lately_reloaded: vec![],
+            dependencies: Default::default(),
+            references: Default::default(),
             error_reporters: Default::default(),
             defer_lately_cleanup: true,
         }
@@ -96,6 +127,14 @@ impl AssetsDatabase {
         self.assets.keys().copied().collect()
     }
 
+    pub fn queued_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn queued_paths(&self) -> Vec<String> {
+        self.queue.iter().map(|(path, _)| path.to_owned()).collect()
+    }
+
     pub fn loading_count(&self) -> usize {
         self.loading.len()
     }
@@ -178,8 +217,99 @@ impl AssetsDatabase {
             .filter_map(move |(prot, id)| if protocol == prot { Some(id) } else { None })
     }
 
+    pub fn lately_reloaded(&self) -> impl Iterator<Item = &AssetId> {
+        self.lately_reloaded.iter().map(|(_, id)| id)
+    }
+
+    pub fn lately_reloaded_paths(&self) -> impl Iterator<Item = &str> {
+        self.lately_reloaded
+            .iter()
+            .filter_map(|(_, id)| self.path_by_id(*id))
+    }
+
+    pub fn lately_reloaded_protocol<'a>(
+        &'a self,
+        protocol: &'a str,
+    ) -> impl Iterator<Item = &'a AssetId> {
+        self.lately_reloaded
+            .iter()
+            .filter_map(move |(prot, id)| if protocol == prot { Some(id) } else { None })
+    }
+
+    /// Paths of assets that given asset yielded on while loading.
+    pub fn dependencies(&self, path: &str) -> impl Iterator<Item = &str> {
+        let path = Self::clean_path(path);
+        self.dependencies
+            .get(path)
+            .into_iter()
+            .flat_map(|list| list.iter().map(|p| p.as_str()))
+    }
+
+    /// Paths of assets that yielded on given asset while loading.
+    pub fn dependents<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a str> {
+        let path = Self::clean_path(path);
+        self.dependencies.iter().filter_map(move |(p, list)| {
+            if list.iter().any(|d| d == path) {
+                Some(p.as_str())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Progress of all assets requested since database was last ready, in range from 0 to 1.
+    pub fn loading_progress(&self) -> Scalar {
+        if self.pending_total == 0 {
+            return 1.0;
+        }
+        let fetched = self
+            .loading
+            .values()
+            .map(|(_, reader)| Self::fetch_progress(reader))
+            .sum::<Scalar>();
+        let progress = self.pending_done as Scalar + fetched + self.yielded.len() as Scalar;
+        (progress / self.pending_total as Scalar).min(1.0)
+    }
+
+    /// Progress of requested asset, in range from 0 to 1.
+    pub fn asset_loading_progress(&self, path: &str) -> Option<Scalar> {
+        let path = Self::clean_path(path);
+        if !self.pending.contains_key(path) {
+            return self.table.get(path).map(|_| 1.0);
+        }
+        let (prot, subpath) = path.split_once("://")?;
+        match self.loading.get(subpath) {
+            Some((p, reader)) if p == prot => Some(Self::fetch_progress(reader)),
+            _ => match self.yielded.get(subpath) {
+                Some((p, _, _)) if p == prot => Some(1.0),
+                _ => Some(0.0),
+            },
+        }
+    }
+
+    /// Number of living handles to given asset.
+    pub fn references_count(&self, path: &str) -> usize {
+        let path = Self::clean_path(path);
+        self.references
+            .get(path)
+            .map(|counter| Arc::strong_count(counter) - 1)
+            .unwrap_or_default()
+    }
+
+    /// Paths of loaded assets that has no living handles.
+    pub fn unreferenced_paths(&self) -> Vec<String> {
+        let mut result = self
+            .table
+            .keys()
+            .filter(|path| self.references_count(path) == 0)
+            .cloned()
+            .collect::<Vec<_>>();
+        result.sort();
+        result
+    }
+
     pub fn is_ready(&self) -> bool {
-        self.loading.is_empty() && self.yielded.is_empty()
+        self.queue.is_empty() && self.loading.is_empty() && self.yielded.is_empty()
     }
 
     pub fn are_ready<I, S>(&self, iter: I) -> bool
@@ -190,6 +320,7 @@ impl AssetsDatabase {
         iter.into_iter().all(|path| {
             let path = Self::clean_path(path.as_ref());
             self.table.contains_key(path)
+                && !self.pending.contains_key(path)
                 && !self.loading.contains_key(path)
                 && !self.yielded.contains_key(path)
         })
@@ -245,33 +376,186 @@ impl AssetsDatabase {
     }
 
     pub fn load(&mut self, path: &str) -> Result<(), LoadStatus> {
+        self.load_with_priority(path, 0)
+    }
+
+    /// Requests asset to load. When `max_loading_count` is reached, asset waits in loading queue
+    /// until assets with higher priority get fetched. Assets that given asset yields on inherit
+    /// its priority. Requesting already requested asset only raises its priority.
+    pub fn load_with_priority(
+        &mut self,
+        path: &str,
+        priority: AssetLoadPriority,
+    ) -> Result<(), LoadStatus> {
         if self.table.contains_key(path) {
             return Ok(());
         }
+        self.request(path, priority)
+    }
+
+    /// Requests asset to load and returns handle to it. Asset gets unloaded (or its loading gets
+    /// canceled) once all of its handles get dropped.
+    pub fn load_handle<T>(&mut self, path: &str) -> Result<AssetHandle<T>, LoadStatus>
+    where
+        T: Any + Send + Sync,
+    {
+        self.load(path)?;
+        Ok(self.make_handle(path))
+    }
+
+    /// Returns handle to already loaded asset if it stores data of given type.
+    pub fn handle<T>(&mut self, path: &str) -> Option<AssetHandle<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        if self.asset_by_path(path)?.is::<T>() {
+            Some(self.make_handle(path))
+        } else {
+            None
+        }
+    }
+
+    pub fn handle_by_id<T>(&mut self, id: AssetId) -> Option<AssetHandle<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        let path = self.path_by_id(id)?.to_owned();
+        self.handle(&path)
+    }
+
+    fn make_handle<T>(&mut self, path: &str) -> AssetHandle<T>
+    where
+        T: Any + Send + Sync,
+    {
         let path = Self::clean_path(path);
-        let parts = path.split("://").take(2).collect::<Vec<_>>();
-        if parts.len() == 2 {
-            let prot = parts[0];
-            let subpath = parts[1];
-            if self.protocols.contains_key(prot) {
-                if let Some(engine) = self.fetch_engine_mut() {
-                    let reader = engine.fetch(subpath);
-                    match reader {
-                        Ok(reader) => {
-                            self.loading
-                                .insert(subpath.to_owned(), (prot.to_owned(), reader));
-                            Ok(())
-                        }
-                        Err(status) => Err(LoadStatus::FetchError(status)),
+        let counter = self
+            .references
+            .entry(path.to_owned())
+            .or_insert_with(|| Arc::new(path.to_owned()));
+        AssetHandle::new(counter.clone())
+    }
+
+    /// Fetches already loaded asset again and replaces its data in place, keeping its `AssetId`.
+    /// Once done, asset gets reported by `lately_reloaded` and every asset that yielded on it
+    /// gets reloaded too. Reloading not loaded asset is the same as loading it.
+    pub fn reload(&mut self, path: &str) -> Result<(), LoadStatus> {
+        self.request(path, 0)
+    }
+
+    /// Cancels loading of requested asset, wherever it is: in loading queue, being fetched or
+    /// waiting for its dependencies. Returns `true` if asset was requested.
+    pub fn cancel(&mut self, path: &str) -> bool {
+        let path = Self::clean_path(path);
+        if self.pending.remove(path).is_none() {
+            return false;
+        }
+        self.pending_total = self.pending_total.saturating_sub(1);
+        self.queue.retain(|(p, _)| p != path);
+        if let Some((prot, subpath)) = path.split_once("://") {
+            if matches!(self.loading.get(subpath), Some((p, _)) if p == prot) {
+                if let Some((_, reader)) = self.loading.remove(subpath) {
+                    if let Some(engine) = self.fetch_engine_mut() {
+                        engine.cancel(*reader);
                     }
-                } else {
-                    Err(LoadStatus::NoFetchEngine)
                 }
-            } else {
-                Err(LoadStatus::UnknownProtocol(prot.to_owned()))
+            }
+            if matches!(self.yielded.get(subpath), Some((p, _, _)) if p == prot) {
+                self.yielded.remove(subpath);
+            }
+        }
+        true
+    }
+
+    fn request(&mut self, path: &str, priority: AssetLoadPriority) -> Result<(), LoadStatus> {
+        let path = Self::clean_path(path);
+        let (prot, _) = match path.split_once("://") {
+            Some(parts) => parts,
+            None => return Err(LoadStatus::InvalidPath(path.to_owned())),
+        };
+        if !self.protocols.contains_key(prot) {
+            return Err(LoadStatus::UnknownProtocol(prot.to_owned()));
+        }
+        if !self.has_fetch_engine() {
+            return Err(LoadStatus::NoFetchEngine);
+        }
+        if let Some(current) = self.pending.get_mut(path) {
+            if priority > *current {
+                *current = priority;
+                if let Some((_, queued)) = self.queue.iter_mut().find(|(p, _)| p == path) {
+                    *queued = priority;
+                }
+            }
+            return Ok(());
+        }
+        self.pending.insert(path.to_owned(), priority);
+        self.pending_total += 1;
+        if self.queue.is_empty() && !self.is_loading_limit_reached() {
+            let result = self.fetch(path);
+            if result.is_err() {
+                self.pending.remove(path);
+                self.pending_total -= 1;
+            }
+            result
+        } else {
+            self.queue.push((path.to_owned(), priority));
+            Ok(())
+        }
+    }
+
+    fn fetch(&mut self, path: &str) -> Result<(), LoadStatus> {
+        let (prot, subpath) = match path.split_once("://") {
+            Some(parts) => parts,
+            None => return Err(LoadStatus::InvalidPath(path.to_owned())),
+        };
+        if let Some(engine) = self.fetch_engine_mut() {
+            match engine.fetch(subpath) {
+                Ok(reader) => {
+                    self.loading
+                        .insert(subpath.to_owned(), (prot.to_owned(), reader));
+                    Ok(())
+                }
+                Err(status) => Err(LoadStatus::FetchError(status)),
             }
         } else {
-            Err(LoadStatus::InvalidPath(path.to_owned()))
+            Err(LoadStatus::NoFetchEngine)
+        }
+    }
+
+    fn is_loading_limit_reached(&self) -> bool {
+        self.max_loading_count
+            .map(|limit| self.loading.len() >= limit)
+            .unwrap_or_default()
+    }
+
+    fn dispatch_queue(&mut self) {
+        if self.queue.is_empty() {
+            return;
+        }
+        self.queue.sort_by_key(|(_, priority)| Reverse(*priority));
+        while !self.queue.is_empty() && !self.is_loading_limit_reached() {
+            let (path, _) = self.queue.remove(0);
+            if let Err(status) = self.fetch(&path) {
+                self.finish(&path);
+                let (prot, subpath) = path.split_once("://").unwrap_or(("", path.as_str()));
+                let message = format!("Could not fetch asset: {:?}", status);
+                for reporter in self.error_reporters.values_mut() {
+                    reporter.on_report(prot, subpath, &message);
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, path: &str) {
+        if self.pending.remove(path).is_some() {
+            self.pending_done += 1;
+        }
+    }
+
+    fn fetch_progress(reader: &FetchProcess) -> Scalar {
+        match reader.status() {
+            FetchStatus::InProgress(progress) => progress,
+            FetchStatus::Done | FetchStatus::Read => 1.0,
+            _ => 0.0,
         }
     }
 
@@ -285,13 +569,62 @@ impl AssetsDatabase {
         id
     }
 
+    fn insert_or_replace(&mut self, protocol: &str, path: &str, data: Box<dyn Any + Send + Sync>) {
+        let full_path = format!("{}://{}", protocol, path);
+        self.finish(&full_path);
+        if let Some(id) = self.table.get(&full_path).copied() {
+            if let Some((_, asset)) = self.assets.get_mut(&id) {
+                let old = asset.replace_boxed(data);
+                self.lately_reloaded.push((protocol.to_owned(), id));
+                if let Some(prot) = self.protocols.get_mut(protocol) {
+                    if let Some(list) = prot.on_unload(&old) {
+                        // assets that new version still depends on have to stay loaded.
+                        let dependencies = self.dependencies.get(&full_path);
+                        let list = list
+                            .into_iter()
+                            .filter(|variant| {
+                                let path = match variant {
+                                    AssetVariant::Id(id) => match self.path_by_id(*id) {
+                                        Some(path) => path,
+                                        None => return false,
+                                    },
+                                    AssetVariant::Path(path) => Self::clean_path(path),
+                                };
+                                !dependencies
+                                    .map(|list| list.iter().any(|item| item == path))
+                                    .unwrap_or_default()
+                            })
+                            .collect::<Vec<_>>();
+                        self.remove_unreferenced_variants(&list);
+                    }
+                }
+                let dependents = self
+                    .dependents(&full_path)
+                    .map(|p| p.to_owned())
+                    .collect::<Vec<_>>();
+                for path in dependents {
+                    if let Err(status) = self.reload(&path) {
+                        let (prot, subpath) = path.split_once("://").unwrap_or(("", path.as_str()));
+                        let message = format!("Could not reload dependent asset: {:?}", status);
+                        for reporter in self.error_reporters.values_mut() {
+                            reporter.on_report(prot, subpath, &message);
+                        }
+                    }
+                }
+                return;
+            }
+        }
+        self.insert(Asset::new_boxed(protocol, path, data));
+    }
+
     pub fn remove_by_id(&mut self, id: AssetId) -> Option<Asset> {
         if let Some((path, asset)) = self.assets.remove(&id) {
             self.table.remove(&path);
+            self.dependencies.remove(&path);
             self.lately_unloaded.push((asset.protocol().to_owned(), id));
             if let Some(protocol) = self.protocols.get_mut(asset.protocol()) {
                 if let Some(list) = protocol.on_unload(&asset) {
-                    self.remove_by_variants(&list);
+                    self.remove_unreferenced_variants(&list);
                 }
             }
             Some(asset)
@@ -303,11 +636,12 @@ impl AssetsDatabase {
     pub fn remove_by_path(&mut self, path: &str) -> Option<Asset> {
         let path = Self::clean_path(path);
         if let Some(id) = self.table.remove(path) {
+            self.dependencies.remove(path);
             if let Some((_, asset)) = self.assets.remove(&id) {
                 self.lately_unloaded.push((asset.protocol().to_owned(), id));
                 if let Some(protocol) = self.protocols.get_mut(asset.protocol()) {
                     if let Some(list) = protocol.on_unload(&asset) {
-                        self.remove_by_variants(&list);
+                        self.remove_unreferenced_variants(&list);
                     }
                 }
                 return Some(asset);
@@ -325,6 +659,21 @@ impl AssetsDatabase {
         }
     }
 
+    fn remove_unreferenced_variants(&mut self, variants: &[AssetVariant]) {
+        for v in variants {
+            let path = match v {
+                AssetVariant::Id(id) => match self.path_by_id(*id) {
+                    Some(path) => path.to_owned(),
+                    None => continue,
+                },
+                AssetVariant::Path(path) => Self::clean_path(path).to_owned(),
+            };
+            if self.references_count(&path) == 0 {
+                self.remove_by_path(&path);
+            }
+        }
+    }
+
     pub fn id_by_path(&self, path: &str) -> Option<AssetId> {
         let path = Self::clean_path(path);
         self.table.get(path).cloned()
@@ -358,7 +707,10 @@ impl AssetsDatabase {
         } else {
             self.lately_loaded.clear();
             self.lately_unloaded.clear();
+            self.lately_reloaded.clear();
         }
+        self.unload_unreferenced();
+        self.dispatch_queue();
         let to_dispatch = {
             let mut bytes_read = 0;
             self.loading
@@ -378,17 +730,18 @@ impl AssetsDatabase {
             if let Some(protocol) = self.protocols.get_mut(&prot) {
                 match protocol.on_load_with_path(&path, data) {
                     AssetLoadResult::Data(data) => {
-                        let asset = Asset::new_boxed(&prot, &path, data);
-                        self.insert(asset);
+                        self.insert_or_replace(&prot, &path, data);
                     }
                     AssetLoadResult::Yield(meta, list) => {
+                        let priority = self.priority(&prot, &path);
                         let list = list
                             .into_iter()
-                            .filter(|(_, path)| self.load(path).is_ok())
+                            .filter(|(_, path)| self.load_with_priority(path, priority).is_ok())
                             .collect();
                         self.yielded.insert(path, (prot, meta, list));
                     }
                     AssetLoadResult::Error(message) => {
+                        self.finish(&format!("{}://{}", prot, path));
                         for reporter in self.error_reporters.values_mut() {
                             reporter.on_report(&prot, &path, &message);
                         }
@@ -396,18 +749,36 @@ impl AssetsDatabase {
                 }
             }
         }
-        self.loading.retain(|_, (_, reader)| {
-            matches!(
-                reader.status(),
-                FetchStatus::InProgress(_) | FetchStatus::Done
-            )
+        let mut removed = vec![];
+        self.loading.retain(|path, (prot, reader)| {
+            let status = reader.status();
+            let keep = matches!(status, FetchStatus::InProgress(_) | FetchStatus::Done);
+            if !keep {
+                removed.push((prot.to_owned(), path.to_owned(), status));
+            }
+            keep
         });
+        for (prot, path, status) in removed {
+            // read assets that yielded are still pending until their dependencies get loaded.
+            if matches!(self.yielded.get(&path), Some((p, _, _)) if p == &prot) {
+                continue;
+            }
+            self.finish(&format!("{}://{}", prot, path));
+            let message = match status {
+                FetchStatus::Empty => "Asset fetch did not start",
+                FetchStatus::Canceled(FetchCancelReason::Error) => "Asset fetch failed",
+                _ => continue,
+            };
+            for reporter in self.error_reporters.values_mut() {
+                reporter.on_report(&prot, &path, message);
+            }
+        }
         let yielded = std::mem::take(&mut self.yielded);
         for (path, (prot, meta, list)) in yielded {
             if list.iter().all(|(_, path)| self.table.contains_key(path)) {
                 let ptr = self as *const Self;
                 if let Some(protocol) = self.protocols.get_mut(&prot) {
-                    let list = list
+                    let assets = list
                         .iter()
                         .map(|(key, path)| unsafe {
                             let asset = &(*ptr).table[path];
@@ -415,19 +786,24 @@ impl AssetsDatabase {
                             (key.as_str(), asset)
                         })
                         .collect::<Vec<_>>();
-                    match protocol.on_resume(meta, &list) {
+                    match protocol.on_resume(meta, &assets) {
                         AssetLoadResult::Data(data) => {
-                            let asset = Asset::new_boxed(&prot, &path, data);
-                            self.insert(asset);
+                            self.dependencies.insert(
+                                format!("{}://{}", prot, path),
+                                list.into_iter().map(|(_, path)| path).collect(),
+                            );
+                            self.insert_or_replace(&prot, &path, data);
                         }
                         AssetLoadResult::Yield(meta, list) => {
+                            let priority = self.priority(&prot, &path);
                             let list = list
                                 .into_iter()
-                                .filter(|(_, path)| self.load(path).is_ok())
+                                .filter(|(_, path)| self.load_with_priority(path, priority).is_ok())
                                 .collect();
                             self.yielded.insert(path, (prot, meta, list));
                         }
                         AssetLoadResult::Error(message) => {
+                            self.finish(&format!("{}://{}", prot, path));
                             for reporter in self.error_reporters.values_mut() {
                                 reporter.on_report(&prot, &path, &message);
                             }
@@ -438,6 +814,32 @@ impl AssetsDatabase {
                 self.yielded.insert(path, (prot, meta, list));
             }
         }
+        if self.pending.is_empty() {
+            self.pending_total = 0;
+            self.pending_done = 0;
+        }
+    }
+
+    fn unload_unreferenced(&mut self) {
+        let unreferenced = self
+            .references
+            .iter()
+            .filter(|(_, counter)| Arc::strong_count(counter) == 1)
+            .map(|(path, _)| path.to_owned())
+            .collect::<Vec<_>>();
+        for path in unreferenced {
+            self.references.remove(&path);
+            // asset might be both loaded and pending reload, so we need to do both.
+            self.cancel(&path);
+            self.remove_by_path(&path);
+        }
+    }
+
+    fn priority(&self, protocol: &str, path: &str) -> AssetLoadPriority {
+        self.pending
+            .get(&format!("{}://{}", protocol, path))
+            .copied()
+            .unwrap_or_default()
     }
 
     fn clean_path(path: &str) -> &str {
@@ -543,4 +945,188 @@ mod tests {
         assert_eq!(database.yielded_count(), 0);
         assert_eq!(database.yielded_deps_count(), 0);
     }
+
+    #[test]
+    fn test_reload() {
+        let list = serde_json::to_string(&MetaAsset::default().with_target("txt://a.txt")).unwrap();
+        let mut fetch_engine = engines::map::MapFetchEngine::default();
+        fetch_engine
+            .map
+            .insert("assets.asset".to_owned(), list.clone().into_bytes());
+        fetch_engine.map.insert("a.txt".to_owned(), b"A".to_vec());
+
+        let mut database = AssetsDatabase::new(fetch_engine);
+        database.register(TextAssetProtocol);
+        database.register(MetaAssetProtocol);
+        assert_eq!(database.load("meta://assets.asset"), Ok(()));
+        for _ in 0..2 {
+            database.process();
+        }
+        assert_eq!(database.loaded_count(), 2);
+        let text_id = database.id_by_path("txt://a.txt").unwrap();
+        let meta_id = database.id_by_path("meta://assets.asset").unwrap();
+        assert_eq!(
+            database.dependents("txt://a.txt").collect::<Vec<_>>(),
+            vec!["meta://assets.asset"]
+        );
+
+        let mut fetch_engine = engines::map::MapFetchEngine::default();
+        fetch_engine
+            .map
+            .insert("assets.asset".to_owned(), list.into_bytes());
+        fetch_engine.map.insert("a.txt".to_owned(), b"B".to_vec());
+        database.pop_fetch_engine();
+        database.push_fetch_engine(Box::new(fetch_engine));
+        assert_eq!(database.reload("txt://a.txt"), Ok(()));
+        database.process();
+        assert_eq!(database.loaded_count(), 2);
+        assert_eq!(database.id_by_path("txt://a.txt"), Some(text_id));
+        assert_eq!(
+            database
+                .asset_by_id(text_id)
+                .unwrap()
+                .get::<TextAsset>()
+                .unwrap()
+                .get(),
+            "B"
+        );
+        assert_eq!(
+            database.lately_reloaded().collect::<Vec<_>>(),
+            vec![&text_id]
+        );
+        assert_eq!(database.loading_count(), 1);
+
+        database.process();
+        assert!(database.is_ready());
+        assert_eq!(database.id_by_path("meta://assets.asset"), Some(meta_id));
+        // targets still listed by reloaded meta asset are not unloaded with its previous version.
+        assert_eq!(database.loaded_count(), 2);
+        assert_eq!(
+            database.lately_reloaded().collect::<Vec<_>>(),
+            vec![&meta_id]
+        );
+    }
+
+    #[test]
+    fn test_loading_queue() {
+        let mut fetch_engine = engines::map::MapFetchEngine::default();
+        fetch_engine.map.insert("a.txt".to_owned(), b"A".to_vec());
+        fetch_engine.map.insert("b.txt".to_owned(), b"B".to_vec());
+        fetch_engine.map.insert("c.txt".to_owned(), b"C".to_vec());
+
+        let mut database = AssetsDatabase::new(fetch_engine);
+        database.max_loading_count = Some(1);
+        database.register(TextAssetProtocol);
+        assert_eq!(database.load("txt://a.txt"), Ok(()));
+        assert_eq!(database.load("txt://b.txt"), Ok(()));
+        assert_eq!(database.load_with_priority("txt://c.txt", 10), Ok(()));
+        assert_eq!(database.loading_count(), 1);
+        assert_eq!(database.queued_count(), 2);
+        assert_eq!(database.loading_progress(), 1.0 / 3.0);
+
+        database.process();
+        assert_eq!(database.loaded_count(), 1);
+        assert_eq!(database.queued_count(), 2);
+        assert_eq!(database.loading_progress(), 1.0 / 3.0);
+
+        database.process();
+        assert_eq!(database.loaded_count(), 2);
+        assert!(database.asset_by_path("txt://c.txt").is_some());
+        assert_eq!(database.queued_paths(), vec!["txt://b.txt".to_owned()]);
+        assert_eq!(database.loading_progress(), 2.0 / 3.0);
+
+        assert!(database.cancel("txt://b.txt"));
+        assert!(!database.cancel("txt://b.txt"));
+        assert_eq!(database.queued_count(), 0);
+        assert_eq!(database.loading_progress(), 1.0);
+        assert!(database.is_ready());
+
+        database.process();
+        assert_eq!(database.loaded_count(), 2);
+        assert!(database.asset_by_path("txt://b.txt").is_none());
+    }
+
+    #[test]
+    fn test_failed_loading() {
+        struct FailingFetchEngine;
+
+        impl FetchEngine for FailingFetchEngine {
+            fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus> {
+                Ok(Box::new(match path {
+                    "empty.txt" => FetchProcess::new(),
+                    _ => FetchProcess::new_cancel(FetchCancelReason::Error),
+                }))
+            }
+        }
+
+        let mut database = AssetsDatabase::new(FailingFetchEngine);
+        database.register(TextAssetProtocol);
+        assert_eq!(database.load("txt://empty.txt"), Ok(()));
+        assert_eq!(database.load("txt://canceled.txt"), Ok(()));
+        assert_eq!(database.loading_count(), 2);
+        assert_eq!(database.loading_progress(), 0.0);
+
+        database.process();
+        assert!(database.is_ready());
+        assert_eq!(database.loading_progress(), 1.0);
+        assert_eq!(database.loaded_count(), 0);
+    }
+
+    #[test]
+    fn test_handles() {
+        let list = serde_json::to_string(
+            &MetaAsset::default()
+                .with_target("txt://a.txt")
+                .with_target("txt://b.txt"),
+        )
+        .unwrap();
+        let mut fetch_engine = engines::map::MapFetchEngine::default();
+        fetch_engine
+            .map
+            .insert("assets.asset".to_owned(), list.into_bytes());
+        fetch_engine.map.insert("a.txt".to_owned(), b"A".to_vec());
+        fetch_engine.map.insert("b.txt".to_owned(), b"B".to_vec());
+
+        let mut database = AssetsDatabase::new(fetch_engine);
+        database.register(TextAssetProtocol);
+        database.register(MetaAssetProtocol);
+        let meta = database
+            .load_handle::<MetaAsset>("meta://assets.asset")
+            .unwrap();
+        for _ in 0..2 {
+            database.process();
+        }
+        assert_eq!(database.loaded_count(), 3);
+        assert!(meta.is_ready(&database));
+        assert!(database.handle::<MetaAsset>("txt://a.txt").is_none());
+        let text = database.handle::<TextAsset>("txt://a.txt").unwrap();
+        assert_eq!(text.get(&database).unwrap().get(), "A");
+        let text2 = text.clone();
+        assert_eq!(database.references_count("txt://a.txt"), 2);
+        assert_eq!(
+            database.unreferenced_paths(),
+            vec!["txt://b.txt".to_owned()]
+        );
+
+        drop(meta);
+        database.process();
+        assert_eq!(database.loaded_count(), 1);
+        assert!(text2.is_ready(&database));
+
+        drop(text);
+        drop(text2);
+        database.process();
+        assert_eq!(database.loaded_count(), 0);
+        assert_eq!(database.references_count("txt://a.txt"), 0);
+
+        let text = database.load_handle::<TextAsset>("txt://a.txt").unwrap();
+        database.process();
+        assert!(text.is_ready(&database));
+        assert_eq!(database.reload("txt://a.txt"), Ok(()));
+        drop(text);
+        database.process();
+        assert_eq!(database.loaded_count(), 0);
+        assert_eq!(database.loading_count(), 0);
+        assert!(database.is_ready());
+    }
 }
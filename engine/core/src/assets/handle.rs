@@ -0,0 +1,62 @@
+use crate::assets::{
+    asset::{Asset, AssetId},
+    database::AssetsDatabase,
+};
+use std::{any::Any, marker::PhantomData, sync::Arc};
+
+/// Typed reference to asset stored in `AssetsDatabase`.
+/// Asset gets unloaded once last handle to it gets dropped.
+pub struct AssetHandle<T> {
+    path: Arc<String>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for AssetHandle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssetHandle")
+            .field("path", &self.path.as_str())
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T> AssetHandle<T>
+where
+    T: Any + Send + Sync,
+{
+    pub(crate) fn new(path: Arc<String>) -> Self {
+        Self {
+            path,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn id(&self, database: &AssetsDatabase) -> Option<AssetId> {
+        database.id_by_path(&self.path)
+    }
+
+    pub fn is_ready(&self, database: &AssetsDatabase) -> bool {
+        self.get(database).is_some()
+    }
+
+    pub fn asset<'a>(&self, database: &'a AssetsDatabase) -> Option<&'a Asset> {
+        database.asset_by_path(&self.path)
+    }
+
+    pub fn get<'a>(&self, database: &'a AssetsDatabase) -> Option<&'a T> {
+        self.asset(database)?.get::<T>()
+    }
+}
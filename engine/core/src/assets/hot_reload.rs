@@ -0,0 +1,83 @@
+#![cfg(not(feature = "web"))]
+
+use crate::{assets::database::AssetsDatabase, ecs::Universe, fetch::engines::fs::FsFetchEngine};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::metadata,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Watches files of assets loaded from file system and reloads them when they get modified.
+pub struct FsAssetsWatcher {
+    root_path: PathBuf,
+    pub interval: Duration,
+    last_check: Option<Instant>,
+    timestamps: HashMap<String, SystemTime>,
+}
+
+impl Default for FsAssetsWatcher {
+    fn default() -> Self {
+        Self::new(&FsFetchEngine::default())
+    }
+}
+
+impl FsAssetsWatcher {
+    pub fn new(fetch_engine: &FsFetchEngine) -> Self {
+        Self::with_root_path(fetch_engine.root_path())
+    }
+
+    pub fn with_root_path<S: AsRef<Path>>(root_path: S) -> Self {
+        Self {
+            root_path: root_path.as_ref().into(),
+            interval: Duration::from_millis(500),
+            last_check: None,
+            timestamps: Default::default(),
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+
+    pub fn process(&mut self, database: &mut AssetsDatabase) {
+        if let Some(last_check) = self.last_check {
+            if last_check.elapsed() < self.interval {
+                return;
+            }
+        }
+        self.last_check = Some(Instant::now());
+        let paths = database.loaded_paths().into_iter().collect::<HashSet<_>>();
+        self.timestamps.retain(|path, _| paths.contains(path));
+        for path in paths {
+            let subpath = match path.split_once("://") {
+                Some((_, subpath)) => subpath,
+                None => continue,
+            };
+            let modified = match metadata(self.root_path.join(subpath)).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(_) => continue,
+            };
+            match self.timestamps.insert(path.to_owned(), modified) {
+                Some(timestamp) if timestamp != modified => {
+                    if let Err(error) = database.reload(&path) {
+                        error!("Could not reload asset: {}\n{:?}", path, error);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub type FsAssetsWatcherSystemResources<'a> = (&'a mut AssetsDatabase, &'a mut FsAssetsWatcher);
+
+pub fn fs_assets_watcher_system(universe: &mut Universe) {
+    let (mut database, mut watcher) = universe.query_resources::<FsAssetsWatcherSystemResources>();
+    watcher.process(&mut database);
+}
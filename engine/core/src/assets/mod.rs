@@ -2,6 +2,9 @@ pub mod asset;
 pub mod asset_pack_preloader;
 pub mod assets_preloader;
 pub mod database;
+pub mod handle;
+#[cfg(not(feature = "web"))]
+pub mod hot_reload;
 pub mod protocol;
 pub mod protocols;
 pub mod system;
@@ -11,9 +14,16 @@ use crate::{
     assets::{
         database::AssetsDatabase,
         protocols::{
-            binary::BinaryAssetProtocol, json::JsonAssetProtocol,
-            localization::LocalizationAssetProtocol, meta::MetaAssetProtocol,
-            pack::PackAssetProtocol, prefab::PrefabAssetProtocol, text::TextAssetProtocol,
+            binary::BinaryAssetProtocol,
+            json::JsonAssetProtocol,
+            localization::{
+                LocalizationAssetProtocol, LocalizationCsvAssetProtocol,
+                LocalizationPoAssetProtocol,
+            },
+            meta::MetaAssetProtocol,
+            pack::PackAssetProtocol,
+            prefab::PrefabAssetProtocol,
+            text::TextAssetProtocol,
             toml::TomlAssetProtocol,
         },
         system::{assets_system, AssetsSystemResources},
@@ -39,6 +49,8 @@ where
     database.register(TomlAssetProtocol);
     database.register(PrefabAssetProtocol);
     database.register(LocalizationAssetProtocol);
+    database.register(LocalizationPoAssetProtocol);
+    database.register(LocalizationCsvAssetProtocol);
     database.register(MetaAssetProtocol);
     assets_database_setup(&mut database);
     builder.install_resource(database);
@@ -1,11 +1,310 @@
-use crate::assets::protocol::{AssetLoadResult, AssetProtocol};
+use crate::{
+    assets::protocol::{AssetLoadResult, AssetProtocol},
+    localization::{LocalizationFormats, PluralRules},
+};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, str::from_utf8};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+    str::from_utf8,
+};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct LocalizationAsset {
     pub language: String,
     pub dictionary: HashMap<String, String>,
+    /// Languages searched for texts missing in this language, in order.
+    #[serde(default)]
+    pub fallback_languages: Vec<String>,
+    /// Overrides default number and date formats of this language.
+    #[serde(default)]
+    pub formats: Option<LocalizationFormats>,
+    /// Overrides default plural rules of this language.
+    #[serde(default)]
+    pub plural_rules: Option<PluralRules>,
+}
+
+impl LocalizationAsset {
+    /// Reads gettext PO file. Language is read from `Language` header, text ID is taken from
+    /// `msgctxt` if present, otherwise from `msgid`. Untranslated and fuzzy entries are skipped.
+    pub fn from_po(content: &str) -> Result<Self, String> {
+        let mut result = Self::default();
+        for entry in parse_po(content)? {
+            if entry.id.is_empty() && entry.context.is_none() {
+                for line in entry.text.lines() {
+                    if let Some((key, value)) = line.split_once(':') {
+                        if key.trim() == "Language" {
+                            result.language = value.trim().to_owned();
+                        }
+                    }
+                }
+            } else if !entry.fuzzy && !entry.text.is_empty() {
+                let id = entry.context.unwrap_or(entry.id);
+                result.dictionary.insert(id, entry.text);
+            }
+        }
+        if result.language.is_empty() {
+            return Err("PO file is missing `Language` header".to_owned());
+        }
+        Ok(result)
+    }
+
+    /// Writes gettext PO file with entries for given text IDs, using `source` dictionary texts
+    /// as `msgid` and texts of this asset as `msgstr`.
+    pub fn to_po<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+        source: Option<&LocalizationAsset>,
+    ) -> String {
+        let mut result = String::new();
+        writeln!(result, "msgid \"\"").unwrap();
+        writeln!(result, "msgstr \"\"").unwrap();
+        writeln!(result, "\"Language: {}\\n\"", escape_po(&self.language)).unwrap();
+        writeln!(result, "\"Content-Type: text/plain; charset=UTF-8\\n\"").unwrap();
+        for id in ids {
+            let source_text = source
+                .and_then(|source| source.dictionary.get(id))
+                .map(|text| text.as_str())
+                .unwrap_or(id);
+            let text = self.dictionary.get(id).map(|text| text.as_str());
+            writeln!(result).unwrap();
+            writeln!(result, "msgctxt \"{}\"", escape_po(id)).unwrap();
+            writeln!(result, "msgid \"{}\"", escape_po(source_text)).unwrap();
+            writeln!(result, "msgstr \"{}\"", escape_po(text.unwrap_or_default())).unwrap();
+        }
+        result
+    }
+}
+
+/// Multiple languages read from single localization table.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LocalizationTableAsset {
+    pub languages: Vec<LocalizationAsset>,
+}
+
+impl LocalizationTableAsset {
+    /// Reads CSV table where first row is header with `id` column followed by language columns,
+    /// and every next row contains text ID followed by texts. Empty cells are skipped.
+    pub fn from_csv(content: &str) -> Result<Self, String> {
+        let mut rows = parse_csv(content)?.into_iter();
+        let header = match rows.next() {
+            Some(header) => header,
+            None => return Err("CSV file is missing header row".to_owned()),
+        };
+        let mut languages = header
+            .into_iter()
+            .skip(1)
+            .map(|language| LocalizationAsset {
+                language: language.trim().to_owned(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        for row in rows {
+            let mut cells = row.into_iter();
+            let id = match cells.next() {
+                Some(id) if !id.trim().is_empty() => id.trim().to_owned(),
+                _ => continue,
+            };
+            for (asset, text) in languages.iter_mut().zip(cells) {
+                if !text.is_empty() {
+                    asset.dictionary.insert(id.to_owned(), text);
+                }
+            }
+        }
+        languages.retain(|asset| !asset.language.is_empty());
+        Ok(Self { languages })
+    }
+
+    /// Writes CSV table with rows for all text IDs found in languages.
+    pub fn to_csv(&self) -> String {
+        let ids = self
+            .languages
+            .iter()
+            .flat_map(|asset| asset.dictionary.keys())
+            .collect::<BTreeSet<_>>();
+        let mut result = String::new();
+        result.push_str("id");
+        for asset in &self.languages {
+            result.push(',');
+            result.push_str(&escape_csv(&asset.language));
+        }
+        result.push('\n');
+        for id in ids {
+            result.push_str(&escape_csv(id));
+            for asset in &self.languages {
+                result.push(',');
+                if let Some(text) = asset.dictionary.get(id) {
+                    result.push_str(&escape_csv(text));
+                }
+            }
+            result.push('\n');
+        }
+        result
+    }
+}
+
+#[derive(Debug, Default)]
+struct PoEntry {
+    context: Option<String>,
+    id: String,
+    text: String,
+    fuzzy: bool,
+}
+
+fn parse_po(content: &str) -> Result<Vec<PoEntry>, String> {
+    #[derive(Copy, Clone)]
+    enum Field {
+        None,
+        Context,
+        Id,
+        IdPlural,
+        Text,
+    }
+
+    let mut result = vec![];
+    let mut entry = PoEntry::default();
+    let mut has_entry = false;
+    let mut fuzzy = false;
+    let mut field = Field::None;
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        let (keyword, rest) = if line.is_empty() || line.starts_with('#') {
+            if line.starts_with("#,") && line.contains("fuzzy") {
+                fuzzy = true;
+            }
+            continue;
+        } else if line.starts_with('"') {
+            ("", line)
+        } else {
+            line.split_once(char::is_whitespace)
+                .map(|(keyword, rest)| (keyword, rest.trim()))
+                .ok_or_else(|| format!("Invalid PO line {}: {}", index + 1, line))?
+        };
+        let value = unescape_po(rest)
+            .ok_or_else(|| format!("Invalid PO string at line {}: {}", index + 1, line))?;
+        let next = match keyword {
+            "" => field,
+            "msgctxt" => Field::Context,
+            "msgid" => Field::Id,
+            "msgid_plural" => Field::IdPlural,
+            "msgstr" | "msgstr[0]" => Field::Text,
+            keyword if keyword.starts_with("msgstr[") => Field::None,
+            keyword => {
+                return Err(format!(
+                    "Unknown PO keyword at line {}: {}",
+                    index + 1,
+                    keyword
+                ))
+            }
+        };
+        let starts_entry = matches!(
+            (field, next, keyword),
+            (
+                Field::Text | Field::None,
+                Field::Context | Field::Id,
+                "msgctxt" | "msgid"
+            )
+        );
+        if starts_entry {
+            if has_entry {
+                result.push(std::mem::take(&mut entry));
+            }
+            entry.fuzzy = fuzzy;
+            fuzzy = false;
+        }
+        match next {
+            Field::Context => entry
+                .context
+                .get_or_insert_with(String::new)
+                .push_str(&value),
+            Field::Id => entry.id.push_str(&value),
+            Field::Text => entry.text.push_str(&value),
+            Field::IdPlural | Field::None => {}
+        }
+        has_entry = true;
+        field = next;
+    }
+    if has_entry {
+        result.push(entry);
+    }
+    Ok(result)
+}
+
+fn unescape_po(value: &str) -> Option<String> {
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                'n' => result.push('\n'),
+                't' => result.push('\t'),
+                'r' => result.push('\r'),
+                c => result.push(c),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Some(result)
+}
+
+fn escape_po(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
+
+fn parse_csv(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut result = vec![];
+    let mut row = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    cell.push('"');
+                } else {
+                    quoted = false;
+                }
+            } else {
+                cell.push(c);
+            }
+        } else {
+            match c {
+                '"' => quoted = true,
+                ',' => row.push(std::mem::take(&mut cell)),
+                '\r' => {}
+                '\n' => {
+                    row.push(std::mem::take(&mut cell));
+                    result.push(std::mem::take(&mut row));
+                }
+                c => cell.push(c),
+            }
+        }
+    }
+    if quoted {
+        return Err("CSV file has unterminated quoted cell".to_owned());
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        result.push(row);
+    }
+    Ok(result)
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
 }
 
 pub struct LocalizationAssetProtocol;
@@ -25,3 +324,118 @@ impl AssetProtocol for LocalizationAssetProtocol {
         }
     }
 }
+
+/// Loads gettext PO files as `LocalizationAsset`.
+pub struct LocalizationPoAssetProtocol;
+
+impl AssetProtocol for LocalizationPoAssetProtocol {
+    fn name(&self) -> &str {
+        "locals-po"
+    }
+
+    fn on_load(&mut self, data: Vec<u8>) -> AssetLoadResult {
+        let data = match from_utf8(&data) {
+            Ok(data) => data,
+            Err(error) => {
+                return AssetLoadResult::Error(format!(
+                    "Error loading localization PO asset: {:?}",
+                    error
+                ))
+            }
+        };
+        match LocalizationAsset::from_po(data) {
+            Ok(result) => AssetLoadResult::Data(Box::new(result)),
+            Err(error) => {
+                AssetLoadResult::Error(format!("Error loading localization PO asset: {}", error))
+            }
+        }
+    }
+}
+
+/// Loads CSV tables as `LocalizationTableAsset`.
+pub struct LocalizationCsvAssetProtocol;
+
+impl AssetProtocol for LocalizationCsvAssetProtocol {
+    fn name(&self) -> &str {
+        "locals-csv"
+    }
+
+    fn on_load(&mut self, data: Vec<u8>) -> AssetLoadResult {
+        let data = match from_utf8(&data) {
+            Ok(data) => data,
+            Err(error) => {
+                return AssetLoadResult::Error(format!(
+                    "Error loading localization CSV asset: {:?}",
+                    error
+                ))
+            }
+        };
+        match LocalizationTableAsset::from_csv(data) {
+            Ok(result) => AssetLoadResult::Data(Box::new(result)),
+            Err(error) => {
+                AssetLoadResult::Error(format!("Error loading localization CSV asset: {}", error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localization_po() {
+        let content = r#"
+# translator comment
+msgid ""
+msgstr ""
+"Language: pl\n"
+"Content-Type: text/plain; charset=UTF-8\n"
+
+msgctxt "hello"
+msgid "Hello |@name|!"
+msgstr "Witaj |@name|!"
+
+msgid "Multi"
+msgstr ""
+"line one\n"
+"line \"two\""
+
+#, fuzzy
+msgctxt "fuzzy"
+msgid "Fuzzy"
+msgstr "Niepewny"
+
+msgctxt "missing"
+msgid "Missing"
+msgstr ""
+"#;
+        let asset = LocalizationAsset::from_po(content).unwrap();
+        assert_eq!(asset.language, "pl");
+        assert_eq!(asset.dictionary.len(), 2);
+        assert_eq!(asset.dictionary["hello"], "Witaj |@name|!");
+        assert_eq!(asset.dictionary["Multi"], "line one\nline \"two\"");
+
+        let written = asset.to_po(["hello", "Multi", "missing"], None);
+        let read = LocalizationAsset::from_po(&written).unwrap();
+        assert_eq!(read.language, "pl");
+        assert_eq!(read.dictionary, asset.dictionary);
+    }
+
+    #[test]
+    fn test_localization_csv() {
+        let content = "id,en,pl\nhello,\"Hello, \"\"friend\"\"\",Witaj\nbye,Bye,\n";
+        let table = LocalizationTableAsset::from_csv(content).unwrap();
+        assert_eq!(table.languages.len(), 2);
+        assert_eq!(table.languages[0].language, "en");
+        assert_eq!(table.languages[0].dictionary["hello"], "Hello, \"friend\"");
+        assert_eq!(table.languages[1].dictionary["hello"], "Witaj");
+        assert!(!table.languages[1].dictionary.contains_key("bye"));
+
+        let written = table.to_csv();
+        assert_eq!(
+            written,
+            "id,en,pl\nbye,Bye,\nhello,\"Hello, \"\"friend\"\"\",Witaj\n"
+        );
+    }
+}
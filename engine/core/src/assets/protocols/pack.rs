@@ -1,18 +1,398 @@
 use crate::{
     assets::protocol::{AssetLoadResult, AssetProtocol},
-    fetch::engines::map::MapFetchEngine,
+    fetch::engines::pack::PackFetchEngine,
 };
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    io::{Read, Seek, SeekFrom, Write},
+    sync::{Arc, Mutex},
+};
+
+/// Bytes that every versioned pack file starts with.
+pub const PACK_MAGIC: &[u8; 8] = b"OXYPACK\0";
+/// Current pack format version.
+///
+/// Pack layout (all numbers are little endian):
+/// - magic bytes (8 bytes),
+/// - format version (u32),
+/// - table of contents byte size (u64),
+/// - bincode-serialized `PackTableOfContents`,
+/// - entries data, with offsets relative to the end of table of contents.
+///
+/// Packs without magic bytes are treated as legacy bincode-serialized
+/// `HashMap<String, Vec<u8>>`.
+pub const PACK_VERSION: u32 = 2;
+
+/// Size of magic bytes, format version and table of contents size.
+const PACK_HEADER_SIZE: usize = PACK_MAGIC.len() + 4 + 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    InvalidHeader,
+    UnsupportedVersion(u32),
+    /// error message.
+    InvalidTableOfContents(String),
+    /// entry path.
+    MissingEntry(String),
+    /// entry path.
+    EntryOutOfBounds(String),
+    /// (entry path, error message)
+    Compression(String, String),
+    /// entry path.
+    HashMismatch(String),
+    /// error message.
+    Io(String),
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackCompression {
+    #[default]
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl PackCompression {
+    pub fn compress(self, bytes: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            Self::Lz4 => Ok(lz4_flex::compress_prepend_size(bytes)),
+            Self::Zstd => Err("Zstd compression is not supported at runtime".to_owned()),
+        }
+    }
+
+    pub fn decompress(self, bytes: &[u8], original_size: usize) -> Result<Vec<u8>, String> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            Self::Lz4 => lz4_flex::decompress_size_prepended(bytes).map_err(|e| e.to_string()),
+            Self::Zstd => {
+                let mut source = bytes;
+                let mut decoder =
+                    ruzstd::StreamingDecoder::new(&mut source).map_err(|e| e.to_string())?;
+                let mut result = Vec::with_capacity(original_size);
+                decoder
+                    .read_to_end(&mut result)
+                    .map_err(|e| e.to_string())?;
+                Ok(result)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackEntry {
+    /// Offset of stored bytes relative to the end of table of contents.
+    pub offset: u64,
+    /// Size of stored (possibly compressed) bytes.
+    pub size: u64,
+    /// Size of original (decompressed) bytes.
+    pub original_size: u64,
+    pub compression: PackCompression,
+    /// `pack_hash` of original (decompressed) bytes.
+    pub hash: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackTableOfContents {
+    pub entries: BTreeMap<String, PackEntry>,
+}
+
+/// FNV-1a hash used to validate pack entries content.
+pub fn pack_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Builds versioned pack file bytes.
+#[derive(Debug, Default)]
+pub struct PackWriter {
+    toc: PackTableOfContents,
+    data: Vec<u8>,
+}
+
+impl PackWriter {
+    pub fn add(
+        &mut self,
+        path: impl ToString,
+        bytes: &[u8],
+        compression: PackCompression,
+    ) -> Result<(), PackError> {
+        let path = path.to_string();
+        let stored = compression
+            .compress(bytes)
+            .map_err(|error| PackError::Compression(path.to_owned(), error))?;
+        self.add_compressed(path, bytes, stored, compression);
+        Ok(())
+    }
 
-pub struct PackAsset(HashMap<String, Vec<u8>>);
+    /// Adds entry which `stored` bytes are `bytes` already compressed with `compression`, for
+    /// compressors that are not available at runtime.
+    pub fn add_compressed(
+        &mut self,
+        path: impl ToString,
+        bytes: &[u8],
+        stored: Vec<u8>,
+        compression: PackCompression,
+    ) {
+        let entry = PackEntry {
+            offset: self.data.len() as u64,
+            size: stored.len() as u64,
+            original_size: bytes.len() as u64,
+            compression,
+            hash: pack_hash(bytes),
+        };
+        self.data.extend(stored);
+        self.toc.entries.insert(path.to_string(), entry);
+    }
+
+    pub fn with(
+        mut self,
+        path: impl ToString,
+        bytes: &[u8],
+        compression: PackCompression,
+    ) -> Result<Self, PackError> {
+        self.add(path, bytes, compression)?;
+        Ok(self)
+    }
+
+    pub fn write(self) -> Result<Vec<u8>, PackError> {
+        let mut result = Vec::with_capacity(PACK_HEADER_SIZE + self.data.len());
+        self.write_into(&mut result)?;
+        Ok(result)
+    }
+
+    pub fn write_into<W: Write>(self, output: &mut W) -> Result<(), PackError> {
+        let toc = bincode::serialize(&self.toc)
+            .map_err(|error| PackError::InvalidTableOfContents(error.to_string()))?;
+        output
+            .write_all(PACK_MAGIC)
+            .and_then(|_| output.write_all(&PACK_VERSION.to_le_bytes()))
+            .and_then(|_| output.write_all(&(toc.len() as u64).to_le_bytes()))
+            .and_then(|_| output.write_all(&toc))
+            .and_then(|_| output.write_all(&self.data))
+            .map_err(|error| PackError::Io(error.to_string()))
+    }
+}
+
+/// Source of stored pack bytes.
+pub trait PackStorage: Send + Sync {
+    /// Reads `size` bytes starting at `offset` from the beginning of pack file.
+    fn read_at(&self, offset: u64, size: usize) -> Option<Cow<[u8]>>;
+
+    /// Total size of pack file in bytes.
+    fn size(&self) -> Option<u64>;
+}
+
+impl PackStorage for Vec<u8> {
+    fn read_at(&self, offset: u64, size: usize) -> Option<Cow<[u8]>> {
+        let from = usize::try_from(offset).ok()?;
+        self.get(from..from.checked_add(size)?).map(Cow::Borrowed)
+    }
+
+    fn size(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+/// Pack storage that reads only requested bytes from seekable reader (for example a file).
+pub struct PackReaderStorage<R>(Mutex<R>)
+where
+    R: Read + Seek + Send;
+
+impl<R> PackReaderStorage<R>
+where
+    R: Read + Seek + Send,
+{
+    pub fn new(reader: R) -> Self {
+        Self(Mutex::new(reader))
+    }
+}
+
+impl<R> PackStorage for PackReaderStorage<R>
+where
+    R: Read + Seek + Send,
+{
+    fn read_at(&self, offset: u64, size: usize) -> Option<Cow<[u8]>> {
+        let mut reader = self.0.lock().ok()?;
+        reader.seek(SeekFrom::Start(offset)).ok()?;
+        let mut result = vec![0; size];
+        reader.read_exact(&mut result).ok()?;
+        Some(Cow::Owned(result))
+    }
+
+    fn size(&self) -> Option<u64> {
+        self.0.lock().ok()?.seek(SeekFrom::End(0)).ok()
+    }
+}
+
+/// Pack file with its table of contents read upfront, while entries are read from storage and
+/// decompressed only when requested.
+pub struct PackArchive {
+    toc: PackTableOfContents,
+    storage: Box<dyn PackStorage>,
+    data_offset: u64,
+}
+
+impl std::fmt::Debug for PackArchive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PackArchive")
+            .field("toc", &self.toc)
+            .field("data_offset", &self.data_offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PackArchive {
+    /// Pack file kept in memory in its stored form.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, PackError> {
+        if !bytes.starts_with(PACK_MAGIC) {
+            return Self::from_legacy_bytes(&bytes);
+        }
+        Self::from_storage(Box::new(bytes))
+    }
+
+    /// Pack file read from seekable reader, so only its table of contents is kept in memory.
+    pub fn from_reader<R>(mut reader: R) -> Result<Self, PackError>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let mut magic = [0; PACK_MAGIC.len()];
+        let is_versioned = reader
+            .read_exact(&mut magic)
+            .map(|_| &magic == PACK_MAGIC)
+            .unwrap_or_default();
+        if !is_versioned {
+            let mut bytes = vec![];
+            reader
+                .seek(SeekFrom::Start(0))
+                .and_then(|_| reader.read_to_end(&mut bytes))
+                .map_err(|error| PackError::Io(error.to_string()))?;
+            return Self::from_legacy_bytes(&bytes);
+        }
+        Self::from_storage(Box::new(PackReaderStorage::new(reader)))
+    }
+
+    pub fn from_storage(storage: Box<dyn PackStorage>) -> Result<Self, PackError> {
+        let header = storage
+            .read_at(0, PACK_HEADER_SIZE)
+            .ok_or(PackError::InvalidHeader)?;
+        if !header.starts_with(PACK_MAGIC) {
+            return Err(PackError::InvalidHeader);
+        }
+        let mut version = [0; 4];
+        version.copy_from_slice(&header[8..12]);
+        let version = u32::from_le_bytes(version);
+        if version != PACK_VERSION {
+            return Err(PackError::UnsupportedVersion(version));
+        }
+        let mut toc_size = [0; 8];
+        toc_size.copy_from_slice(&header[12..20]);
+        let toc_size = u64::from_le_bytes(toc_size);
+        let storage_size = storage.size().ok_or(PackError::InvalidHeader)?;
+        let data_offset = (PACK_HEADER_SIZE as u64)
+            .checked_add(toc_size)
+            .filter(|data_offset| *data_offset <= storage_size)
+            .ok_or(PackError::InvalidHeader)?;
+        let toc = usize::try_from(toc_size)
+            .ok()
+            .and_then(|toc_size| storage.read_at(PACK_HEADER_SIZE as u64, toc_size))
+            .ok_or(PackError::InvalidHeader)?;
+        let toc: PackTableOfContents = bincode::deserialize(&toc)
+            .map_err(|error| PackError::InvalidTableOfContents(error.to_string()))?;
+        for (path, entry) in &toc.entries {
+            let in_bounds = data_offset
+                .checked_add(entry.offset)
+                .and_then(|offset| offset.checked_add(entry.size))
+                .map(|end| end <= storage_size)
+                .unwrap_or_default();
+            if !in_bounds {
+                return Err(PackError::EntryOutOfBounds(path.to_owned()));
+            }
+        }
+        Ok(Self {
+            toc,
+            storage,
+            data_offset,
+        })
+    }
+
+    fn from_legacy_bytes(bytes: &[u8]) -> Result<Self, PackError> {
+        let files = bincode::deserialize::<HashMap<String, Vec<u8>>>(bytes)
+            .map_err(|_| PackError::InvalidHeader)?;
+        let mut writer = PackWriter::default();
+        for (path, bytes) in files {
+            writer.add(path, &bytes, PackCompression::None)?;
+        }
+        Self::from_bytes(writer.write()?)
+    }
+
+    pub fn toc(&self) -> &PackTableOfContents {
+        &self.toc
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.toc.entries.keys().map(|path| path.as_str())
+    }
+
+    pub fn has_entry(&self, path: &str) -> bool {
+        self.toc.entries.contains_key(path)
+    }
+
+    pub fn entry(&self, path: &str) -> Option<&PackEntry> {
+        self.toc.entries.get(path)
+    }
+
+    /// Stored (possibly compressed) bytes of entry.
+    pub fn stored_bytes(&self, path: &str) -> Result<Cow<[u8]>, PackError> {
+        let entry = self
+            .entry(path)
+            .ok_or_else(|| PackError::MissingEntry(path.to_owned()))?;
+        usize::try_from(entry.size)
+            .ok()
+            .and_then(|size| {
+                let offset = self.data_offset.checked_add(entry.offset)?;
+                self.storage.read_at(offset, size)
+            })
+            .ok_or_else(|| PackError::EntryOutOfBounds(path.to_owned()))
+    }
+
+    /// Original bytes of entry, decompressed and validated against its hash.
+    pub fn read(&self, path: &str) -> Result<Cow<[u8]>, PackError> {
+        let entry = self
+            .entry(path)
+            .ok_or_else(|| PackError::MissingEntry(path.to_owned()))?;
+        let stored = self.stored_bytes(path)?;
+        let bytes = match entry.compression {
+            PackCompression::None => stored,
+            compression => Cow::Owned(
+                compression
+                    .decompress(&stored, entry.original_size as usize)
+                    .map_err(|error| PackError::Compression(path.to_owned(), error))?,
+            ),
+        };
+        if bytes.len() as u64 != entry.original_size || pack_hash(&bytes) != entry.hash {
+            return Err(PackError::HashMismatch(path.to_owned()));
+        }
+        Ok(bytes)
+    }
+}
+
+pub struct PackAsset(Arc<PackArchive>);
 
 impl PackAsset {
-    pub fn get_asset_data(&self, path: &str) -> Option<&[u8]> {
-        self.0.get(path).map(|d| d.as_ref())
+    pub fn archive(&self) -> &PackArchive {
+        &self.0
     }
 
-    pub fn make_fetch_engine(&self) -> MapFetchEngine {
-        MapFetchEngine::new(self.0.clone())
+    pub fn get_asset_data(&self, path: &str) -> Option<Cow<[u8]>> {
+        self.0.read(path).ok()
+    }
+
+    pub fn make_fetch_engine(&self) -> PackFetchEngine {
+        PackFetchEngine::new(self.0.clone())
     }
 }
 
@@ -24,9 +404,78 @@ impl AssetProtocol for PackAssetProtocol {
     }
 
     fn on_load(&mut self, data: Vec<u8>) -> AssetLoadResult {
-        match bincode::deserialize(&data) {
-            Ok(data) => AssetLoadResult::Data(Box::new(PackAsset(data))),
+        match PackArchive::from_bytes(data) {
+            Ok(archive) => AssetLoadResult::Data(Box::new(PackAsset(Arc::new(archive)))),
             Err(error) => AssetLoadResult::Error(format!("Error loading pack asset: {:?}", error)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack() {
+        let bytes = PackWriter::default()
+            .with("a.txt", b"A", PackCompression::None)
+            .unwrap()
+            .with("b.txt", &[42u8; 1024], PackCompression::Lz4)
+            .unwrap()
+            .write()
+            .unwrap();
+        for archive in [
+            PackArchive::from_bytes(bytes.to_owned()).unwrap(),
+            PackArchive::from_reader(std::io::Cursor::new(bytes)).unwrap(),
+        ] {
+            assert_eq!(archive.paths().collect::<Vec<_>>(), vec!["a.txt", "b.txt"]);
+            assert_eq!(archive.read("a.txt").unwrap().as_ref(), b"A");
+            assert!(archive.stored_bytes("b.txt").unwrap().len() < 1024);
+            assert_eq!(archive.read("b.txt").unwrap().as_ref(), &[42u8; 1024]);
+            assert_eq!(
+                archive.read("c.txt"),
+                Err(PackError::MissingEntry("c.txt".to_owned()))
+            );
+        }
+
+        let mut legacy = HashMap::new();
+        legacy.insert("a.txt".to_owned(), b"A".to_vec());
+        let legacy = bincode::serialize(&legacy).unwrap();
+        let archive = PackArchive::from_bytes(legacy.to_owned()).unwrap();
+        assert_eq!(archive.read("a.txt").unwrap().as_ref(), b"A");
+        let archive = PackArchive::from_reader(std::io::Cursor::new(legacy)).unwrap();
+        assert_eq!(archive.read("a.txt").unwrap().as_ref(), b"A");
+    }
+
+    #[test]
+    fn test_pack_out_of_bounds() {
+        let bytes = PackWriter::default()
+            .with("a.txt", b"A", PackCompression::None)
+            .unwrap()
+            .with("b.txt", b"B", PackCompression::None)
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let mut invalid_toc_size = bytes.to_owned();
+        invalid_toc_size[12..20].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            PackArchive::from_bytes(invalid_toc_size.to_owned()).unwrap_err(),
+            PackError::InvalidHeader
+        );
+        assert_eq!(
+            PackArchive::from_reader(std::io::Cursor::new(invalid_toc_size)).unwrap_err(),
+            PackError::InvalidHeader
+        );
+
+        let truncated = bytes[..bytes.len() - 1].to_vec();
+        assert_eq!(
+            PackArchive::from_bytes(truncated.to_owned()).unwrap_err(),
+            PackError::EntryOutOfBounds("b.txt".to_owned())
+        );
+        assert_eq!(
+            PackArchive::from_reader(std::io::Cursor::new(truncated)).unwrap_err(),
+            PackError::EntryOutOfBounds("b.txt".to_owned())
+        );
+    }
+}
@@ -33,6 +33,13 @@ impl PrefabProxy<NonPersistentPrefabProxy> for NonPersistent {
     ) -> Result<Self, PrefabError> {
         Ok(NonPersistent(state_token))
     }
+
+    fn into_proxy_with_extras(
+        &self,
+        _: &HashMap<Entity, String>,
+    ) -> Result<NonPersistentPrefabProxy, PrefabError> {
+        Ok(NonPersistentPrefabProxy)
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -135,6 +142,17 @@ where
     ) -> Result<Self, PrefabError> {
         Ok(Events::new(proxy.capacity, proxy.auto_clear))
     }
+
+    fn into_proxy_with_extras(
+        &self,
+        _: &HashMap<Entity, String>,
+    ) -> Result<EventsPrefabProxy<T>, PrefabError> {
+        Ok(EventsPrefabProxy {
+            capacity: self.capacity,
+            auto_clear: self.auto_clear,
+            _phantom: Default::default(),
+        })
+    }
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -29,6 +29,20 @@ impl PrefabProxy<ParentPrefabProxy> for Parent {
             )))
         }
     }
+
+    /// Parent that is not part of serialized entities is omitted, so its child becomes root.
+    fn into_proxy_with_extras(
+        &self,
+        entity_names: &HashMap<Entity, String>,
+    ) -> Result<ParentPrefabProxy, PrefabError> {
+        if let Some(name) = entity_names.get(&self.0) {
+            Ok(ParentPrefabProxy(name.to_owned()))
+        } else {
+            Err(PrefabError::NotSerializable(
+                std::any::type_name::<Self>().to_owned(),
+            ))
+        }
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
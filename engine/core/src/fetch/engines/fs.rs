@@ -31,6 +31,10 @@ impl FsFetchEngine {
             },
         }
     }
+
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
 }
 
 impl FetchEngine for FsFetchEngine {
@@ -58,4 +62,8 @@ impl FetchEngine for FsFetchEngine {
             }
         }
     }
+
+    fn exists(&self, path: &str) -> Option<bool> {
+        Some(self.root_path.join(path).is_file())
+    }
 }
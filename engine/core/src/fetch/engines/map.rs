@@ -20,4 +20,8 @@ impl FetchEngine for MapFetchEngine {
             Err(FetchStatus::Canceled(FetchCancelReason::Error))
         }
     }
+
+    fn exists(&self, path: &str) -> Option<bool> {
+        Some(self.map.contains_key(path))
+    }
 }
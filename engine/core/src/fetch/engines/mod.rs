@@ -1,3 +1,5 @@
 #[cfg(not(feature = "web"))]
 pub mod fs;
 pub mod map;
+pub mod overlay;
+pub mod pack;
@@ -0,0 +1,151 @@
+use crate::fetch::{FetchCancelReason, FetchEngine, FetchProcess, FetchProcessId, FetchStatus};
+use std::collections::HashMap;
+
+/// Single layer of `OverlayFetchEngine`.
+pub struct OverlayFetchLayer {
+    /// Only paths starting with mount point are resolved by this layer, with mount point
+    /// stripped from the path.
+    pub mount: String,
+    /// Prefix added to path before it gets fetched by layer engine.
+    pub prefix: String,
+    engine: Box<dyn FetchEngine>,
+}
+
+impl OverlayFetchLayer {
+    pub fn new<FE>(engine: FE) -> Self
+    where
+        FE: FetchEngine + 'static,
+    {
+        Self::new_boxed(Box::new(engine))
+    }
+
+    pub fn new_boxed(engine: Box<dyn FetchEngine>) -> Self {
+        Self {
+            mount: Default::default(),
+            prefix: Default::default(),
+            engine,
+        }
+    }
+
+    pub fn mount(mut self, mount: impl ToString) -> Self {
+        self.mount = mount.to_string();
+        self
+    }
+
+    pub fn prefix(mut self, prefix: impl ToString) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn engine(&self) -> &dyn FetchEngine {
+        self.engine.as_ref()
+    }
+
+    pub fn engine_mut(&mut self) -> &mut dyn FetchEngine {
+        self.engine.as_mut()
+    }
+
+    pub fn resolve(&self, path: &str) -> Option<String> {
+        path.strip_prefix(&self.mount)
+            .map(|path| format!("{}{}", self.prefix, path))
+    }
+}
+
+/// Resolves paths through stack of fetch engines, starting from the top one, so upper layers
+/// (patches, mods) can override assets of lower layers (base game).
+#[derive(Default)]
+pub struct OverlayFetchEngine {
+    layers: Vec<OverlayFetchLayer>,
+    /// {process id: (layer index, process)}
+    processes: HashMap<FetchProcessId, (usize, FetchProcess)>,
+}
+
+impl OverlayFetchEngine {
+    pub fn with_layer(mut self, layer: OverlayFetchLayer) -> Self {
+        self.push_layer(layer);
+        self
+    }
+
+    pub fn push_layer(&mut self, layer: OverlayFetchLayer) {
+        self.layers.push(layer);
+    }
+
+    pub fn pop_layer(&mut self) -> Option<OverlayFetchLayer> {
+        self.processes
+            .retain(|_, (index, _)| *index + 1 < self.layers.len());
+        self.layers.pop()
+    }
+
+    pub fn layers_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn layers(&self) -> impl Iterator<Item = &OverlayFetchLayer> {
+        self.layers.iter().rev()
+    }
+
+    /// Index of the top-most layer that can resolve given path.
+    pub fn resolve_layer(&self, path: &str) -> Option<usize> {
+        self.layers
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, layer)| {
+                layer
+                    .resolve(path)
+                    .map(|path| layer.engine.exists(&path).unwrap_or(true))
+                    .unwrap_or_default()
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+impl FetchEngine for OverlayFetchEngine {
+    fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus> {
+        self.processes.retain(|_, (_, process)| {
+            !matches!(
+                process.status(),
+                FetchStatus::Read | FetchStatus::Canceled(_)
+            )
+        });
+        for (index, layer) in self.layers.iter_mut().enumerate().rev() {
+            let path = match layer.resolve(path) {
+                Some(path) => path,
+                None => continue,
+            };
+            if layer.engine.exists(&path) == Some(false) {
+                continue;
+            }
+            if let Ok(process) = layer.engine.fetch(&path) {
+                self.processes
+                    .insert(process.id(), (index, (*process).clone()));
+                return Ok(process);
+            }
+        }
+        Err(FetchStatus::Canceled(FetchCancelReason::Error))
+    }
+
+    fn exists(&self, path: &str) -> Option<bool> {
+        let mut result = Some(false);
+        for layer in &self.layers {
+            if let Some(path) = layer.resolve(path) {
+                match layer.engine.exists(&path) {
+                    Some(true) => return Some(true),
+                    Some(false) => {}
+                    None => result = None,
+                }
+            }
+        }
+        result
+    }
+
+    fn cancel(&mut self, mut reader: FetchProcess) {
+        if let Some((index, _)) = self.processes.remove(&reader.id()) {
+            if let Some(layer) = self.layers.get_mut(index) {
+                layer.engine.cancel(reader);
+                return;
+            }
+        }
+        reader.cancel(FetchCancelReason::User)
+    }
+}
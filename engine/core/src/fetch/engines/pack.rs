@@ -0,0 +1,34 @@
+use crate::{
+    assets::protocols::pack::PackArchive,
+    fetch::{FetchCancelReason, FetchEngine, FetchProcess, FetchStatus},
+};
+use std::sync::Arc;
+
+/// Fetches entries of shared pack archive, decompressing only requested ones.
+#[derive(Clone)]
+pub struct PackFetchEngine {
+    archive: Arc<PackArchive>,
+}
+
+impl PackFetchEngine {
+    pub fn new(archive: Arc<PackArchive>) -> Self {
+        Self { archive }
+    }
+
+    pub fn archive(&self) -> &PackArchive {
+        &self.archive
+    }
+}
+
+impl FetchEngine for PackFetchEngine {
+    fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus> {
+        match self.archive.read(path) {
+            Ok(bytes) => Ok(Box::new(FetchProcess::new_done(bytes.into_owned()))),
+            Err(_) => Err(FetchStatus::Canceled(FetchCancelReason::Error)),
+        }
+    }
+
+    fn exists(&self, path: &str) -> Option<bool> {
+        Some(self.archive.has_entry(path))
+    }
+}
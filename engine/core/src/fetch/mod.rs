@@ -137,6 +137,11 @@ impl FetchProcess {
 pub trait FetchEngine: Send + Sync {
     fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus>;
 
+    /// Tells if resource exists, or `None` if engine can find out only by fetching it.
+    fn exists(&self, _path: &str) -> Option<bool> {
+        None
+    }
+
     fn cancel(&mut self, mut reader: FetchProcess) {
         reader.cancel(FetchCancelReason::User)
     }
@@ -170,4 +175,34 @@ mod tests {
         assert_eq!(reader.status(), FetchStatus::Read);
         assert_eq!(reader2.status(), FetchStatus::Read);
     }
+
+    #[test]
+    fn test_overlay_fetch() {
+        let mut base = engines::map::MapFetchEngine::default();
+        base.map.insert("a.txt".to_owned(), b"base A".to_vec());
+        base.map.insert("b.txt".to_owned(), b"base B".to_vec());
+        let mut patch = engines::map::MapFetchEngine::default();
+        patch.map.insert("a.txt".to_owned(), b"patch A".to_vec());
+        let mut dlc = engines::map::MapFetchEngine::default();
+        dlc.map.insert("dlc/c.txt".to_owned(), b"dlc C".to_vec());
+
+        let mut engine = engines::overlay::OverlayFetchEngine::default()
+            .with_layer(engines::overlay::OverlayFetchLayer::new(base))
+            .with_layer(engines::overlay::OverlayFetchLayer::new(patch))
+            .with_layer(
+                engines::overlay::OverlayFetchLayer::new(dlc)
+                    .mount("extra/")
+                    .prefix("dlc/"),
+            );
+        assert_eq!(engine.fetch("a.txt").unwrap().read().unwrap(), b"patch A");
+        assert_eq!(engine.fetch("b.txt").unwrap().read().unwrap(), b"base B");
+        assert_eq!(
+            engine.fetch("extra/c.txt").unwrap().read().unwrap(),
+            b"dlc C"
+        );
+        assert!(engine.fetch("c.txt").is_err());
+        assert_eq!(engine.exists("extra/c.txt"), Some(true));
+        assert_eq!(engine.exists("d.txt"), Some(false));
+        assert_eq!(engine.resolve_layer("a.txt"), Some(1));
+    }
 }
@@ -10,6 +10,7 @@ pub mod assets;
 pub mod error;
 pub mod fetch;
 pub mod prefab;
+pub mod save_game;
 pub mod state;
 #[macro_use]
 pub mod localization;
@@ -35,6 +36,7 @@ pub mod prelude {
             asset_pack_preloader::*,
             assets_preloader::*,
             database::*,
+            handle::*,
             protocol::*,
             protocols::{
                 binary::*, json::*, localization::*, meta::*, pack::*, prefab::*, text::*, *,
@@ -54,7 +56,7 @@ pub mod prelude {
             *,
         },
         fetch::{
-            engines::{map::*, *},
+            engines::{map::*, overlay::*, pack::*, *},
             *,
         },
         id::*,
@@ -62,6 +64,7 @@ pub mod prelude {
         localization::*,
         log::*,
         prefab::*,
+        save_game::*,
         scripting::*,
         state::*,
         storage::{
@@ -72,7 +75,7 @@ pub mod prelude {
         Scalar, *,
     };
     #[cfg(not(feature = "web"))]
-    pub use crate::{fetch::engines::fs::*, storage::engines::fs::*};
+    pub use crate::{assets::hot_reload::*, fetch::engines::fs::*, storage::engines::fs::*};
 }
 
 #[cfg(feature = "scalar64")]
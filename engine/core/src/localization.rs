@@ -1,7 +1,9 @@
 use crate::{
     app::AppBuilder,
     assets::{
-        asset::AssetId, database::AssetsDatabase, protocols::localization::LocalizationAsset,
+        asset::AssetId,
+        database::AssetsDatabase,
+        protocols::localization::{LocalizationAsset, LocalizationTableAsset},
     },
     ecs::{
         pipeline::{PipelineBuilder, PipelineBuilderError},
@@ -9,7 +11,11 @@ use crate::{
     },
 };
 use pest::{iterators::Pair, Parser};
-use std::{collections::HashMap, fmt::Write};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+};
 
 #[allow(clippy::upper_case_acronyms)]
 mod parser {
@@ -18,12 +24,307 @@ mod parser {
     pub(super) struct SentenceParser;
 }
 
+/// CLDR plural category.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Zero => "zero",
+            Self::One => "one",
+            Self::Two => "two",
+            Self::Few => "few",
+            Self::Many => "many",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// CLDR plural operands of a number.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PluralOperands {
+    /// Absolute value.
+    pub n: f64,
+    /// Integer digits.
+    pub i: u64,
+    /// Number of visible fraction digits.
+    pub v: usize,
+    /// Visible fraction digits.
+    pub f: u64,
+}
+
+impl PluralOperands {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix('-').unwrap_or(value);
+        let n = value.parse::<f64>().ok()?.abs();
+        let (i, f) = value.split_once('.').unwrap_or((value, ""));
+        Some(Self {
+            n,
+            i: if i.is_empty() { 0 } else { i.parse().ok()? },
+            v: f.len(),
+            f: if f.is_empty() { 0 } else { f.parse().ok()? },
+        })
+    }
+}
+
+/// Families of CLDR plural rules, named after their representative language.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PluralRules {
+    /// other (ja, zh, ko, th, vi, id, ...).
+    Japanese,
+    /// one: i = 1 and v = 0; other (en, de, nl, sv, it, es, ...).
+    #[default]
+    English,
+    /// one: i = 0,1; other (fr, pt, hi, ...).
+    French,
+    /// one, few, many, other (ru, uk, be).
+    Russian,
+    /// one, few, many, other (pl).
+    Polish,
+    /// one, few, many, other (cs, sk).
+    Czech,
+    /// zero, one, two, few, many, other (ar).
+    Arabic,
+}
+
+impl PluralRules {
+    pub fn for_language(language: &str) -> Self {
+        match primary_language(language).to_lowercase().as_str() {
+            "ja" | "zh" | "ko" | "th" | "vi" | "id" | "ms" | "lo" | "my" | "km" | "yue" => {
+                Self::Japanese
+            }
+            "fr" | "pt" | "hi" | "bn" | "fa" => Self::French,
+            "ru" | "uk" | "be" => Self::Russian,
+            "pl" => Self::Polish,
+            "cs" | "sk" => Self::Czech,
+            "ar" => Self::Arabic,
+            _ => Self::English,
+        }
+    }
+
+    pub fn category(self, operands: &PluralOperands) -> PluralCategory {
+        let PluralOperands { n, i, v, .. } = *operands;
+        let i10 = i % 10;
+        let i100 = i % 100;
+        match self {
+            Self::Japanese => PluralCategory::Other,
+            Self::English => {
+                if i == 1 && v == 0 {
+                    PluralCategory::One
+                } else {
+                    PluralCategory::Other
+                }
+            }
+            Self::French => {
+                if i == 0 || i == 1 {
+                    PluralCategory::One
+                } else {
+                    PluralCategory::Other
+                }
+            }
+            Self::Russian => {
+                if v != 0 {
+                    PluralCategory::Other
+                } else if i10 == 1 && i100 != 11 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&i10) && !(12..=14).contains(&i100) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            }
+            Self::Polish => {
+                if v != 0 {
+                    PluralCategory::Other
+                } else if i == 1 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&i10) && !(12..=14).contains(&i100) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Many
+                }
+            }
+            Self::Czech => {
+                if v != 0 {
+                    PluralCategory::Many
+                } else if i == 1 {
+                    PluralCategory::One
+                } else if (2..=4).contains(&i) {
+                    PluralCategory::Few
+                } else {
+                    PluralCategory::Other
+                }
+            }
+            Self::Arabic => {
+                if v != 0 || n.fract() != 0.0 {
+                    PluralCategory::Other
+                } else if i == 0 {
+                    PluralCategory::Zero
+                } else if i == 1 {
+                    PluralCategory::One
+                } else if i == 2 {
+                    PluralCategory::Two
+                } else if (3..=10).contains(&i100) {
+                    PluralCategory::Few
+                } else if (11..=99).contains(&i100) {
+                    PluralCategory::Many
+                } else {
+                    PluralCategory::Other
+                }
+            }
+        }
+    }
+}
+
+/// Locale-specific number and date formatting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizationFormats {
+    pub decimal_separator: String,
+    pub grouping_separator: String,
+    #[serde(default = "LocalizationFormats::default_grouping_size")]
+    pub grouping_size: usize,
+    /// Short date pattern where `YYYY`, `MM` and `DD` get replaced with date components.
+    pub date_pattern: String,
+}
+
+impl Default for LocalizationFormats {
+    fn default() -> Self {
+        Self::new(".", ",", "YYYY-MM-DD")
+    }
+}
+
+impl LocalizationFormats {
+    fn default_grouping_size() -> usize {
+        3
+    }
+
+    pub fn new(decimal_separator: &str, grouping_separator: &str, date_pattern: &str) -> Self {
+        Self {
+            decimal_separator: decimal_separator.to_owned(),
+            grouping_separator: grouping_separator.to_owned(),
+            grouping_size: Self::default_grouping_size(),
+            date_pattern: date_pattern.to_owned(),
+        }
+    }
+
+    pub fn for_language(language: &str) -> Self {
+        let language = language.to_lowercase().replace('_', "-");
+        match language.as_str() {
+            "en" | "en-us" => return Self::new(".", ",", "MM/DD/YYYY"),
+            "pt-br" => return Self::new(",", ".", "DD/MM/YYYY"),
+            _ => {}
+        }
+        match primary_language(&language) {
+            "en" | "ar" => Self::new(".", ",", "DD/MM/YYYY"),
+            "de" | "tr" => Self::new(",", ".", "DD.MM.YYYY"),
+            "ru" | "uk" | "be" | "pl" | "cs" | "sk" => Self::new(",", "\u{a0}", "DD.MM.YYYY"),
+            "fr" | "pt" => Self::new(",", "\u{a0}", "DD/MM/YYYY"),
+            "es" | "it" => Self::new(",", ".", "DD/MM/YYYY"),
+            "nl" => Self::new(",", ".", "DD-MM-YYYY"),
+            "sv" => Self::new(",", "\u{a0}", "YYYY-MM-DD"),
+            "ja" | "zh" | "ko" => Self::new(".", ",", "YYYY/MM/DD"),
+            _ => Self::default(),
+        }
+    }
+
+    /// Formats number with locale separators, with given number of fraction digits if set.
+    /// Values that are not numbers are returned unchanged.
+    pub fn format_number(&self, value: &str, precision: Option<usize>) -> String {
+        let value = value.trim();
+        let number = match value.parse::<f64>() {
+            Ok(number) if number.is_finite() => number,
+            _ => return value.to_owned(),
+        };
+        let value = match precision {
+            Some(precision) => format!("{:.*}", precision, number),
+            None => value.to_owned(),
+        };
+        let (sign, value) = match value.strip_prefix('-') {
+            Some(value) => ("-", value),
+            None => ("", value.strip_prefix('+').unwrap_or(&value)),
+        };
+        let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+        let mut result = sign.to_owned();
+        let size = self.grouping_size.max(1);
+        for (index, c) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % size == 0 {
+                result.push_str(&self.grouping_separator);
+            }
+            result.push(c);
+        }
+        if !fraction.is_empty() {
+            result.push_str(&self.decimal_separator);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Formats date given either as UNIX timestamp in seconds or as `YYYY-MM-DD` string.
+    /// Values that are not dates are returned unchanged.
+    pub fn format_date(&self, value: &str) -> String {
+        let value = value.trim();
+        let (year, month, day) = match parse_date(value) {
+            Some(date) => date,
+            None => return value.to_owned(),
+        };
+        self.date_pattern
+            .replace("YYYY", &format!("{:04}", year))
+            .replace("MM", &format!("{:02}", month))
+            .replace("DD", &format!("{:02}", day))
+    }
+}
+
+fn primary_language(language: &str) -> &str {
+    language
+        .split(|c| c == '-' || c == '_')
+        .next()
+        .unwrap_or(language)
+}
+
+fn parse_date(value: &str) -> Option<(i64, u32, u32)> {
+    if let Ok(timestamp) = value.parse::<i64>() {
+        // days to civil date conversion from: http://howardhinnant.github.io/date_algorithms.html
+        let days = timestamp.div_euclid(86400) + 719468;
+        let era = days.div_euclid(146097);
+        let doe = days.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+        return Some((year, month, day));
+    }
+    let mut parts = value.get(0..10)?.split('-');
+    let year = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    if (1..=12).contains(&month) && (1..=31).contains(&day) {
+        Some((year, month, day))
+    } else {
+        None
+    }
+}
+
 #[derive(Default)]
 pub struct Localization {
     default_language: Option<String>,
     current_language: Option<String>,
     /// { text id: { language: text format } }
     map: HashMap<String, HashMap<String, String>>,
+    /// { language: fallback languages }
+    fallback_languages: HashMap<String, Vec<String>>,
+    formats: HashMap<String, LocalizationFormats>,
+    plural_rules: HashMap<String, PluralRules>,
 }
 
 impl Localization {
@@ -46,6 +347,88 @@ impl Localization {
         }
     }
 
+    pub fn fallback_languages(&self, language: &str) -> &[String] {
+        self.fallback_languages
+            .get(language)
+            .map(|list| list.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn set_fallback_languages(&mut self, language: &str, fallback: Vec<String>) {
+        if fallback.is_empty() {
+            self.fallback_languages.remove(language);
+        } else {
+            self.fallback_languages
+                .insert(language.to_owned(), fallback);
+        }
+    }
+
+    /// Languages searched for texts, in order: current language, its fallback languages
+    /// (recursively), its primary language subtag (`en` for `en-US`), then the same for default
+    /// language.
+    pub fn languages_chain(&self) -> Vec<&str> {
+        let mut result = vec![];
+        if let Some(current) = &self.current_language {
+            self.collect_languages_chain(current, &mut result);
+        }
+        if let Some(default) = &self.default_language {
+            self.collect_languages_chain(default, &mut result);
+        }
+        result
+    }
+
+    fn collect_languages_chain<'a>(&'a self, language: &'a str, result: &mut Vec<&'a str>) {
+        if result.contains(&language) {
+            return;
+        }
+        result.push(language);
+        for fallback in self.fallback_languages(language) {
+            self.collect_languages_chain(fallback, result);
+        }
+        let primary = primary_language(language);
+        if primary != language {
+            self.collect_languages_chain(primary, result);
+        }
+    }
+
+    pub fn formats(&self, language: &str) -> LocalizationFormats {
+        self.formats
+            .get(language)
+            .or_else(|| self.formats.get(primary_language(language)))
+            .cloned()
+            .unwrap_or_else(|| LocalizationFormats::for_language(language))
+    }
+
+    pub fn set_formats(&mut self, language: &str, formats: Option<LocalizationFormats>) {
+        match formats {
+            Some(formats) => {
+                self.formats.insert(language.to_owned(), formats);
+            }
+            None => {
+                self.formats.remove(language);
+            }
+        }
+    }
+
+    pub fn plural_rules(&self, language: &str) -> PluralRules {
+        self.plural_rules
+            .get(language)
+            .or_else(|| self.plural_rules.get(primary_language(language)))
+            .copied()
+            .unwrap_or_else(|| PluralRules::for_language(language))
+    }
+
+    pub fn set_plural_rules(&mut self, language: &str, rules: Option<PluralRules>) {
+        match rules {
+            Some(rules) => {
+                self.plural_rules.insert(language.to_owned(), rules);
+            }
+            None => {
+                self.plural_rules.remove(language);
+            }
+        }
+    }
+
     pub fn add_text(&mut self, id: &str, language: &str, text_format: &str) {
         if let Some(map) = self.map.get_mut(id) {
             map.insert(language.to_owned(), text_format.to_owned());
@@ -74,35 +457,95 @@ impl Localization {
         self.map.remove(id).is_some()
     }
 
+    /// Languages that have any text.
+    pub fn languages(&self) -> BTreeSet<&str> {
+        self.map
+            .values()
+            .flat_map(|map| map.keys())
+            .map(|language| language.as_str())
+            .collect()
+    }
+
+    pub fn text_ids(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(|id| id.as_str())
+    }
+
+    pub fn text(&self, id: &str, language: &str) -> Option<&str> {
+        self.map
+            .get(id)
+            .and_then(|map| map.get(language))
+            .map(|text| text.as_str())
+    }
+
+    /// IDs of texts that exist in any other language but are missing in given language.
+    pub fn missing_text_ids(&self, language: &str) -> BTreeSet<&str> {
+        self.map
+            .iter()
+            .filter(|(_, map)| !map.contains_key(language))
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    /// Makes localization asset with all texts of given language.
+    pub fn language_asset(&self, language: &str) -> LocalizationAsset {
+        LocalizationAsset {
+            language: language.to_owned(),
+            dictionary: self
+                .map
+                .iter()
+                .filter_map(|(id, map)| Some((id.to_owned(), map.get(language)?.to_owned())))
+                .collect(),
+            fallback_languages: self.fallback_languages(language).to_vec(),
+            formats: self.formats.get(language).cloned(),
+            plural_rules: self.plural_rules.get(language).copied(),
+        }
+    }
+
     pub fn remove_language(&mut self, lang: &str) {
         for map in self.map.values_mut() {
             map.remove(lang);
         }
+        self.map.retain(|_, map| !map.is_empty());
+        self.fallback_languages.remove(lang);
+        self.formats.remove(lang);
+        self.plural_rules.remove(lang);
     }
 
     pub fn find_text_format(&self, id: &str) -> Option<&str> {
-        if let Some(current) = &self.current_language {
-            if let Some(default) = &self.default_language {
-                if let Some(map) = self.map.get(id) {
-                    return map
-                        .get(current)
-                        .or_else(|| map.get(default))
-                        .or(None)
-                        .as_ref()
-                        .map(|v| v.as_str());
-                }
-            }
-        }
-        None
+        self.find_text_format_with_language(id)
+            .map(|(_, text_format)| text_format)
+    }
+
+    /// Returns language which text format was found in and that text format.
+    pub fn find_text_format_with_language(&self, id: &str) -> Option<(&str, &str)> {
+        let map = self.map.get(id)?;
+        self.languages_chain().into_iter().find_map(|language| {
+            map.get(language)
+                .map(|text_format| (language, text_format.as_str()))
+        })
     }
 
+    /// Formats text with given params. Text format chunks are separated with `|`, where chunks
+    /// starting with `@` are params that can be additionally formatted:
+    /// - `@ident` - param value,
+    /// - `@ident:number` / `@ident:number(2)` - locale number, optionally with fraction digits,
+    /// - `@ident:date` - locale date of UNIX timestamp or `YYYY-MM-DD` value,
+    /// - `@ident:plural =0{none} one{# item} other{# items}` - branch selected by exact value
+    ///   or CLDR plural category of text language, `#` gets replaced with locale number,
+    /// - `@ident:select male{he} female{she} other{they}` - branch selected by value.
+    ///
+    /// Branches can reference other params with `{@ident}` and fall back to `other` branch.
+    /// Numbers and dates are formatted for current language, while plural rules are taken from
+    /// language that text format was found in.
     pub fn format_text(&self, id: &str, params: &[(&str, &str)]) -> Result<String, String> {
-        if let Some(text_format) = self.find_text_format(id) {
+        if let Some((language, text_format)) = self.find_text_format_with_language(id) {
             match parser::SentenceParser::parse(parser::Rule::sentence, text_format) {
                 Ok(mut ast) => {
                     let pair = ast.next().unwrap();
                     match pair.as_rule() {
-                        parser::Rule::sentence => Ok(Self::parse_sentence_inner(pair, params)),
+                        parser::Rule::sentence => {
+                            Ok(self.parse_sentence_inner(pair, params, language))
+                        }
                         _ => unreachable!(),
                     }
                 }
@@ -113,11 +556,120 @@ impl Localization {
         }
     }
 
-    fn parse_sentence_inner(pair: Pair<parser::Rule>, params: &[(&str, &str)]) -> String {
+    fn parse_sentence_inner(
+        &self,
+        pair: Pair<parser::Rule>,
+        params: &[(&str, &str)],
+        language: &str,
+    ) -> String {
         let mut result = String::new();
         for p in pair.into_inner() {
             match p.as_rule() {
                 parser::Rule::text => result.push_str(&p.as_str().replace("\\|", "|")),
+                parser::Rule::param => self.parse_param(p, params, language, &mut result),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    fn parse_param(
+        &self,
+        pair: Pair<parser::Rule>,
+        params: &[(&str, &str)],
+        language: &str,
+        result: &mut String,
+    ) {
+        let mut pairs = pair.into_inner();
+        let ident = pairs.next().unwrap().as_str();
+        let value = match params.iter().find(|(id, _)| id == &ident) {
+            Some((_, value)) => *value,
+            None => {
+                write!(result, "{{@{}}}", ident).unwrap();
+                return;
+            }
+        };
+        let format = match pairs.next() {
+            Some(format) => format,
+            None => {
+                result.push_str(value);
+                return;
+            }
+        };
+        let formats = self.formats(self.current_language.as_deref().unwrap_or(language));
+        match format.as_rule() {
+            parser::Rule::number => {
+                let precision = format
+                    .into_inner()
+                    .next()
+                    .and_then(|p| p.as_str().parse().ok());
+                result.push_str(&formats.format_number(value, precision));
+            }
+            parser::Rule::date => result.push_str(&formats.format_date(value)),
+            parser::Rule::plural => {
+                let operands = PluralOperands::parse(value);
+                let category = operands
+                    .map(|operands| self.plural_rules(language).category(&operands))
+                    .unwrap_or(PluralCategory::Other);
+                let exact = operands.map(|operands| operands.n);
+                let branch = Self::find_branch(format, |key| match key.strip_prefix('=') {
+                    Some(key) => key.parse::<f64>().ok() == exact,
+                    None => key == category.as_str(),
+                });
+                if let Some(branch) = branch {
+                    let number = formats.format_number(value, None);
+                    Self::parse_branch(branch, params, &number, result);
+                }
+            }
+            parser::Rule::select => {
+                if let Some(branch) = Self::find_branch(format, |key| key == value) {
+                    Self::parse_branch(branch, params, value, result);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn find_branch<'a, F>(pair: Pair<'a, parser::Rule>, f: F) -> Option<Pair<'a, parser::Rule>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let branches = pair.into_inner().collect::<Vec<_>>();
+        let key = |branch: &Pair<parser::Rule>| {
+            branch
+                .clone()
+                .into_inner()
+                .next()
+                .map(|key| key.as_str().to_owned())
+                .unwrap_or_default()
+        };
+        branches
+            .iter()
+            .find(|branch| f(&key(*branch)))
+            .or_else(|| branches.iter().find(|branch| key(*branch) == "other"))
+            .cloned()
+    }
+
+    fn parse_branch(
+        pair: Pair<parser::Rule>,
+        params: &[(&str, &str)],
+        hash: &str,
+        result: &mut String,
+    ) {
+        for p in pair.into_inner().skip(1) {
+            match p.as_rule() {
+                parser::Rule::branch_text => {
+                    let mut escaped = false;
+                    for c in p.as_str().chars() {
+                        if escaped || c != '\\' {
+                            result.push(c);
+                            escaped = false;
+                        } else {
+                            escaped = true;
+                        }
+                    }
+                }
+                parser::Rule::branch_hash => result.push_str(hash),
                 parser::Rule::identifier => {
                     let ident = p.as_str();
                     if let Some((_, v)) = params.iter().find(|(id, _)| id == &ident) {
@@ -129,7 +681,6 @@ impl Localization {
                 _ => {}
             }
         }
-        result
     }
 }
 
@@ -144,9 +695,11 @@ macro_rules! localization_format_text {
     }
 }
 
+const LOCALIZATION_PROTOCOLS: [&str; 3] = ["locals", "locals-po", "locals-csv"];
+
 #[derive(Default)]
 pub struct LocalizationSystemCache {
-    language_table: HashMap<AssetId, String>,
+    language_table: HashMap<AssetId, Vec<String>>,
 }
 
 pub type LocalizationSystemResources<'a> = (
@@ -159,22 +712,48 @@ pub fn localization_system(universe: &mut Universe) {
     let (assets, mut localization, mut cache) =
         universe.query_resources::<LocalizationSystemResources>();
 
-    for id in assets.lately_loaded_protocol("locals") {
-        let id = *id;
-        let asset = assets
-            .asset_by_id(id)
-            .expect("trying to use not loaded localization asset");
-        let asset = asset
-            .get::<LocalizationAsset>()
-            .expect("trying to use non-localization asset");
-        for (k, v) in &asset.dictionary {
-            localization.add_text(k, &asset.language, v);
-        }
-        cache.language_table.insert(id, asset.language.clone());
-    }
-    for id in assets.lately_unloaded_protocol("locals") {
-        if let Some(name) = cache.language_table.remove(id) {
-            localization.remove_language(&name);
+    for protocol in LOCALIZATION_PROTOCOLS {
+        for id in assets.lately_reloaded_protocol(protocol) {
+            if let Some(names) = cache.language_table.remove(id) {
+                for name in names {
+                    localization.remove_language(&name);
+                }
+            }
+        }
+        for id in assets
+            .lately_loaded_protocol(protocol)
+            .chain(assets.lately_reloaded_protocol(protocol))
+        {
+            let id = *id;
+            let asset = assets
+                .asset_by_id(id)
+                .expect("trying to use not loaded localization asset");
+            let languages = if let Some(asset) = asset.get::<LocalizationAsset>() {
+                vec![asset]
+            } else if let Some(asset) = asset.get::<LocalizationTableAsset>() {
+                asset.languages.iter().collect()
+            } else {
+                panic!("trying to use non-localization asset");
+            };
+            let mut names = Vec::with_capacity(languages.len());
+            for asset in languages {
+                for (k, v) in &asset.dictionary {
+                    localization.add_text(k, &asset.language, v);
+                }
+                localization
+                    .set_fallback_languages(&asset.language, asset.fallback_languages.clone());
+                localization.set_formats(&asset.language, asset.formats.clone());
+                localization.set_plural_rules(&asset.language, asset.plural_rules);
+                names.push(asset.language.clone());
+            }
+            cache.language_table.insert(id, names);
+        }
+        for id in assets.lately_unloaded_protocol(protocol) {
+            if let Some(names) = cache.language_table.remove(id) {
+                for name in names {
+                    localization.remove_language(&name);
+                }
+            }
         }
     }
 }
@@ -16,6 +16,9 @@ use std::collections::HashMap;
 
 pub use serde_json::Value as PrefabValue;
 
+/// Prefix of UIDs generated for snapshot entities without unique name.
+pub const PREFAB_SNAPSHOT_UID_PREFIX: &str = "#";
+
 type ComponentFactory = Box<
     dyn Fn(
             &mut EntityBuilder,
@@ -27,10 +30,22 @@ type ComponentFactory = Box<
         + Sync,
 >;
 
+type ComponentSerializer = Box<
+    dyn Fn(&World, Entity, &HashMap<Entity, String>) -> Option<Result<PrefabValue, PrefabError>>
+        + Send
+        + Sync,
+>;
+
 #[derive(Debug)]
 pub enum PrefabError {
     CouldNotSerialize(String),
     CouldNotDeserialize(String),
+    /// component type name - such components are skipped when serializing entities.
+    NotSerializable(String),
+    /// component name.
+    UnknownComponent(String),
+    /// (template name, entity UID)
+    UnknownEntity(String, String),
     Custom(String),
 }
 
@@ -91,15 +106,54 @@ where
         named_entities: &HashMap<String, Entity>,
         state_token: StateToken,
     ) -> Result<Self, PrefabError>;
+
+    /// Converts component back into its proxy, `entity_names` maps entities to their scene UIDs.
+    /// By default components behind proxies cannot be serialized and are omitted from snapshots.
+    fn into_proxy_with_extras(
+        &self,
+        _entity_names: &HashMap<Entity, String>,
+    ) -> Result<P, PrefabError> {
+        Err(PrefabError::NotSerializable(
+            std::any::type_name::<Self>().to_owned(),
+        ))
+    }
 }
 
 impl Prefab for PrefabValue {}
 pub trait PrefabComponent: Prefab + Component {}
 
+/// Applies JSON merge patch (RFC 7386) to `target`: objects are merged recursively, `null` fields
+/// of patch remove fields from target and any other values replace target values.
+pub fn prefab_merge_patch(target: &mut PrefabValue, patch: &PrefabValue) {
+    if let PrefabValue::Object(patch) = patch {
+        if !target.is_object() {
+            *target = PrefabValue::Object(Default::default());
+        }
+        if let Some(target) = target.as_object_mut() {
+            for (key, value) in patch {
+                if value.is_null() {
+                    target.remove(key);
+                } else {
+                    prefab_merge_patch(
+                        target.entry(key.to_owned()).or_insert(PrefabValue::Null),
+                        value,
+                    );
+                }
+            }
+        }
+    } else {
+        *target = patch.clone();
+    }
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct PrefabScene {
     #[serde(default)]
     pub template_name: Option<String>,
+    /// Name of template this template extends. Entities with UIDs matching base template entities
+    /// patch their components, other entities are appended to base template entities.
+    #[serde(default)]
+    pub extends: Option<String>,
     #[serde(default)]
     pub dependencies: Vec<String>,
     #[serde(default)]
@@ -110,6 +164,7 @@ pub struct PrefabScene {
 pub enum PrefabSceneEntity {
     Data(PrefabSceneEntityData),
     Template(String),
+    Instance(PrefabSceneInstance),
 }
 
 impl Default for PrefabSceneEntity {
@@ -131,9 +186,21 @@ pub struct PrefabSceneEntityData {
 
 impl Prefab for PrefabSceneEntityData {}
 
+/// Template instantiated with per-instance component overrides.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PrefabSceneInstance {
+    pub template: String,
+    /// {entity UID: {component name: component merge patch}}
+    #[serde(default)]
+    pub overrides: HashMap<String, HashMap<String, PrefabValue>>,
+}
+
+impl Prefab for PrefabSceneInstance {}
+
 #[derive(Default)]
 pub struct PrefabManager {
     component_factory: HashMap<String, ComponentFactory>,
+    component_serializer: HashMap<String, ComponentSerializer>,
     templates: HashMap<String, PrefabScene>,
 }
 
@@ -153,6 +220,15 @@ impl PrefabManager {
                 Ok(())
             }),
         );
+        self.component_serializer.insert(
+            name.to_owned(),
+            Box::new(|world, entity, _| {
+                world
+                    .get::<&T>(entity)
+                    .ok()
+                    .map(|component| component.to_prefab())
+            }),
+        );
     }
 
     pub fn register_component_factory_proxy<T, P>(&mut self, name: &str)
@@ -168,10 +244,49 @@ impl PrefabManager {
                 Ok(())
             }),
         );
+        self.component_serializer.insert(
+            name.to_owned(),
+            Box::new(|world, entity, entity_names| {
+                let component = world.get::<&T>(entity).ok()?;
+                match component.into_proxy_with_extras(entity_names) {
+                    Ok(proxy) => Some(proxy.to_prefab()),
+                    Err(PrefabError::NotSerializable(_)) => None,
+                    Err(error) => Some(Err(error)),
+                }
+            }),
+        );
     }
 
     pub fn unregister_component_factory(&mut self, name: &str) {
         self.component_factory.remove(name);
+        self.component_serializer.remove(name);
+    }
+
+    pub fn component_names(&self) -> impl Iterator<Item = &str> {
+        self.component_factory.keys().map(|name| name.as_str())
+    }
+
+    pub fn has_component_factory(&self, name: &str) -> bool {
+        self.component_factory.contains_key(name)
+    }
+
+    /// Serializes component registered under `name` of given entity.
+    /// Returns `None` if entity does not have that component or it cannot be serialized.
+    /// `entity_names` maps entities to their scene UIDs, used by proxies referencing entities.
+    pub fn serialize_component(
+        &self,
+        name: &str,
+        world: &World,
+        entity: Entity,
+        entity_names: &HashMap<Entity, String>,
+    ) -> Option<Result<PrefabValue, PrefabError>> {
+        match self.component_serializer.get(name) {
+            Some(serializer) => serializer(world, entity, entity_names),
+            None => Some(Err(PrefabError::CouldNotSerialize(format!(
+                "Could not find component serializer: {}",
+                name
+            )))),
+        }
     }
 
     pub fn register_scene_template(&mut self, prefab: PrefabScene) -> Result<(), PrefabError> {
@@ -200,6 +315,79 @@ impl PrefabManager {
         self.templates.get(name)
     }
 
+    /// Returns template with all templates it extends applied.
+    pub fn resolve_template(&self, name: &str) -> Result<PrefabScene, PrefabError> {
+        self.resolve_template_inner(name, &mut vec![])
+    }
+
+    fn resolve_template_inner(
+        &self,
+        name: &str,
+        chain: &mut Vec<String>,
+    ) -> Result<PrefabScene, PrefabError> {
+        if chain.iter().any(|item| item == name) {
+            return Err(PrefabError::Custom(format!(
+                "Template inheritance cycle: {} -> {}",
+                chain.join(" -> "),
+                name
+            )));
+        }
+        let template = match self.templates.get(name) {
+            Some(template) => template,
+            None => {
+                return Err(PrefabError::Custom(format!(
+                    "There is no template registered: {}",
+                    name
+                )))
+            }
+        };
+        let base = match &template.extends {
+            Some(base) => {
+                chain.push(name.to_owned());
+                let base = self.resolve_template_inner(base, chain)?;
+                chain.pop();
+                base
+            }
+            None => return Ok(template.clone()),
+        };
+        let mut result = PrefabScene {
+            template_name: template.template_name.clone(),
+            extends: None,
+            dependencies: base.dependencies,
+            entities: base.entities,
+        };
+        for dependency in &template.dependencies {
+            if !result.dependencies.contains(dependency) {
+                result.dependencies.push(dependency.to_owned());
+            }
+        }
+        for entity in &template.entities {
+            if let PrefabSceneEntity::Data(data) = entity {
+                let index = data.uid.as_ref().and_then(|uid| {
+                    result.entities.iter().position(|entity| match entity {
+                        PrefabSceneEntity::Data(base) => base.uid.as_ref() == Some(uid),
+                        _ => false,
+                    })
+                });
+                if let Some(index) = index {
+                    if let PrefabSceneEntity::Data(base) = &mut result.entities[index] {
+                        self.patch_components(&mut base.components, &data.components)?;
+                    }
+                    continue;
+                }
+                if let Some(name) = data
+                    .components
+                    .keys()
+                    .find(|name| !self.component_factory.contains_key(name.as_str()))
+                {
+                    return Err(PrefabError::UnknownComponent(name.to_owned()));
+                }
+            }
+            result.entities.push(entity.to_owned());
+        }
+        Ok(result)
+    }
+
     pub fn instantiate(
         &mut self,
         name: &str,
@@ -221,7 +409,14 @@ impl PrefabManager {
         state_token: StateToken,
     ) -> Result<Vec<Entity>, PrefabError> {
         Ok(self
-            .build_template(name, world, changes, state_token, &Default::default())?
+            .build_template(
+                name,
+                &Default::default(),
+                world,
+                changes,
+                state_token,
+                &Default::default(),
+            )?
             .0)
     }
 
@@ -279,8 +474,28 @@ impl PrefabManager {
                     result_entities.push(entity);
                 }
                 PrefabSceneEntity::Template(name) => {
-                    let (entities, uids) =
-                        self.build_template(name, world, changes, state_token, &named_entities)?;
+                    let (entities, uids) = self.build_template(
+                        name,
+                        &Default::default(),
+                        world,
+                        changes,
+                        state_token,
+                        &named_entities,
+                    )?;
+                    for (uid, entity) in uids {
+                        named_entities.insert(uid.to_owned(), entity);
+                    }
+                    result_entities.extend(entities);
+                }
+                PrefabSceneEntity::Instance(instance) => {
+                    let (entities, uids) = self.build_template(
+                        &instance.template,
+                        &instance.overrides,
+                        world,
+                        changes,
+                        state_token,
+                        &named_entities,
+                    )?;
                     for (uid, entity) in uids {
                         named_entities.insert(uid.to_owned(), entity);
                     }
@@ -291,6 +506,107 @@ impl PrefabManager {
         Ok((result_entities, named_entities))
     }
 
+    /// Serializes given entities with all registered component factories into scene that can
+    /// be loaded back with `load_scene_from_prefab`. Parents are always put before children and
+    /// entities which parent is not part of snapshot become its roots. `Name` of entity is used
+    /// as its UID when it is unique among snapshot entities and does not start with
+    /// `PREFAB_SNAPSHOT_UID_PREFIX`, reserved for generated UIDs.
+    /// Components that do not support conversion into their proxies are omitted.
+    pub fn snapshot_scene(
+        &self,
+        entities: &[Entity],
+        universe: &Universe,
+    ) -> Result<PrefabScene, PrefabError> {
+        self.snapshot_scene_direct(entities, &universe.world())
+    }
+
+    pub fn snapshot_scene_direct(
+        &self,
+        entities: &[Entity],
+        world: &World,
+    ) -> Result<PrefabScene, PrefabError> {
+        self.snapshot_scene_filtered(entities, world, |_| true)
+    }
+
+    /// Same as `snapshot_scene_direct` but serializes only components which names pass `filter`.
+    pub fn snapshot_scene_filtered<F>(
+        &self,
+        entities: &[Entity],
+        world: &World,
+        filter: F,
+    ) -> Result<PrefabScene, PrefabError>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let mut ordered = entities
+            .iter()
+            .enumerate()
+            .map(|(index, entity)| {
+                let mut depth = 0;
+                let mut current = *entity;
+                while let Ok(parent) = world.get::<&Parent>(current) {
+                    if depth > entities.len() {
+                        break;
+                    }
+                    depth += 1;
+                    current = parent.0;
+                }
+                (depth, index, *entity)
+            })
+            .collect::<Vec<_>>();
+        ordered.sort_by_key(|(depth, index, _)| (*depth, *index));
+        let mut names_count = HashMap::<String, usize>::with_capacity(entities.len());
+        for entity in entities {
+            if let Ok(name) = world.get::<&Name>(*entity) {
+                *names_count.entry(name.0.to_string()).or_default() += 1;
+            }
+        }
+        let entity_names = ordered
+            .iter()
+            .map(|(_, index, entity)| {
+                let name = world
+                    .get::<&Name>(*entity)
+                    .ok()
+                    .map(|name| name.0.to_string())
+                    .filter(|name| {
+                        !name.starts_with(PREFAB_SNAPSHOT_UID_PREFIX)
+                            && names_count.get(name).copied().unwrap_or_default() == 1
+                    })
+                    .unwrap_or_else(|| format!("{}{}", PREFAB_SNAPSHOT_UID_PREFIX, index));
+                (*entity, name)
+            })
+            .collect::<HashMap<_, _>>();
+        let mut names = self
+            .component_serializer
+            .keys()
+            .filter(|name| filter(name))
+            .collect::<Vec<_>>();
+        names.sort();
+        let mut result = Vec::with_capacity(ordered.len());
+        for (_, _, entity) in ordered {
+            if !world.contains(entity) {
+                return Err(PrefabError::CouldNotSerialize(format!(
+                    "Entity does not exists: {:?}",
+                    entity
+                )));
+            }
+            let mut components = HashMap::with_capacity(names.len());
+            for name in &names {
+                if let Some(data) = self.serialize_component(name, world, entity, &entity_names) {
+                    components.insert(name.to_string(), data?);
+                }
+            }
+            result.push(PrefabSceneEntity::Data(PrefabSceneEntityData {
+                uid: entity_names.get(&entity).cloned(),
+                components,
+            }));
+        }
+        Ok(PrefabScene {
+            entities: result,
+            ..Default::default()
+        })
+    }
+
     fn build_entity(
         &mut self,
         components: &HashMap<String, PrefabValue>,
@@ -327,19 +643,49 @@ impl PrefabManager {
     fn build_template(
         &mut self,
         name: &str,
+        overrides: &HashMap<String, HashMap<String, PrefabValue>>,
         world: &mut World,
         changes: &mut EntityChanges,
         state_token: StateToken,
         named_entities: &HashMap<String, Entity>,
     ) -> Result<(Vec<Entity>, HashMap<String, Entity>), PrefabError> {
-        if let Some(prefab) = self.templates.get(name).cloned() {
-            self.load_scene_from_prefab_inner(&prefab, world, changes, state_token, named_entities)
-        } else {
-            Err(PrefabError::Custom(format!(
-                "There is no template registered: {}",
-                name
-            )))
+        let mut prefab = self.resolve_template(name)?;
+        for (uid, patch) in overrides {
+            let data = prefab
+                .entities
+                .iter_mut()
+                .find_map(|entity| match entity {
+                    PrefabSceneEntity::Data(data) if data.uid.as_ref() == Some(uid) => Some(data),
+                    _ => None,
+                })
+                .ok_or_else(|| PrefabError::UnknownEntity(name.to_owned(), uid.to_owned()))?;
+            self.patch_components(&mut data.components, patch)?;
+        }
+        self.load_scene_from_prefab_inner(&prefab, world, changes, state_token, named_entities)
+    }
+
+    fn patch_components(
+        &self,
+        components: &mut HashMap<String, PrefabValue>,
+        patch: &HashMap<String, PrefabValue>,
+    ) -> Result<(), PrefabError> {
+        for (name, value) in patch {
+            if !self.component_factory.contains_key(name) {
+                return Err(PrefabError::UnknownComponent(name.to_owned()));
+            }
+            if value.is_null() {
+                components.remove(name);
+                continue;
+            }
+            // Patch of missing component is applied to empty value, so it gets stripped of nulls.
+            prefab_merge_patch(
+                components
+                    .entry(name.to_owned())
+                    .or_insert(PrefabValue::Null),
+                value,
+            );
         }
+        Ok(())
     }
 }
 
@@ -356,7 +702,15 @@ pub type PrefabSystemResources<'a> = (
 pub fn prefab_system(universe: &mut Universe) {
     let (assets, mut prefabs, mut cache) = universe.query_resources::<PrefabSystemResources>();
 
-    for id in assets.lately_loaded_protocol("prefab") {
+    for id in assets.lately_reloaded_protocol("prefab") {
+        if let Some(name) = cache.templates_table.remove(id) {
+            prefabs.unregister_scene_template(&name);
+        }
+    }
+    for id in assets
+        .lately_loaded_protocol("prefab")
+        .chain(assets.lately_reloaded_protocol("prefab"))
+    {
         let id = *id;
         let asset = assets
             .asset_by_id(id)
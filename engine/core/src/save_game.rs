@@ -0,0 +1,578 @@
+use crate::{
+    app::AppLifeCycle,
+    ecs::{life_cycle::EntityChanges, Entity, Universe},
+    prefab::{
+        Prefab, PrefabError, PrefabManager, PrefabScene, PrefabSceneEntity, PrefabSceneEntityData,
+        PrefabValue,
+    },
+    state::StateToken,
+    storage::{StorageEngine, StorageError},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+pub type SaveGameVersion = u32;
+
+type ResourceSerializer =
+    Box<dyn Fn(&Universe) -> Option<Result<PrefabValue, PrefabError>> + Send + Sync>;
+type ResourceDeserializer =
+    Box<dyn Fn(&Universe, &PrefabValue) -> Option<Result<(), PrefabError>> + Send + Sync>;
+type SaveGameMigration = Box<dyn Fn(&mut SaveGameData) -> Result<(), SaveGameError> + Send + Sync>;
+type TimestampProvider = Box<dyn Fn() -> u64 + Send + Sync>;
+
+#[derive(Debug)]
+pub enum SaveGameError {
+    Storage(StorageError),
+    Prefab(PrefabError),
+    /// slot name.
+    SlotNotFound(String),
+    /// (save version, current version)
+    UnsupportedVersion(SaveGameVersion, SaveGameVersion),
+    /// save version that has no registered migration to next version.
+    MissingMigration(SaveGameVersion),
+    /// resource name.
+    MissingResource(String),
+    Custom(String),
+}
+
+impl From<StorageError> for SaveGameError {
+    fn from(error: StorageError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<PrefabError> for SaveGameError {
+    fn from(error: PrefabError) -> Self {
+        Self::Prefab(error)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGameMetadata {
+    pub slot: String,
+    /// Seconds since UNIX epoch.
+    pub timestamp: u64,
+    pub game_version: String,
+    pub save_version: SaveGameVersion,
+    #[serde(default)]
+    pub has_thumbnail: bool,
+}
+
+impl Prefab for SaveGameMetadata {}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct SaveGameData {
+    #[serde(default)]
+    pub resources: HashMap<String, PrefabValue>,
+    /// Entities are ordered so that parents always come before their children.
+    #[serde(default)]
+    pub entities: Vec<PrefabSceneEntityData>,
+}
+
+impl Prefab for SaveGameData {}
+
+impl SaveGameData {
+    pub fn to_scene(&self) -> PrefabScene {
+        PrefabScene {
+            entities: self
+                .entities
+                .iter()
+                .cloned()
+                .map(PrefabSceneEntity::Data)
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Stores selected components and resources of universe in named slots of storage engine.
+///
+/// Storage layout (relative to `prefix`):
+/// - `index.json` - metadata of all slots,
+/// - `<slot>.json` - serialized `SaveGameData`,
+/// - `<slot>.thumbnail` - raw thumbnail bytes.
+///
+/// Components are serialized with factories registered in `PrefabManager` resource, so component
+/// names must match names of registered component factories.
+pub struct SaveGameManager {
+    storage: Box<dyn StorageEngine>,
+    save_version: SaveGameVersion,
+    components: Vec<String>,
+    resources: HashMap<String, (ResourceSerializer, ResourceDeserializer)>,
+    migrations: HashMap<SaveGameVersion, SaveGameMigration>,
+    timestamp_provider: TimestampProvider,
+    pub prefix: String,
+    pub game_version: String,
+}
+
+impl SaveGameManager {
+    pub fn new<S>(storage: S, save_version: SaveGameVersion) -> Self
+    where
+        S: StorageEngine + 'static,
+    {
+        Self {
+            storage: Box::new(storage),
+            save_version,
+            components: vec![],
+            resources: Default::default(),
+            migrations: Default::default(),
+            timestamp_provider: Box::new(Self::default_timestamp),
+            prefix: "saves/".to_owned(),
+            game_version: Default::default(),
+        }
+    }
+
+    pub fn with_prefix(mut self, prefix: impl ToString) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn with_game_version(mut self, game_version: impl ToString) -> Self {
+        self.game_version = game_version.to_string();
+        self
+    }
+
+    /// Replaces source of save timestamps (seconds since UNIX epoch).
+    /// On web targets default provider always returns 0.
+    pub fn with_timestamp_provider<F>(mut self, f: F) -> Self
+    where
+        F: Fn() -> u64 + Send + Sync + 'static,
+    {
+        self.timestamp_provider = Box::new(f);
+        self
+    }
+
+    pub fn save_version(&self) -> SaveGameVersion {
+        self.save_version
+    }
+
+    pub fn storage(&self) -> &dyn StorageEngine {
+        self.storage.as_ref()
+    }
+
+    pub fn storage_mut(&mut self) -> &mut dyn StorageEngine {
+        self.storage.as_mut()
+    }
+
+    pub fn register_component(&mut self, name: &str) {
+        if !self.components.iter().any(|n| n == name) {
+            self.components.push(name.to_owned());
+        }
+    }
+
+    pub fn unregister_component(&mut self, name: &str) {
+        self.components.retain(|n| n != name);
+    }
+
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.components.iter().map(|name| name.as_str())
+    }
+
+    /// Resources are loaded in place, so they must already exist in universe when loading.
+    pub fn register_resource<T>(&mut self, name: &str)
+    where
+        T: Prefab + Send + Sync + 'static,
+    {
+        self.resources.insert(
+            name.to_owned(),
+            (
+                Box::new(|universe| universe.resource::<T>().map(|res| res.to_prefab())),
+                Box::new(|universe, data| {
+                    universe.resource_mut::<T>().map(|mut res| {
+                        *res = T::from_prefab(data)?;
+                        Ok(())
+                    })
+                }),
+            ),
+        );
+    }
+
+    pub fn unregister_resource(&mut self, name: &str) {
+        self.resources.remove(name);
+    }
+
+    pub fn resources(&self) -> impl Iterator<Item = &str> {
+        self.resources.keys().map(|name| name.as_str())
+    }
+
+    /// Registers migration of save data from `version` to `version + 1`.
+    pub fn register_migration<F>(&mut self, version: SaveGameVersion, f: F)
+    where
+        F: Fn(&mut SaveGameData) -> Result<(), SaveGameError> + Send + Sync + 'static,
+    {
+        self.migrations.insert(version, Box::new(f));
+    }
+
+    pub fn unregister_migration(&mut self, version: SaveGameVersion) {
+        self.migrations.remove(&version);
+    }
+
+    pub fn slots(&mut self) -> Result<Vec<SaveGameMetadata>, SaveGameError> {
+        Ok(self.load_index()?.into_values().collect())
+    }
+
+    pub fn has_slot(&mut self, slot: &str) -> Result<bool, SaveGameError> {
+        Ok(self.load_index()?.contains_key(slot))
+    }
+
+    pub fn metadata(&mut self, slot: &str) -> Result<SaveGameMetadata, SaveGameError> {
+        self.load_index()?
+            .remove(slot)
+            .ok_or_else(|| SaveGameError::SlotNotFound(slot.to_owned()))
+    }
+
+    pub fn thumbnail(&mut self, slot: &str) -> Result<Option<Vec<u8>>, SaveGameError> {
+        if self.metadata(slot)?.has_thumbnail {
+            let path = self.slot_path(slot, "thumbnail");
+            Ok(Some(self.storage.load(&path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Removes slot from index. Storage engines cannot delete data, so slot data is cleared.
+    pub fn delete(&mut self, slot: &str) -> Result<(), SaveGameError> {
+        let mut index = self.load_index()?;
+        let metadata = index
+            .remove(slot)
+            .ok_or_else(|| SaveGameError::SlotNotFound(slot.to_owned()))?;
+        self.store_index(&index)?;
+        let path = self.slot_path(slot, "json");
+        self.storage.store(&path, &[])?;
+        if metadata.has_thumbnail {
+            let path = self.slot_path(slot, "thumbnail");
+            self.storage.store(&path, &[])?;
+        }
+        Ok(())
+    }
+
+    pub fn save(
+        &mut self,
+        slot: &str,
+        universe: &Universe,
+        thumbnail: Option<&[u8]>,
+    ) -> Result<SaveGameMetadata, SaveGameError> {
+        let data = self.snapshot(universe)?;
+        self.save_data(slot, &data, thumbnail)
+    }
+
+    pub fn save_data(
+        &mut self,
+        slot: &str,
+        data: &SaveGameData,
+        thumbnail: Option<&[u8]>,
+    ) -> Result<SaveGameMetadata, SaveGameError> {
+        let metadata = SaveGameMetadata {
+            slot: slot.to_owned(),
+            timestamp: (self.timestamp_provider)(),
+            game_version: self.game_version.to_owned(),
+            save_version: self.save_version,
+            has_thumbnail: thumbnail.is_some(),
+        };
+        let bytes = serde_json::to_vec(data)
+            .map_err(|error| PrefabError::CouldNotSerialize(error.to_string()))?;
+        let path = self.slot_path(slot, "json");
+        self.storage.store(&path, &bytes)?;
+        if let Some(thumbnail) = thumbnail {
+            let path = self.slot_path(slot, "thumbnail");
+            self.storage.store(&path, thumbnail)?;
+        }
+        let mut index = self.load_index()?;
+        index.insert(slot.to_owned(), metadata.clone());
+        self.store_index(&index)?;
+        Ok(metadata)
+    }
+
+    /// Loads slot into universe, returns slot metadata and spawned entities.
+    /// Saves made with older save versions are migrated before being applied.
+    pub fn load(
+        &mut self,
+        slot: &str,
+        universe: &Universe,
+    ) -> Result<(SaveGameMetadata, Vec<Entity>), SaveGameError> {
+        let (metadata, data) = self.load_data(slot)?;
+        let entities = self.apply(&data, universe)?;
+        Ok((metadata, entities))
+    }
+
+    /// Reads slot data already migrated to current save version.
+    pub fn load_data(
+        &mut self,
+        slot: &str,
+    ) -> Result<(SaveGameMetadata, SaveGameData), SaveGameError> {
+        let metadata = self.metadata(slot)?;
+        let path = self.slot_path(slot, "json");
+        let bytes = self.storage.load(&path)?;
+        let mut data = serde_json::from_slice::<SaveGameData>(&bytes)
+            .map_err(|error| PrefabError::CouldNotDeserialize(error.to_string()))?;
+        self.migrate(&mut data, metadata.save_version)?;
+        Ok((metadata, data))
+    }
+
+    pub fn migrate(
+        &self,
+        data: &mut SaveGameData,
+        version: SaveGameVersion,
+    ) -> Result<(), SaveGameError> {
+        if version > self.save_version {
+            return Err(SaveGameError::UnsupportedVersion(
+                version,
+                self.save_version,
+            ));
+        }
+        // Data is changed only when whole migration chain succeeds.
+        let migrations = (version..self.save_version)
+            .map(|version| {
+                self.migrations
+                    .get(&version)
+                    .ok_or(SaveGameError::MissingMigration(version))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if migrations.is_empty() {
+            return Ok(());
+        }
+        let mut result = data.clone();
+        for migration in migrations {
+            migration(&mut result)?;
+        }
+        *data = result;
+        Ok(())
+    }
+
+    /// Serializes registered resources and components of all entities that have any of them.
+    pub fn snapshot(&self, universe: &Universe) -> Result<SaveGameData, SaveGameError> {
+        let selected = {
+            let prefabs = universe.expect_resource::<PrefabManager>();
+            let world = universe.world();
+            // Every entity gets a name so components referencing other entities are detected.
+            let entity_names = world
+                .iter()
+                .map(|entity_ref| (entity_ref.entity(), String::new()))
+                .collect::<HashMap<_, _>>();
+            world
+                .iter()
+                .map(|entity_ref| entity_ref.entity())
+                .filter(|entity| {
+                    self.components.iter().any(|name| {
+                        prefabs
+                            .serialize_component(name, &world, *entity, &entity_names)
+                            .is_some()
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        self.snapshot_entities(universe, &selected)
+    }
+
+    /// Serializes registered resources and registered components of given entities only.
+    /// Entities which parent is not among given entities are saved as roots.
+    pub fn snapshot_entities(
+        &self,
+        universe: &Universe,
+        entities: &[Entity],
+    ) -> Result<SaveGameData, SaveGameError> {
+        let mut resources = HashMap::with_capacity(self.resources.len());
+        for (name, (serializer, _)) in &self.resources {
+            match serializer(universe) {
+                Some(data) => {
+                    resources.insert(name.to_owned(), data?);
+                }
+                None => return Err(SaveGameError::MissingResource(name.to_owned())),
+            }
+        }
+        let prefabs = universe.expect_resource::<PrefabManager>();
+        let world = universe.world();
+        let entities = prefabs
+            .snapshot_scene_filtered(entities, &world, |name| {
+                self.components.iter().any(|n| n == name)
+            })?
+            .entities
+            .into_iter()
+            .filter_map(|entity| match entity {
+                PrefabSceneEntity::Data(data) => Some(data),
+                _ => None,
+            })
+            .collect();
+        Ok(SaveGameData {
+            resources,
+            entities,
+        })
+    }
+
+    /// Overwrites registered resources and spawns saved entities, returns spawned entities.
+    pub fn apply(
+        &self,
+        data: &SaveGameData,
+        universe: &Universe,
+    ) -> Result<Vec<Entity>, SaveGameError> {
+        for (name, value) in &data.resources {
+            if let Some((_, deserializer)) = self.resources.get(name) {
+                match deserializer(universe, value) {
+                    Some(result) => result?,
+                    None => return Err(SaveGameError::MissingResource(name.to_owned())),
+                }
+            }
+        }
+        let state_token = universe
+            .resource::<AppLifeCycle>()
+            .map(|lifecycle| lifecycle.current_state_token())
+            .unwrap_or_else(StateToken::new);
+        let entities = universe
+            .expect_resource_mut::<PrefabManager>()
+            .load_scene_from_prefab_direct(
+                &data.to_scene(),
+                &mut universe.world_mut(),
+                &mut universe.expect_resource_mut::<EntityChanges>(),
+                state_token,
+            )?;
+        Ok(entities)
+    }
+
+    fn slot_path(&self, slot: &str, extension: &str) -> String {
+        format!("{}{}.{}", self.prefix, slot, extension)
+    }
+
+    fn load_index(&mut self) -> Result<BTreeMap<String, SaveGameMetadata>, SaveGameError> {
+        let path = format!("{}index.json", self.prefix);
+        match self.storage.load(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|error| PrefabError::CouldNotDeserialize(error.to_string()).into()),
+            Err(_) => Ok(Default::default()),
+        }
+    }
+
+    fn store_index(
+        &mut self,
+        index: &BTreeMap<String, SaveGameMetadata>,
+    ) -> Result<(), SaveGameError> {
+        let path = format!("{}index.json", self.prefix);
+        let bytes = serde_json::to_vec_pretty(index)
+            .map_err(|error| PrefabError::CouldNotSerialize(error.to_string()))?;
+        self.storage.store(&path, &bytes)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "web"))]
+    fn default_timestamp() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "web")]
+    fn default_timestamp() -> u64 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ecs::{
+            components::Name,
+            hierarchy::{Parent, ParentPrefabProxy},
+        },
+        storage::engines::map::MapStorageEngine,
+    };
+    use std::borrow::Cow;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Score(u32);
+
+    impl Prefab for Score {}
+
+    fn make_universe() -> Universe {
+        let mut prefabs = PrefabManager::default();
+        prefabs.register_component_factory::<Name>("Name");
+        prefabs.register_component_factory_proxy::<Parent, ParentPrefabProxy>("Parent");
+        let mut universe = Universe::default();
+        universe.insert_resource(prefabs);
+        universe.insert_resource(EntityChanges::default());
+        universe.insert_resource(Score::default());
+        universe
+    }
+
+    #[test]
+    fn test_save_game() {
+        let mut manager = SaveGameManager::new(MapStorageEngine::default(), 1)
+            .with_game_version("1.0.0")
+            .with_timestamp_provider(|| 42);
+        manager.register_component("Name");
+        manager.register_component("Parent");
+        manager.register_resource::<Score>("Score");
+
+        let universe = make_universe();
+        let child = {
+            let mut world = universe.world_mut();
+            let root = world.spawn((Name(Cow::Borrowed("root")),));
+            let child = world.spawn((Name(Cow::Borrowed("child")), Parent(root)));
+            world.spawn((42u8,));
+            child
+        };
+        universe.expect_resource_mut::<Score>().0 = 7;
+        let metadata = manager.save("a", &universe, Some(&[1, 2, 3])).unwrap();
+        assert_eq!(metadata.timestamp, 42);
+        assert_eq!(metadata.game_version, "1.0.0");
+        assert_eq!(metadata.save_version, 1);
+        assert_eq!(manager.slots().unwrap().len(), 1);
+        assert_eq!(manager.thumbnail("a").unwrap(), Some(vec![1, 2, 3]));
+
+        let partial = manager.snapshot_entities(&universe, &[child]).unwrap();
+        assert_eq!(partial.entities.len(), 1);
+        assert_eq!(
+            partial.entities[0].components.get("Name"),
+            Some(&PrefabValue::from("child"))
+        );
+        assert!(!partial.entities[0].components.contains_key("Parent"));
+
+        let (_, data) = manager.load_data("a").unwrap();
+        assert_eq!(data.entities.len(), 2);
+        assert_eq!(
+            data.entities[0].components.get("Name"),
+            Some(&PrefabValue::from("root"))
+        );
+        assert_eq!(
+            data.entities[1].components.get("Parent"),
+            Some(&PrefabValue::from(data.entities[0].uid.clone().unwrap()))
+        );
+
+        let universe = make_universe();
+        let (_, entities) = manager.load("a", &universe).unwrap();
+        assert_eq!(entities.len(), 2);
+        assert_eq!(universe.expect_resource::<Score>().0, 7);
+        {
+            let world = universe.world();
+            let parent = world.get::<&Parent>(entities[1]).unwrap();
+            assert_eq!(parent.0, entities[0]);
+            assert_eq!(world.get::<&Name>(entities[0]).unwrap().0, "root");
+        }
+
+        let mut manager = SaveGameManager::new(MapStorageEngine::default(), 3);
+        manager.register_migration(1, |data| {
+            let score = data.resources.remove("Score").unwrap_or_default();
+            data.resources.insert("Points".to_owned(), score);
+            Ok(())
+        });
+        let mut migrated = data.clone();
+        assert!(matches!(
+            manager.migrate(&mut migrated, 1),
+            Err(SaveGameError::MissingMigration(2))
+        ));
+        assert!(migrated.resources.contains_key("Score"));
+        assert!(!migrated.resources.contains_key("Points"));
+        manager.register_migration(2, |_| Ok(()));
+        let mut migrated = data.clone();
+        manager.migrate(&mut migrated, 1).unwrap();
+        assert!(!migrated.resources.contains_key("Score"));
+        assert_eq!(
+            migrated.resources.get("Points"),
+            Some(&PrefabValue::from(7))
+        );
+        assert!(matches!(
+            manager.migrate(&mut migrated, 4),
+            Err(SaveGameError::UnsupportedVersion(4, 3))
+        ));
+    }
+}
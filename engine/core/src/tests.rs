@@ -5,7 +5,7 @@ use crate::{
     assets::{database::AssetsDatabase, protocols::prefab::PrefabAsset},
     ecs::{
         commands::{DespawnEntity, SpawnEntity, UniverseCommand},
-        components::Name,
+        components::{Name, Tag},
         hierarchy::{Hierarchy, Parent},
         life_cycle::EntityChanges,
         pipeline::{engines::sequence::SequencePipelineEngine, LinearPipelineBuilder},
@@ -15,10 +15,12 @@ use crate::{
     localization::Localization,
     log::{logger_setup, DefaultLogger},
     prefab::{
-        Prefab, PrefabManager, PrefabScene, PrefabSceneEntity, PrefabSceneEntityData, PrefabValue,
+        Prefab, PrefabComponent, PrefabError, PrefabManager, PrefabScene, PrefabSceneEntity,
+        PrefabSceneEntityData, PrefabValue,
     },
-    state::{State, StateChange},
+    state::{State, StateChange, StateToken},
 };
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Default)]
@@ -70,6 +72,7 @@ struct Child {
 fn test_prefabs() {
     let prefab = PrefabScene {
         template_name: None,
+        extends: None,
         dependencies: vec![],
         entities: vec![
             PrefabSceneEntity::Data(PrefabSceneEntityData {
@@ -171,6 +174,226 @@ fn test_hierarchy_find() {
     assert_eq!(hierarchy.find(None, "a/b/../../c"), Some(child_c));
 }
 
+#[test]
+fn test_prefab_snapshot() {
+    let mut app = App::build::<LinearPipelineBuilder>()
+        .with_bundle(crate::prefab::bundle_installer, |_| {})
+        .unwrap()
+        .build_empty::<SequencePipelineEngine, _>(StandardAppTimer::default());
+    let universe = app.multiverse.default_universe_mut().unwrap();
+    let entities = {
+        let mut world = universe.world_mut();
+        let root = world.spawn((Name("root".into()), Tag("tag".into())));
+        let child_a = world.spawn(Child {
+            name: Name("child".into()),
+            parent: Parent(root),
+        });
+        let child_b = world.spawn(Child {
+            name: Name("child".into()),
+            parent: Parent(root),
+        });
+        vec![child_a, root, child_b]
+    };
+    let prefab = universe
+        .expect_resource::<PrefabManager>()
+        .snapshot_scene(&entities, universe)
+        .unwrap();
+    let uids = prefab
+        .entities
+        .iter()
+        .map(|entity| match entity {
+            PrefabSceneEntity::Data(data) => data.uid.clone().unwrap(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(uids, vec!["root", "#0", "#2"]);
+    if let PrefabSceneEntity::Data(data) = &prefab.entities[1] {
+        assert_eq!(
+            data.components.get("Parent"),
+            Some(&PrefabValue::String("root".to_owned()))
+        );
+        assert_eq!(
+            data.components.get("Name"),
+            Some(&PrefabValue::String("child".to_owned()))
+        );
+    }
+
+    let loaded = universe
+        .expect_resource_mut::<PrefabManager>()
+        .load_scene_from_prefab(&prefab, universe)
+        .unwrap();
+    assert_eq!(loaded.len(), 3);
+    {
+        let world = universe.world();
+        assert_eq!(world.get::<&Tag>(loaded[0]).unwrap().0, "tag");
+        assert_eq!(world.get::<&Parent>(loaded[1]).unwrap().0, loaded[0]);
+        assert_eq!(world.get::<&Parent>(loaded[2]).unwrap().0, loaded[0]);
+    }
+    let snapshot = universe
+        .expect_resource::<PrefabManager>()
+        .snapshot_scene(&loaded, universe)
+        .unwrap();
+    let reloaded = universe
+        .expect_resource_mut::<PrefabManager>()
+        .load_scene_from_prefab(&snapshot, universe)
+        .unwrap();
+    let resnapshot = universe
+        .expect_resource::<PrefabManager>()
+        .snapshot_scene(&reloaded, universe)
+        .unwrap();
+    assert_eq!(
+        snapshot.to_prefab().unwrap(),
+        resnapshot.to_prefab().unwrap()
+    );
+
+    let reserved = universe.world_mut().spawn((Name("#0".into()),));
+    let prefab = universe
+        .expect_resource::<PrefabManager>()
+        .snapshot_scene(&[loaded[1], loaded[2], reserved], universe)
+        .unwrap();
+    let uids = prefab
+        .entities
+        .iter()
+        .map(|entity| match entity {
+            PrefabSceneEntity::Data(data) => {
+                assert!(!data.components.contains_key("Parent"));
+                data.uid.clone().unwrap()
+            }
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(uids, vec!["#2", "#0", "#1"]);
+    let loaded = universe
+        .expect_resource_mut::<PrefabManager>()
+        .load_scene_from_prefab(&prefab, universe)
+        .unwrap();
+    assert_eq!(loaded.len(), 3);
+    assert!(universe.world().get::<&Parent>(loaded[1]).is_err());
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stats {
+    #[serde(default)]
+    health: usize,
+    #[serde(default)]
+    speed: usize,
+    #[serde(default)]
+    loot: Vec<String>,
+}
+
+impl Prefab for Stats {}
+impl PrefabComponent for Stats {}
+
+#[test]
+fn test_prefab_inheritance() {
+    let mut prefabs = PrefabManager::default();
+    prefabs.register_component_factory::<Name>("Name");
+    prefabs.register_component_factory::<Tag>("Tag");
+    prefabs.register_component_factory::<Stats>("Stats");
+    prefabs
+        .register_scene_template(
+            PrefabScene::from_prefab_str(
+                r#"{
+                    "template_name": "enemy",
+                    "entities": [
+                        { "Data": { "uid": "body", "components": {
+                            "Name": "enemy",
+                            "Stats": { "health": 10, "speed": 1, "loot": ["coin"] }
+                        } } }
+                    ]
+                }"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    prefabs
+        .register_scene_template(
+            PrefabScene::from_prefab_str(
+                r#"{
+                    "template_name": "boss",
+                    "extends": "enemy",
+                    "entities": [
+                        { "Data": { "uid": "body", "components": {
+                            "Tag": "boss",
+                            "Stats": { "health": 100, "speed": null, "loot": ["crown"] }
+                        } } },
+                        { "Data": { "uid": "weapon", "components": { "Name": "sword" } } }
+                    ]
+                }"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    let boss = prefabs.resolve_template("boss").unwrap();
+    assert_eq!(boss.entities.len(), 2);
+    if let PrefabSceneEntity::Data(data) = &boss.entities[0] {
+        assert_eq!(
+            data.components.get("Name"),
+            Some(&PrefabValue::from("enemy"))
+        );
+        assert_eq!(data.components.get("Tag"), Some(&PrefabValue::from("boss")));
+        assert_eq!(
+            data.components.get("Stats"),
+            Some(&serde_json::json!({ "health": 100, "loot": ["crown"] }))
+        );
+    } else {
+        unreachable!();
+    }
+
+    let mut world = hecs::World::new();
+    let mut changes = EntityChanges::default();
+    let scene = PrefabScene::from_prefab_str(
+        r#"{
+            "entities": [
+                { "Instance": { "template": "boss", "overrides": {
+                    "body": { "Tag": null },
+                    "weapon": { "Name": "axe", "Stats": { "health": 5, "speed": null } }
+                } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let entities = prefabs
+        .load_scene_from_prefab_direct(&scene, &mut world, &mut changes, StateToken::new())
+        .unwrap();
+    assert_eq!(entities.len(), 2);
+    assert_eq!(world.get::<&Name>(entities[0]).unwrap().0, "enemy");
+    assert!(world.get::<&Tag>(entities[0]).is_err());
+    assert_eq!(world.get::<&Stats>(entities[0]).unwrap().health, 100);
+    assert_eq!(world.get::<&Name>(entities[1]).unwrap().0, "axe");
+    assert_eq!(world.get::<&Stats>(entities[1]).unwrap().health, 5);
+
+    let scene = PrefabScene::from_prefab_str(
+        r#"{
+            "entities": [
+                { "Instance": { "template": "boss", "overrides": {
+                    "weapon": { "Damage": 42 }
+                } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        prefabs.load_scene_from_prefab_direct(&scene, &mut world, &mut changes, StateToken::new()),
+        Err(PrefabError::UnknownComponent(name)) if name == "Damage"
+    ));
+    let scene = PrefabScene::from_prefab_str(
+        r#"{
+            "entities": [
+                { "Instance": { "template": "boss", "overrides": {
+                    "shield": { "Name": "shield" }
+                } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        prefabs.load_scene_from_prefab_direct(&scene, &mut world, &mut changes, StateToken::new()),
+        Err(PrefabError::UnknownEntity(template, uid)) if template == "boss" && uid == "shield"
+    ));
+}
+
 #[test]
 fn test_entity_life_cycle() {
     fn set(container: &[Entity]) -> HashSet<Entity> {
@@ -304,3 +527,52 @@ fn test_localization() {
     let text = localization_format_text!(loc, "hello", name => "Person", score => 42).unwrap();
     assert_eq!(text, "Hello Person, you've got 42 points! | {@bye}");
 }
+
+#[test]
+fn test_localization_formatting() {
+    let mut loc = Localization::default();
+    loc.add_text(
+        "items",
+        "en",
+        "|@name:select female{She} male{He} other{They}| got |@count:plural =0{no items} one{# item} other{# items}|.",
+    );
+    loc.add_text(
+        "items",
+        "pl",
+        "|@name|, masz |@count:plural one{# przedmiot} few{# przedmioty} many{# przedmiotów} other{# przedmiotu}|.",
+    );
+    loc.add_text(
+        "price",
+        "en",
+        "Price: |@value:number(2)|, date: |@date:date|",
+    );
+    loc.add_text("only-default", "en", "default");
+    loc.add_text("only-fallback", "de", "fallback");
+    loc.set_current_language(Some("en".to_owned()));
+
+    let text = localization_format_text!(loc, "items", name => "female", count => 0).unwrap();
+    assert_eq!(text, "She got no items.");
+    let text = localization_format_text!(loc, "items", name => "cat", count => 1).unwrap();
+    assert_eq!(text, "They got 1 item.");
+    let text = localization_format_text!(loc, "items", name => "male", count => 1234).unwrap();
+    assert_eq!(text, "He got 1,234 items.");
+    let text =
+        localization_format_text!(loc, "price", value => 1234.5, date => "2021-03-04").unwrap();
+    assert_eq!(text, "Price: 1,234.50, date: 03/04/2021");
+
+    loc.set_current_language(Some("pl-PL".to_owned()));
+    loc.set_fallback_languages("pl", vec!["de".to_owned()]);
+    assert_eq!(loc.languages_chain(), vec!["pl-PL", "pl", "de", "en"]);
+    let text = localization_format_text!(loc, "items", name => "Ala", count => 3).unwrap();
+    assert_eq!(text, "Ala, masz 3 przedmioty.");
+    let text = localization_format_text!(loc, "items", name => "Ala", count => 12).unwrap();
+    assert_eq!(text, "Ala, masz 12 przedmiotów.");
+    let text = localization_format_text!(loc, "items", name => "Ala", count => 22).unwrap();
+    assert_eq!(text, "Ala, masz 22 przedmioty.");
+    let text = localization_format_text!(loc, "items", name => "Ala", count => 1.5).unwrap();
+    assert_eq!(text, "Ala, masz 1,5 przedmiotu.");
+    let text = localization_format_text!(loc, "price", value => 1234.5, date => 86400).unwrap();
+    assert_eq!(text, "Price: 1\u{a0}234,50, date: 02.01.1970");
+    assert_eq!(loc.find_text_format("only-fallback"), Some("fallback"));
+    assert_eq!(loc.find_text_format("only-default"), Some("default"));
+}
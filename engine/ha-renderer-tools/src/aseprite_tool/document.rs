@@ -0,0 +1,362 @@
+use image::RgbaImage;
+use oxygengine_core::Scalar;
+use oxygengine_ha_renderer::math::*;
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use std::{
+    fmt,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub image: RgbaImage,
+    /// Duration in milliseconds.
+    pub duration: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub name: String,
+    /// Inclusive range of frame indices.
+    pub from: usize,
+    pub to: usize,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone)]
+pub struct SliceKey {
+    /// First frame this key is active from, until next key.
+    pub frame: usize,
+    pub rect: Rect,
+    pub pivot: Option<Vec2>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Slice {
+    pub name: String,
+    pub keys: Vec<SliceKey>,
+}
+
+impl Slice {
+    pub fn key_at(&self, frame: usize) -> Option<(usize, &SliceKey)> {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| key.frame <= frame)
+            .last()
+    }
+}
+
+/// Format-agnostic sprite document read either from Aseprite binary file or its JSON export.
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub name: String,
+    pub frames: Vec<Frame>,
+    pub tags: Vec<Tag>,
+    pub slices: Vec<Slice>,
+}
+
+impl Document {
+    /// JSON files are supported only when they are Aseprite sprite sheet exports.
+    pub fn is_supported(path: &Path) -> bool {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("aseprite") | Some("ase") => true,
+            Some("json") => Self::is_json_export(path),
+            _ => false,
+        }
+    }
+
+    fn is_json_export(path: &Path) -> bool {
+        read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|value| {
+                value
+                    .pointer("/meta/app")
+                    .and_then(|app| app.as_str())
+                    .map(|app| app.contains("aseprite"))
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn load(path: &Path) -> Self {
+        let name = path
+            .file_stem()
+            .unwrap_or_else(|| panic!("Could not get path file name: {:?}", path))
+            .to_string_lossy()
+            .to_string();
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Self::load_json_export(name, path),
+            _ => Self::load_aseprite(name, path),
+        }
+    }
+
+    fn load_aseprite(name: String, path: &Path) -> Self {
+        let file = asefile::AsepriteFile::read_file(path)
+            .unwrap_or_else(|error| panic!("Could not load Aseprite file: {:?}. {}", path, error));
+        let frames = (0..file.num_frames())
+            .map(|index| {
+                let frame = file.frame(index);
+                Frame {
+                    image: frame.image(),
+                    duration: frame.duration(),
+                }
+            })
+            .collect();
+        let tags = (0..file.num_tags())
+            .map(|index| {
+                let tag = file.tag(index);
+                Tag {
+                    name: tag.name().to_owned(),
+                    from: tag.from_frame() as _,
+                    to: tag.to_frame() as _,
+                    direction: match tag.animation_direction() {
+                        asefile::AnimationDirection::Forward => Direction::Forward,
+                        asefile::AnimationDirection::Reverse => Direction::Reverse,
+                        _ => Direction::PingPong,
+                    },
+                }
+            })
+            .collect();
+        let slices = file
+            .slices()
+            .iter()
+            .map(|slice| Slice {
+                name: slice.name.to_owned(),
+                keys: slice
+                    .keys
+                    .iter()
+                    .map(|key| SliceKey {
+                        frame: key.from_frame as _,
+                        rect: Rect {
+                            x: key.origin.0 as _,
+                            y: key.origin.1 as _,
+                            w: key.size.0 as _,
+                            h: key.size.1 as _,
+                        },
+                        pivot: key.pivot.map(|(x, y)| vec2(x as _, y as _)),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            name,
+            frames,
+            tags,
+            slices,
+        }
+    }
+
+    fn load_json_export(name: String, path: &Path) -> Self {
+        let content = read_to_string(path)
+            .unwrap_or_else(|_| panic!("Could not read Aseprite JSON export: {:?}", path));
+        let sheet = serde_json::from_str::<SheetExport>(&content).unwrap_or_else(|error| {
+            panic!(
+                "Could not parse Aseprite JSON export: {:?}. {}",
+                path, error
+            )
+        });
+        let image_path = path
+            .parent()
+            .map(|parent| parent.join(&sheet.meta.image))
+            .unwrap_or_else(|| PathBuf::from(&sheet.meta.image));
+        let image = image::open(&image_path)
+            .unwrap_or_else(|_| panic!("Could not load sprite sheet image: {:?}", image_path))
+            .to_rgba8();
+        let frames = sheet
+            .frames
+            .0
+            .iter()
+            .map(|frame| {
+                let SheetRect { x, y, w, h } = frame.frame;
+                let (w, h) = if frame.rotated { (h, w) } else { (w, h) };
+                let mut region = image::imageops::crop_imm(&image, x, y, w, h).to_image();
+                if frame.rotated {
+                    region = image::imageops::rotate270(&region);
+                }
+                let mut result = RgbaImage::new(frame.source_size.w, frame.source_size.h);
+                image::imageops::replace(
+                    &mut result,
+                    &region,
+                    frame.sprite_source_size.x as _,
+                    frame.sprite_source_size.y as _,
+                );
+                Frame {
+                    image: result,
+                    duration: frame.duration,
+                }
+            })
+            .collect();
+        let tags = sheet
+            .meta
+            .frame_tags
+            .into_iter()
+            .map(|tag| Tag {
+                name: tag.name,
+                from: tag.from,
+                to: tag.to,
+                direction: match tag.direction.as_str() {
+                    "reverse" => Direction::Reverse,
+                    "pingpong" | "pingpong_reverse" => Direction::PingPong,
+                    _ => Direction::Forward,
+                },
+            })
+            .collect();
+        let slices = sheet
+            .meta
+            .slices
+            .into_iter()
+            .map(|slice| Slice {
+                name: slice.name,
+                keys: slice
+                    .keys
+                    .into_iter()
+                    .map(|key| SliceKey {
+                        frame: key.frame,
+                        rect: Rect {
+                            x: key.bounds.x as _,
+                            y: key.bounds.y as _,
+                            w: key.bounds.w as _,
+                            h: key.bounds.h as _,
+                        },
+                        pivot: key.pivot.map(|pivot| vec2(pivot.x, pivot.y)),
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            name,
+            frames,
+            tags,
+            slices,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SheetExport {
+    frames: SheetFrames,
+    meta: SheetMeta,
+}
+
+/// Aseprite exports frames either as array or as map keyed by frame file name - in both cases
+/// order of frames matters, so map entries are collected in the order they appear in.
+#[derive(Debug)]
+struct SheetFrames(Vec<SheetFrame>);
+
+impl<'de> Deserialize<'de> for SheetFrames {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SheetFramesVisitor;
+
+        impl<'de> Visitor<'de> for SheetFramesVisitor {
+            type Value = SheetFrames;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("array or map of sprite sheet frames")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut result = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                while let Some(frame) = seq.next_element()? {
+                    result.push(frame);
+                }
+                Ok(SheetFrames(result))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut result = Vec::with_capacity(map.size_hint().unwrap_or_default());
+                while let Some((_, frame)) = map.next_entry::<String, _>()? {
+                    result.push(frame);
+                }
+                Ok(SheetFrames(result))
+            }
+        }
+
+        deserializer.deserialize_any(SheetFramesVisitor)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SheetFrame {
+    frame: SheetRect,
+    #[serde(default)]
+    rotated: bool,
+    sprite_source_size: SheetRect,
+    source_size: SheetSize,
+    duration: u32,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+struct SheetRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+struct SheetSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+struct SheetPoint {
+    x: Scalar,
+    y: Scalar,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SheetMeta {
+    image: String,
+    #[serde(default)]
+    frame_tags: Vec<SheetTag>,
+    #[serde(default)]
+    slices: Vec<SheetSlice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SheetTag {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default)]
+    direction: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SheetSlice {
+    name: String,
+    #[serde(default)]
+    keys: Vec<SheetSliceKey>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SheetSliceKey {
+    frame: usize,
+    bounds: SheetRect,
+    #[serde(default)]
+    pivot: Option<SheetPoint>,
+}
@@ -0,0 +1,238 @@
+#[path = "../atlas_pages.rs"]
+mod atlas_pages;
+mod document;
+
+use crate::{atlas_pages::*, document::*};
+use image::DynamicImage;
+use oxygengine_build_tools::*;
+use oxygengine_core::Scalar;
+use oxygengine_ha_renderer::asset_protocols::sprite_animation::*;
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{create_dir_all, write},
+    io::Error,
+    iter::repeat,
+};
+use texture_packer::MultiTexturePacker;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Params {
+    #[serde(flatten)]
+    pub page: PageParams,
+    /// Identical frames are packed once and shared between animations.
+    #[serde(default = "Params::default_deduplicate")]
+    pub deduplicate: bool,
+}
+
+impl Params {
+    fn default_deduplicate() -> bool {
+        true
+    }
+}
+
+impl ParamsFromArgs for Params {}
+
+fn main() -> Result<(), Error> {
+    AssetPipelinePlugin::run::<Params, _>(|input| {
+        let AssetPipelineInput {
+            source,
+            target,
+            assets,
+            params,
+        } = input;
+        create_dir_all(&target)?;
+
+        let mut paths = vec![];
+        for path in &source {
+            if path.is_dir() {
+                get_files_recursively(path, &mut paths);
+            } else {
+                paths.push(path.to_owned());
+            }
+        }
+        paths.retain(|path| Document::is_supported(path));
+        // Aseprite files take precedence over their own JSON exports lying next to them.
+        let sources = paths
+            .iter()
+            .filter(|path| {
+                path.extension().and_then(|extension| extension.to_str()) != Some("json")
+            })
+            .map(|path| path.with_extension(""))
+            .collect::<HashSet<_>>();
+        paths.retain(|path| {
+            path.extension().and_then(|extension| extension.to_str()) != Some("json")
+                || !sources.contains(&path.with_extension(""))
+        });
+        let mut documents = paths
+            .into_iter()
+            .map(|path| Document::load(&path))
+            .filter(|document| !document.frames.is_empty())
+            .collect::<Vec<_>>();
+        documents.sort_by(|a, b| a.name.cmp(&b.name));
+        for pair in documents.windows(2) {
+            if pair[0].name == pair[1].name {
+                panic!("Found multiple Aseprite documents named: {}", pair[0].name);
+            }
+        }
+
+        let mut packer = MultiTexturePacker::new_skyline(params.page.packer_config());
+        let mut packed = HashMap::<String, PackedImage>::default();
+        // { (width, height, pixels): region id }
+        let mut unique = HashMap::<(u32, u32, Vec<u8>), String>::default();
+        // [document index][frame index] => region id
+        let mut frame_ids = Vec::with_capacity(documents.len());
+        for document in &documents {
+            let mut ids = Vec::with_capacity(document.frames.len());
+            for (index, frame) in document.frames.iter().enumerate() {
+                let key = (
+                    frame.image.width(),
+                    frame.image.height(),
+                    frame.image.as_raw().to_owned(),
+                );
+                if params.deduplicate {
+                    if let Some(id) = unique.get(&key) {
+                        ids.push(id.to_owned());
+                        continue;
+                    }
+                }
+                let id = format!("{}.{}", document.name, index);
+                let (image, info) = prepare_image(&frame.image, &params.page);
+                packer
+                    .pack_own(id.to_owned(), DynamicImage::ImageRgba8(image))
+                    .unwrap_or_else(|_| panic!("Could not pack frame: {}", id));
+                packed.insert(id.to_owned(), info);
+                if params.deduplicate {
+                    unique.insert(key, id.to_owned());
+                }
+                ids.push(id);
+            }
+            frame_ids.push(ids);
+        }
+
+        let mut pages = HashMap::default();
+        for (i, page) in packer.get_pages().iter().enumerate() {
+            let name = format!("page.{}", i);
+            let (path, regions) = write_page(page, &packed, &name, &target, &assets, &params.page);
+            pages.insert(path, regions);
+        }
+        let atlas = write_atlas(pages, &target, &assets);
+
+        let mut assets_used = vec![format!("atlas://{}", atlas)];
+        for (document, ids) in documents.iter().zip(frame_ids.iter()) {
+            let asset = build_animation(document, ids, &atlas);
+            let path = target.join(format!("{}.json", document.name));
+            write(
+                &path,
+                serde_json::to_string_pretty(&asset).unwrap_or_else(|_| {
+                    panic!("Could not serialize sprite animation: {}", document.name)
+                }),
+            )
+            .unwrap_or_else(|_| {
+                panic!("Could not write sprite animation asset to file: {:?}", path)
+            });
+            assets_used.push(format!("spriteanim://{}/{}.json", assets, document.name));
+        }
+        Ok(assets_used)
+    })
+}
+
+/// Document without tags gets single `default` state playing all frames.
+fn build_animation(document: &Document, frame_ids: &[String], atlas: &str) -> SpriteAnimationAsset {
+    let default_tag = Tag {
+        name: "default".to_owned(),
+        from: 0,
+        to: document.frames.len() - 1,
+        direction: Direction::Forward,
+    };
+    let tags = if document.tags.is_empty() {
+        std::slice::from_ref(&default_tag)
+    } else {
+        &document.tags
+    };
+    SpriteAnimationAsset {
+        default_state: tags.first().map(|tag| tag.name.to_owned()),
+        speed: 1.0,
+        states: tags
+            .iter()
+            .map(|tag| {
+                (
+                    tag.name.to_owned(),
+                    build_state(document, tag, frame_ids, atlas),
+                )
+            })
+            .collect(),
+        rules: vec![],
+    }
+}
+
+/// Sprite animation frames have constant duration so Aseprite frames are repeated by multiple of
+/// greatest common divisor of tag frame durations, which becomes state time step.
+fn build_state(
+    document: &Document,
+    tag: &Tag,
+    frame_ids: &[String],
+    atlas: &str,
+) -> SpriteAnimationState {
+    let last = document.frames.len() - 1;
+    let mut indices = (tag.from.min(last)..=tag.to.min(last)).collect::<Vec<_>>();
+    if tag.direction == Direction::Reverse {
+        indices.reverse();
+    }
+    let step = indices
+        .iter()
+        .map(|index| document.frames[*index].duration.max(1))
+        .fold(0, gcd)
+        .max(1);
+    let mut frames = vec![];
+    let mut signals = vec![];
+    let mut active_keys = vec![None; document.slices.len()];
+    for index in indices {
+        for (slice, active) in document.slices.iter().zip(active_keys.iter_mut()) {
+            let key = slice.key_at(index);
+            let current = key.map(|(key_index, _)| key_index);
+            if current != *active {
+                *active = current;
+                if let Some((_, key)) = key {
+                    signals.push(slice_signal(slice, key, frames.len() as _));
+                }
+            }
+        }
+        let count = (document.frames[index].duration.max(1) / step).max(1) as usize;
+        frames.extend(repeat(format!("{}@{}", atlas, frame_ids[index])).take(count));
+    }
+    SpriteAnimationState {
+        frames,
+        signals,
+        speed: 1000.0 / step as Scalar,
+        looping: true,
+        bounce: tag.direction == Direction::PingPong,
+        rules: vec![],
+    }
+}
+
+/// Slice keys are emitted as signals named after slice, fired when key becomes active.
+fn slice_signal(slice: &Slice, key: &SliceKey, time: Scalar) -> SpriteAnimationSignal {
+    let mut params = HashMap::with_capacity(6);
+    params.insert("x".to_owned(), key.rect.x.into());
+    params.insert("y".to_owned(), key.rect.y.into());
+    params.insert("width".to_owned(), key.rect.w.into());
+    params.insert("height".to_owned(), key.rect.h.into());
+    if let Some(pivot) = key.pivot {
+        params.insert("pivot_x".to_owned(), pivot.x.into());
+        params.insert("pivot_y".to_owned(), pivot.y.into());
+    }
+    SpriteAnimationSignal {
+        time,
+        id: slice.name.to_owned(),
+        params,
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
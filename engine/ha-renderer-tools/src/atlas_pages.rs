@@ -0,0 +1,303 @@
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use oxygengine_core::Scalar;
+use oxygengine_ha_renderer::{
+    asset_protocols::{atlas::*, image::*},
+    math::*,
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs::{read_dir, write},
+    path::{Path, PathBuf},
+};
+use texture_packer::{exporter::ImageExporter, TexturePacker, TexturePackerConfig};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub enum PageFormat {
+    #[default]
+    Png,
+    Ktx2,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PageParams {
+    #[serde(default = "PageParams::default_max_width")]
+    pub max_width: u32,
+    #[serde(default = "PageParams::default_max_height")]
+    pub max_height: u32,
+    #[serde(default = "PageParams::default_padding")]
+    pub padding: u32,
+    /// Removes transparent borders of images, original size and offset are stored in regions.
+    #[serde(default)]
+    pub trim: bool,
+    #[serde(default)]
+    pub allow_rotation: bool,
+    /// Number of pixels image edges are repeated around regions to prevent filtering bleed.
+    #[serde(default)]
+    pub extrude: u32,
+    #[serde(default)]
+    pub power_of_two: bool,
+    #[serde(default)]
+    pub square: bool,
+    #[serde(default)]
+    pub page_format: PageFormat,
+}
+
+impl PageParams {
+    fn default_max_width() -> u32 {
+        1024
+    }
+
+    fn default_max_height() -> u32 {
+        1024
+    }
+
+    fn default_padding() -> u32 {
+        2
+    }
+
+    pub fn packer_config(&self) -> TexturePackerConfig {
+        TexturePackerConfig {
+            max_width: self.max_width,
+            max_height: self.max_height,
+            allow_rotation: self.allow_rotation,
+            border_padding: 0,
+            texture_padding: self.padding,
+            texture_extrusion: 0,
+            trim: false,
+            texture_outlines: false,
+        }
+    }
+}
+
+pub fn get_files_recursively(directory: impl AsRef<Path>, result: &mut Vec<PathBuf>) {
+    if let Ok(entries) = read_dir(directory) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() {
+                result.push(path);
+            } else if path.is_dir() {
+                get_files_recursively(path, result);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PackedImage {
+    offset: Vec2,
+    source_size: Option<Vec2>,
+}
+
+/// Trims and extrudes image according to params, before it gets packed.
+pub fn prepare_image(image: &RgbaImage, params: &PageParams) -> (RgbaImage, PackedImage) {
+    let (image, info) = if params.trim {
+        let (x, y, w, h) = trim_bounds(image);
+        let info = PackedImage {
+            offset: vec2(x as _, y as _),
+            source_size: Some(vec2(image.width() as _, image.height() as _)),
+        };
+        (image.view(x, y, w, h).to_image(), info)
+    } else {
+        (image.to_owned(), PackedImage::default())
+    };
+    (extrude(&image, params.extrude), info)
+}
+
+/// Returns (x, y, width, height) of non-transparent pixels area, at least single pixel.
+fn trim_bounds(image: &RgbaImage) -> (u32, u32, u32, u32) {
+    let mut min = (u32::MAX, u32::MAX);
+    let mut max = (0, 0);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        if pixel[3] > 0 {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+    }
+    if min.0 > max.0 || min.1 > max.1 {
+        return (0, 0, 1.min(image.width()), 1.min(image.height()));
+    }
+    (min.0, min.1, max.0 - min.0 + 1, max.1 - min.1 + 1)
+}
+
+/// Surrounds image with copies of its edge pixels.
+fn extrude(image: &RgbaImage, size: u32) -> RgbaImage {
+    if size == 0 || image.width() == 0 || image.height() == 0 {
+        return image.to_owned();
+    }
+    let width = image.width() + size * 2;
+    let height = image.height() + size * 2;
+    RgbaImage::from_fn(width, height, |x, y| {
+        let x = x.saturating_sub(size).min(image.width() - 1);
+        let y = y.saturating_sub(size).min(image.height() - 1);
+        *image.get_pixel(x, y)
+    })
+}
+
+/// Saves packed page image with its image asset and returns (image asset path, page regions).
+pub fn write_page(
+    page: &TexturePacker<DynamicImage, String>,
+    packed: &HashMap<String, PackedImage>,
+    name: &str,
+    target: &Path,
+    assets: &str,
+    params: &PageParams,
+) -> (String, HashMap<String, AtlasRegion>) {
+    let image = ImageExporter::export(page)
+        .unwrap_or_else(|_| panic!("Could not export atlas page: {}", name))
+        .to_rgba8();
+    let image = fit_page_size(image, params.power_of_two, params.square);
+    let asset = match params.page_format {
+        PageFormat::Png => {
+            let path = target.join(format!("{}.png", name));
+            image
+                .save_with_format(&path, image::ImageFormat::Png)
+                .unwrap_or_else(|_| {
+                    panic!("Could not save atlas page: {} to file: {:?}", name, path)
+                });
+            ImageAssetSource::Png {
+                bytes_paths: vec![format!("{}/{}.png", assets, name)],
+                descriptor: Default::default(),
+            }
+        }
+        PageFormat::Ktx2 => {
+            let path = target.join(format!("{}.ktx2", name));
+            write(&path, encode_ktx2(&image)).unwrap_or_else(|_| {
+                panic!("Could not save atlas page: {} to file: {:?}", name, path)
+            });
+            ImageAssetSource::Ktx2 {
+                bytes_path: format!("{}/{}.ktx2", assets, name),
+                descriptor: Default::default(),
+            }
+        }
+    };
+    let path = target.join(format!("{}.json", name));
+    write(
+        &path,
+        serde_json::to_string_pretty(&asset)
+            .unwrap_or_else(|_| panic!("Could not serialize atlas page: {} image asset", name)),
+    )
+    .unwrap_or_else(|_| {
+        panic!(
+            "Could not write atlas page: {} image asset to file: {:?}",
+            name, path
+        )
+    });
+    let extrude = params.extrude as Scalar;
+    let regions = page
+        .get_frames()
+        .iter()
+        .map(|(id, frame)| {
+            let info = packed.get(id).copied().unwrap_or_default();
+            (
+                id.to_owned(),
+                AtlasRegion {
+                    rect: Rect {
+                        x: frame.frame.x as Scalar + extrude,
+                        y: frame.frame.y as Scalar + extrude,
+                        w: frame.frame.w as Scalar - extrude * 2.0,
+                        h: frame.frame.h as Scalar - extrude * 2.0,
+                    },
+                    layer: 0,
+                    rotated: frame.rotated,
+                    offset: info.offset,
+                    source_size: info.source_size,
+                },
+            )
+        })
+        .collect();
+    (format!("{}/{}.json", assets, name), regions)
+}
+
+/// Writes atlas asset of given pages and returns its asset path.
+pub fn write_atlas(
+    pages: HashMap<String, HashMap<String, AtlasRegion>>,
+    target: &Path,
+    assets: &str,
+) -> String {
+    let asset = AtlasAssetSource::Raw(pages);
+    let path = target.join("atlas.json");
+    write(
+        &path,
+        serde_json::to_string_pretty(&asset)
+            .unwrap_or_else(|_| panic!("Could not serialize atlas asset")),
+    )
+    .unwrap_or_else(|_| panic!("Could not write atlas asset to file: {:?}", path));
+    format!("{}/atlas.json", assets)
+}
+
+fn fit_page_size(image: RgbaImage, power_of_two: bool, square: bool) -> RgbaImage {
+    let (mut width, mut height) = image.dimensions();
+    if square {
+        width = width.max(height);
+        height = width;
+    }
+    if power_of_two {
+        width = width.next_power_of_two();
+        height = height.next_power_of_two();
+    }
+    if (width, height) == image.dimensions() {
+        return image;
+    }
+    let mut result = RgbaImage::new(width, height);
+    image::imageops::replace(&mut result, &image, 0, 0);
+    result
+}
+
+/// Encodes uncompressed single level `R8G8B8A8_UNORM` KTX2 texture.
+fn encode_ktx2(image: &RgbaImage) -> Vec<u8> {
+    const IDENTIFIER: [u8; 12] = [
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ];
+    const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+    const HEADER_SIZE: u32 = 80;
+    const LEVEL_INDEX_SIZE: u32 = 24;
+    const DFD_SIZE: u32 = 4 + 24 + 16 * 4;
+
+    let data = image.as_raw();
+    let dfd_offset = HEADER_SIZE + LEVEL_INDEX_SIZE;
+    let level_offset = dfd_offset + DFD_SIZE;
+    let mut result = Vec::with_capacity(level_offset as usize + data.len());
+    result.extend(IDENTIFIER);
+    for value in [
+        VK_FORMAT_R8G8B8A8_UNORM,
+        1,
+        image.width(),
+        image.height(),
+        0,
+        0,
+        1,
+        1,
+        0,
+    ] {
+        result.extend(value.to_le_bytes());
+    }
+    // DFD and KVD offsets and sizes.
+    for value in [dfd_offset, DFD_SIZE, 0, 0] {
+        result.extend(value.to_le_bytes());
+    }
+    // SGD offset and size.
+    result.extend(0u64.to_le_bytes());
+    result.extend(0u64.to_le_bytes());
+    // Level index.
+    result.extend((level_offset as u64).to_le_bytes());
+    result.extend((data.len() as u64).to_le_bytes());
+    result.extend((data.len() as u64).to_le_bytes());
+    // Basic data format descriptor: RGBSDA color model, BT709 primaries, linear transfer.
+    result.extend(DFD_SIZE.to_le_bytes());
+    result.extend(0u32.to_le_bytes());
+    result.extend((2u32 | ((DFD_SIZE - 4) << 16)).to_le_bytes());
+    result.extend([1, 1, 1, 0]);
+    result.extend([0, 0, 0, 0]);
+    result.extend([4, 0, 0, 0, 0, 0, 0, 0]);
+    for (index, channel) in [0u8, 1, 2, 15].into_iter().enumerate() {
+        result.extend(((index * 8) as u16).to_le_bytes());
+        result.push(7);
+        result.push(channel);
+        result.extend([0, 0, 0, 0]);
+        result.extend(0u32.to_le_bytes());
+        result.extend(255u32.to_le_bytes());
+    }
+    result.extend(data);
+    result
+}
@@ -21,6 +21,8 @@ pub struct Font {
     pub common: Common,
     pub pages: Pages,
     pub chars: Chars,
+    #[serde(default)]
+    pub kernings: Option<Kernings>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +74,23 @@ pub struct CharAttributes {
     pub page: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kernings {
+    #[serde(rename = "$value", default)]
+    kernings: Vec<Kerning>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kerning(pub KerningAttributes);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KerningAttributes {
+    pub first: usize,
+    pub second: usize,
+    pub amount: isize,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct Params {
     #[serde(default)]
@@ -86,6 +105,8 @@ struct Params {
     pub padding: u32,
     #[serde(default)]
     pub force_line_height: Option<usize>,
+    #[serde(default)]
+    pub fallback_character: Option<char>,
 }
 
 impl Params {
@@ -110,12 +131,12 @@ fn main() -> Result<(), Error> {
         } = input;
         create_dir_all(&target)?;
 
-        let source = match source.first() {
-            Some(source) => source,
-            None => return Ok(vec![]),
-        };
+        // first source is the main font, next ones are fallbacks for its missing characters.
+        if source.is_empty() {
+            return Ok(vec![]);
+        }
         Ok(vec![generate_sdf_font(
-            source,
+            &source,
             &target,
             &assets,
             params.image_filtering,
@@ -124,13 +145,19 @@ fn main() -> Result<(), Error> {
             params.max_height,
             params.padding,
             params.force_line_height,
+            params.fallback_character,
         )?])
     })
 }
 
+fn character_from_id(id: usize) -> char {
+    std::char::from_u32(id as _)
+        .unwrap_or_else(|| panic!("Could not convert character id: {} to character", id))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn generate_sdf_font(
-    source: &Path,
+    sources: &[PathBuf],
     target: &Path,
     assets: &str,
     image_filtering: ImageFiltering,
@@ -139,32 +166,94 @@ fn generate_sdf_font(
     max_height: u32,
     padding: u32,
     force_line_height: Option<usize>,
+    fallback_character: Option<char>,
 ) -> Result<String, Error> {
-    let dirname = source.parent().map(|p| p.to_path_buf()).unwrap_or_default();
-    let font = serde_xml_rs::from_str::<Font>(&read_to_string(source)?)
-        .unwrap_or_else(|_| panic!("Could not load bmfont XML descriptor: {:?}", source));
-    let glyphs = font
-        .pages
-        .pages
+    let fonts = sources
         .iter()
-        .flat_map(|page| {
+        .map(|source| {
+            let font = serde_xml_rs::from_str::<Font>(&read_to_string(source)?)
+                .unwrap_or_else(|_| panic!("Could not load bmfont XML descriptor: {:?}", source));
+            Ok((source.as_path(), font))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let (source, font) = &fonts[0];
+    // { character id: font index }
+    let mut owners = HashMap::<usize, usize>::default();
+    for (index, (_, font)) in fonts.iter().enumerate() {
+        for c in &font.chars.chars {
+            owners.entry(c.0.id).or_insert(index);
+        }
+    }
+    // (scale, baseline shift) of fallback fonts glyphs to match main font metrics.
+    let transforms = fonts
+        .iter()
+        .map(|(_, other)| {
+            let scale = font.common.0.line_height as f32 / other.common.0.line_height.max(1) as f32;
+            let shift = font.common.0.base as f32 - other.common.0.base as f32 * scale;
+            (scale, shift)
+        })
+        .collect::<Vec<_>>();
+    // { character id: (xoffset, yoffset, xadvance) }
+    let mut metrics = HashMap::<usize, (isize, isize, isize)>::default();
+    let mut glyphs = HashMap::new();
+    for (index, (source, other)) in fonts.iter().enumerate() {
+        let dirname = source.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        let (scale, shift) = transforms[index];
+        for page in &other.pages.pages {
             let path = dirname.join(&page.0.file);
             let image = image::open(&path)
                 .unwrap_or_else(|_| panic!("Could not load font: {:?} image: {:?}", source, path))
                 .into_luma8();
-            font.chars
+            for c in other
                 .chars
+                .chars
+                .iter()
+                .filter(|c| c.0.page == page.0.id && owners.get(&c.0.id) == Some(&index))
+            {
+                let mut image = image
+                    .view(c.0.x as _, c.0.y as _, c.0.width as _, c.0.height as _)
+                    .to_image();
+                if scale != 1.0 {
+                    image = imageops::resize(
+                        &image,
+                        ((c.0.width as f32 * scale).round() as u32).max(1),
+                        ((c.0.height as f32 * scale).round() as u32).max(1),
+                        imageops::FilterType::Lanczos3,
+                    );
+                }
+                glyphs.insert(c.0.id, generator.process(&image));
+                metrics.insert(
+                    c.0.id,
+                    (
+                        (c.0.xoffset as f32 * scale).round() as isize,
+                        (c.0.yoffset as f32 * scale + shift).round() as isize,
+                        (c.0.xadvance as f32 * scale).round() as isize,
+                    ),
+                );
+            }
+        }
+    }
+    let kernings = fonts
+        .iter()
+        .enumerate()
+        .flat_map(|(index, (_, other))| {
+            let (scale, _) = transforms[index];
+            let owners = &owners;
+            other
+                .kernings
                 .iter()
-                .filter(move |c| c.0.page == page.0.id)
-                .map(move |c| {
-                    let image = image
-                        .view(c.0.x as _, c.0.y as _, c.0.width as _, c.0.height as _)
-                        .to_image();
-                    let image = generator.process(&image);
-                    (c.0.id, image)
+                .flat_map(|kernings| kernings.kernings.iter())
+                .filter(move |k| {
+                    owners.get(&k.0.first) == Some(&index)
+                        && owners.get(&k.0.second) == Some(&index)
+                })
+                .map(move |k| FontAssetSourceKerning {
+                    first: character_from_id(k.0.first),
+                    second: character_from_id(k.0.second),
+                    amount: (k.0.amount as f32 * scale).round() as isize,
                 })
         })
-        .collect::<HashMap<_, _>>();
+        .collect();
 
     let config = TexturePackerConfig {
         max_width,
@@ -230,30 +319,22 @@ fn generate_sdf_font(
                 .get_frames()
                 .iter()
                 .map(|(id, frame)| {
-                    let character = &font
-                        .chars
-                        .chars
-                        .iter()
-                        .find(|c| c.0.id == *id)
-                        .unwrap_or_else(|| {
-                            panic!(
-                                "Could not find font: {:?} page: {} character: {}",
-                                source, i, id
-                            )
-                        })
-                        .0;
+                    let (xoffset, yoffset, xadvance) = *metrics.get(id).unwrap_or_else(|| {
+                        panic!(
+                            "Could not find font: {:?} page: {} character: {}",
+                            source, i, id
+                        )
+                    });
                     let character = FontAssetSourceCharacter {
                         x: frame.frame.x as _,
                         y: frame.frame.y as _,
                         width: frame.frame.w as _,
                         height: frame.frame.h as _,
-                        xoffset: character.xoffset,
-                        yoffset: character.yoffset + diff_y,
-                        xadvance: character.xadvance,
+                        xoffset,
+                        yoffset: yoffset + diff_y,
+                        xadvance,
                     };
-                    let c = std::char::from_u32(*id as _).unwrap_or_else(|| {
-                        panic!("Could not convert character id: {} to character", id)
-                    });
+                    let c = character_from_id(*id);
                     (c, character)
                 })
                 .collect();
@@ -270,6 +351,8 @@ fn generate_sdf_font(
         sdf_resolution: generator.resolution,
         pages,
         filtering: image_filtering,
+        kernings,
+        fallback_character,
     };
     let path = target.join("font.json");
     write(
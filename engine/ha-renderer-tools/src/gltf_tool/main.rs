@@ -0,0 +1,784 @@
+use base64::Engine;
+use gltf::{
+    animation::{util::ReadOutputs, Interpolation, Property},
+    image::Source,
+    mesh::Mode,
+    Document, Skin,
+};
+use oxygengine_animation::{phase::*, spline::*};
+use oxygengine_build_tools::*;
+use oxygengine_core::{
+    prefab::{Prefab, PrefabScene, PrefabSceneEntity, PrefabSceneEntityData},
+    scripting::*,
+    Scalar,
+};
+use oxygengine_ha_renderer::{
+    asset_protocols::{image::*, mesh::*, rig::*, rig_animation::*},
+    components::{material_instance::*, mesh_instance::*, rig_instance::*, transform::*},
+    image::*,
+    material::{common::MaterialValue, MaterialReference},
+    math::*,
+    mesh::{geometry::*, rig::skeleton::*, MeshReference},
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fs::{copy, create_dir_all, write},
+    io::Error,
+    path::Path,
+};
+
+const DEFAULT_MATERIAL_ASSET: &str = "@material/graph/surface/flat/color";
+const DEFAULT_TEXTURE_MATERIAL_ASSET: &str = "@material/graph/surface/flat/texture-2d";
+
+#[derive(Debug, Default, Clone, Deserialize)]
+struct Params {
+    #[serde(default)]
+    pub image_filtering: ImageFiltering,
+    #[serde(default)]
+    pub loop_sequences: Vec<String>,
+    #[serde(default)]
+    pub default_state: Option<String>,
+}
+
+impl ParamsFromArgs for Params {}
+
+fn main() -> Result<(), Error> {
+    AssetPipelinePlugin::run::<Params, _>(|input| {
+        let AssetPipelineInput {
+            source,
+            target,
+            assets,
+            params,
+        } = input;
+        create_dir_all(&target)?;
+
+        let source = match source.first() {
+            Some(source) => source,
+            None => return Ok(vec![]),
+        };
+        let mut source_dir = source.to_owned();
+        source_dir.pop();
+        let gltf = gltf::Gltf::open(source)
+            .unwrap_or_else(|error| panic!("Could not open glTF file: {:?}. {}", source, error));
+        let buffers = gltf::import_buffers(&gltf.document, Some(&source_dir), gltf.blob)
+            .unwrap_or_else(|error| panic!("Could not load glTF buffers: {:?}. {}", source, error));
+        let document = gltf.document;
+        let mut assets_used = Vec::default();
+
+        let images = convert_images(&document, &buffers, &source_dir, &target, &assets);
+        for name in images.values() {
+            assets_used.push(format!("image://{}", name));
+        }
+
+        let skins = document
+            .skins()
+            .filter(|skin| skin.joints().next().is_some())
+            .collect::<Vec<_>>();
+        let multiple_skins = skins.len() > 1;
+        let mut skeletons = HashMap::<usize, (GltfSkeleton, String)>::default();
+        for skin in skins {
+            let suffix = if multiple_skins {
+                format!("-{}", skin.index())
+            } else {
+                String::new()
+            };
+            let skeleton = GltfSkeleton::new(&document, &skin, &buffers);
+            let name = format!("rig{}.json", suffix);
+            let path = target.join(&name);
+            let control = RigAssetControl::new(
+                ScriptStructReference::parse("control_rig::AnimationRigControl").unwrap(),
+            )
+            .binding("playing_property", "playing")
+            .binding("speed_property", "speed")
+            .binding("animation_asset_property", "animation-asset")
+            .binding("state_property", "state");
+            let asset = RigAsset::new(skeleton.hierarchy(), Default::default(), vec![control]);
+            write(
+                &path,
+                serde_json::to_string_pretty(&asset)
+                    .unwrap_or_else(|_| panic!("Could not serialize rig asset: {:?}", path)),
+            )
+            .unwrap_or_else(|_| panic!("Could not write rig asset to file: {:?}", path));
+            assets_used.push(format!("rig://{}/{}", assets, name));
+
+            if document.animations().next().is_some() {
+                let name = format!("animation{}.json", suffix);
+                let path = target.join(&name);
+                let asset = convert_animations(&document, &buffers, &skeleton, &params);
+                write(
+                    &path,
+                    serde_json::to_string_pretty(&asset).unwrap_or_else(|_| {
+                        panic!("Could not serialize animation asset: {:?}", path)
+                    }),
+                )
+                .unwrap_or_else(|_| panic!("Could not write animation asset to file: {:?}", path));
+                assets_used.push(format!("riganim://{}/{}", assets, name));
+            }
+            skeletons.insert(skin.index(), (skeleton, format!("{}/{}", assets, name)));
+        }
+
+        // Bone indices baked into vertices depend on skin, so mesh gets converted once per skin
+        // it is bound to across nodes.
+        let mut mesh_skins = HashMap::<usize, BTreeSet<Option<usize>>>::default();
+        for node in document.nodes() {
+            if let Some(mesh) = node.mesh() {
+                let skin = node
+                    .skin()
+                    .map(|skin| skin.index())
+                    .filter(|index| skeletons.contains_key(index));
+                mesh_skins.entry(mesh.index()).or_default().insert(skin);
+            }
+        }
+        let mut meshes = HashMap::<(usize, Option<usize>), Vec<(String, Option<usize>)>>::default();
+        for mesh in document.meshes() {
+            let skins = mesh_skins
+                .remove(&mesh.index())
+                .unwrap_or_else(|| std::iter::once(None).collect());
+            for skin in skins {
+                let skeleton = skin
+                    .and_then(|index| skeletons.get(&index))
+                    .map(|(skeleton, _)| skeleton);
+                for primitive in mesh.primitives() {
+                    let name = match skin {
+                        Some(skin) => format!(
+                            "mesh-{}-{}-skin-{}.json",
+                            mesh.index(),
+                            primitive.index(),
+                            skin
+                        ),
+                        None => format!("mesh-{}-{}.json", mesh.index(), primitive.index()),
+                    };
+                    let path = target.join(&name);
+                    let asset = match convert_primitive(&primitive, &buffers, skeleton) {
+                        Ok(asset) => asset,
+                        Err(error) => {
+                            eprintln!(
+                                "Skipping mesh: {:?} primitive: {}. {}",
+                                mesh.name().unwrap_or_default(),
+                                primitive.index(),
+                                error
+                            );
+                            continue;
+                        }
+                    };
+                    write(
+                        &path,
+                        serde_json::to_string_pretty(&asset).unwrap_or_else(|_| {
+                            panic!("Could not serialize mesh asset: {:?}", path)
+                        }),
+                    )
+                    .unwrap_or_else(|_| panic!("Could not write mesh asset to file: {:?}", path));
+                    assets_used.push(format!("mesh://{}/{}", assets, name));
+                    let image = primitive
+                        .material()
+                        .pbr_metallic_roughness()
+                        .base_color_texture()
+                        .map(|info| info.texture().source().index());
+                    meshes
+                        .entry((mesh.index(), skin))
+                        .or_default()
+                        .push((format!("{}/{}", assets, name), image));
+                }
+            }
+        }
+
+        let rigs = skeletons
+            .into_iter()
+            .map(|(index, (_, rig))| (index, rig))
+            .collect();
+        let path = target.join("prefab.json");
+        let asset = convert_scene(&document, &meshes, &rigs, &images, params.image_filtering);
+        write(
+            &path,
+            asset
+                .to_prefab_string()
+                .unwrap_or_else(|_| panic!("Could not serialize prefab asset: {:?}", path)),
+        )
+        .unwrap_or_else(|_| panic!("Could not write prefab asset to file: {:?}", path));
+        assets_used.push(format!("prefab://{}/prefab.json", assets));
+
+        Ok(assets_used)
+    })
+}
+
+/// Skin joints laid out in the same order as `Skeleton` built from their hierarchy.
+struct GltfSkeleton {
+    /// [(node index, bone name, parent bone index, local transform)]
+    bones: Vec<(usize, String, Option<usize>, HaTransform)>,
+    /// {joint index: bone index}
+    joints: HashMap<usize, usize>,
+}
+
+impl GltfSkeleton {
+    fn new(document: &Document, skin: &Skin, buffers: &[gltf::buffer::Data]) -> Self {
+        let joints = skin.joints().collect::<Vec<_>>();
+        // Bind pose comes from inverse bind matrices (in mesh space) when skin provides them,
+        // otherwise we assume joint nodes are placed in bind pose.
+        let bind_matrices = skin
+            .reader(|buffer| Some(&buffers[buffer.index()]))
+            .read_inverse_bind_matrices()
+            .map(|matrices| {
+                matrices
+                    .map(|matrix| Mat4::from_col_arrays(matrix).inverted())
+                    .collect::<Vec<_>>()
+            });
+        let mut parents = HashMap::<usize, usize>::default();
+        for node in document.nodes() {
+            for child in node.children() {
+                parents.insert(child.index(), node.index());
+            }
+        }
+        let joint_parent = |mut index: usize| {
+            while let Some(parent) = parents.get(&index) {
+                if joints.iter().any(|joint| joint.index() == *parent) {
+                    return Some(*parent);
+                }
+                index = *parent;
+            }
+            None
+        };
+        let mut names = HashSet::<String>::default();
+        let mut result = Self {
+            bones: Vec::with_capacity(joints.len() + 1),
+            joints: Default::default(),
+        };
+        // Bind pose model space matrices of bones.
+        let mut bind_pose = Vec::<Mat4>::with_capacity(joints.len() + 1);
+        let roots = joints
+            .iter()
+            .filter(|joint| joint_parent(joint.index()).is_none())
+            .collect::<Vec<_>>();
+        let root_parent = if roots.len() > 1 {
+            names.insert("root".to_owned());
+            result
+                .bones
+                .push((usize::MAX, "root".to_owned(), None, Default::default()));
+            bind_pose.push(Mat4::identity());
+            Some(0)
+        } else {
+            None
+        };
+        let mut stack = roots
+            .into_iter()
+            .rev()
+            .map(|joint| (joint.index(), root_parent))
+            .collect::<Vec<_>>();
+        while let Some((index, parent)) = stack.pop() {
+            let node = match document.nodes().nth(index) {
+                Some(node) => node,
+                None => continue,
+            };
+            let mut name = node
+                .name()
+                .map(|name| name.to_owned())
+                .unwrap_or_else(|| format!("bone-{}", index));
+            if names.contains(&name) {
+                name = format!("{}-{}", name, index);
+            }
+            names.insert(name.to_owned());
+            let joint = joints.iter().position(|joint| joint.index() == index);
+            let parent_matrix = parent
+                .map(|parent| bind_pose[parent])
+                .unwrap_or_else(Mat4::identity);
+            let matrix = match joint.and_then(|joint| bind_matrices.as_ref()?.get(joint)) {
+                Some(matrix) => *matrix,
+                None => parent_matrix * Mat4::from_col_arrays(node.transform().matrix()),
+            };
+            let transform = HaTransform::from_matrix(parent_matrix.inverted() * matrix);
+            let bone_index = result.bones.len();
+            result.bones.push((index, name, parent, transform));
+            bind_pose.push(matrix);
+            if let Some(joint) = joint {
+                result.joints.insert(joint, bone_index);
+            }
+            let children = joints
+                .iter()
+                .filter(|joint| joint_parent(joint.index()) == Some(index))
+                .map(|joint| (joint.index(), Some(bone_index)))
+                .collect::<Vec<_>>();
+            stack.extend(children.into_iter().rev());
+        }
+        result
+    }
+
+    fn bone_name(&self, node_index: usize) -> Option<&str> {
+        self.bones
+            .iter()
+            .find(|(index, _, _, _)| *index == node_index)
+            .map(|(_, name, _, _)| name.as_str())
+    }
+
+    fn hierarchy(&self) -> SkeletonHierarchy {
+        self.hierarchy_of(0)
+    }
+
+    fn hierarchy_of(&self, bone_index: usize) -> SkeletonHierarchy {
+        let (_, name, _, transform) = &self.bones[bone_index];
+        let children = self
+            .bones
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, parent, _))| *parent == Some(bone_index))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let target = children
+            .first()
+            .map(|index| self.bones[*index].3.get_translation())
+            .unwrap_or_default();
+        children.into_iter().fold(
+            SkeletonHierarchy::new(name)
+                .target(target)
+                .transform(transform.to_owned()),
+            |result, index| result.child(self.hierarchy_of(index)),
+        )
+    }
+}
+
+fn convert_images(
+    document: &Document,
+    buffers: &[gltf::buffer::Data],
+    source_dir: &Path,
+    target: &Path,
+    assets_path_prefix: &str,
+) -> HashMap<usize, String> {
+    let mut result = HashMap::with_capacity(document.images().len());
+    for image in document.images() {
+        let (extension, bytes) = match image.source() {
+            Source::View { view, mime_type } => {
+                let buffer = &buffers[view.buffer().index()];
+                let bytes = buffer[view.offset()..(view.offset() + view.length())].to_owned();
+                (mime_type.trim_start_matches("image/").to_owned(), bytes)
+            }
+            Source::Uri { uri, mime_type } => {
+                if let Some(data) = uri.strip_prefix("data:") {
+                    let (header, data) = data.split_once(',').unwrap_or_default();
+                    let header = match header.strip_suffix(";base64") {
+                        Some(header) => header,
+                        None => {
+                            eprintln!("Skipping non-base64 data URI image: {}", image.index());
+                            continue;
+                        }
+                    };
+                    let bytes = base64::engine::general_purpose::STANDARD
+                        .decode(data)
+                        .unwrap_or_else(|_| {
+                            panic!("Could not decode data URI image: {}", image.index())
+                        });
+                    let extension = mime_type
+                        .unwrap_or(header)
+                        .trim_start_matches("image/")
+                        .to_owned();
+                    (extension, bytes)
+                } else {
+                    let from = source_dir.join(uri);
+                    let extension = mime_type
+                        .map(|mime_type| mime_type.trim_start_matches("image/").to_owned())
+                        .or_else(|| {
+                            from.extension()
+                                .map(|extension| extension.to_string_lossy().to_lowercase())
+                        })
+                        .unwrap_or_default();
+                    let to = target.join(format!("image-{}.{}", image.index(), extension));
+                    copy(&from, &to).unwrap_or_else(|_| {
+                        panic!("Could not copy image file from {:?} to {:?}", from, to)
+                    });
+                    (extension, vec![])
+                }
+            }
+        };
+        let file_name = format!("image-{}.{}", image.index(), extension);
+        if !bytes.is_empty() {
+            let path = target.join(&file_name);
+            write(&path, bytes)
+                .unwrap_or_else(|_| panic!("Could not write image file: {:?}", path));
+        }
+        let bytes_paths = vec![format!("{}/{}", assets_path_prefix, file_name)];
+        let asset = match extension.as_str() {
+            "png" => ImageAssetSource::Png {
+                descriptor: Default::default(),
+                bytes_paths,
+            },
+            "jpeg" | "jpg" => ImageAssetSource::Jpeg {
+                descriptor: Default::default(),
+                bytes_paths,
+            },
+            _ => {
+                eprintln!(
+                    "Skipping image: {} of unsupported type: {}",
+                    image.index(),
+                    extension
+                );
+                continue;
+            }
+        };
+        let name = format!("image-{}.json", image.index());
+        let path = target.join(&name);
+        write(
+            &path,
+            serde_json::to_string_pretty(&asset)
+                .unwrap_or_else(|_| panic!("Could not serialize image asset: {:?}", path)),
+        )
+        .unwrap_or_else(|_| panic!("Could not write image asset to file: {:?}", path));
+        result.insert(image.index(), format!("{}/{}", assets_path_prefix, name));
+    }
+    result
+}
+
+fn convert_primitive(
+    primitive: &gltf::Primitive,
+    buffers: &[gltf::buffer::Data],
+    skeleton: Option<&GltfSkeleton>,
+) -> Result<MeshAsset, String> {
+    if primitive.mode() != Mode::Triangles {
+        return Err(format!(
+            "Unsupported primitive mode: {:?}",
+            primitive.mode()
+        ));
+    }
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let positions = match reader.read_positions() {
+        Some(positions) => positions.map(vek::Vec3::from).collect::<Vec<_>>(),
+        None => return Err("Primitive has no positions".to_owned()),
+    };
+    let mut columns = vec![GeometryVerticesColumn::new(
+        "position",
+        GeometryValues::Vec3F(positions),
+    )];
+    let texture = if let Some(coords) = reader.read_tex_coords(0) {
+        let coords = coords.into_f32().map(vek::Vec2::from).collect();
+        columns.push(GeometryVerticesColumn::new(
+            "textureCoord",
+            GeometryValues::Vec2F(coords),
+        ));
+        true
+    } else {
+        false
+    };
+    let color = if let Some(colors) = reader.read_colors(0) {
+        let colors = colors.into_rgba_f32().map(vek::Vec4::from).collect();
+        columns.push(GeometryVerticesColumn::new(
+            "color",
+            GeometryValues::Vec4F(colors),
+        ));
+        true
+    } else {
+        false
+    };
+    let skinning = match (skeleton, reader.read_joints(0), reader.read_weights(0)) {
+        (Some(skeleton), Some(joints), Some(weights)) => {
+            // Vertex packs four bone indices into single integer, so each one has to fit a byte.
+            let indices = joints
+                .into_u16()
+                .map(|joints| {
+                    joints
+                        .iter()
+                        .enumerate()
+                        .try_fold(0_i32, |accum, (index, joint)| {
+                            let bone = skeleton
+                                .joints
+                                .get(&(*joint as usize))
+                                .copied()
+                                .unwrap_or_default();
+                            if bone > 0xFF {
+                                return Err(format!(
+                                    "Bone index: {} exceeds limit of 256 bones per skin",
+                                    bone
+                                ));
+                            }
+                            Ok(accum | ((bone << (index * 8)) as i32))
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+            let weights = weights.into_f32().map(vek::Vec4::from).collect();
+            columns.push(GeometryVerticesColumn::new(
+                "boneIndices",
+                GeometryValues::Integer(indices),
+            ));
+            columns.push(GeometryVerticesColumn::new(
+                "boneWeights",
+                GeometryValues::Vec4F(weights),
+            ));
+            true
+        }
+        _ => false,
+    };
+    let vertices = GeometryVertices::default()
+        .with_columns(columns)
+        .map_err(|error| format!("Could not build geometry vertices: {:?}", error))?;
+    let indices = match reader.read_indices() {
+        Some(indices) => indices.into_u32().map(|index| index as usize).collect(),
+        None => (0..vertices.len()).collect::<Vec<_>>(),
+    };
+    let triangles = indices
+        .chunks_exact(3)
+        .map(|chunk| GeometryTriangle::new([chunk[0], chunk[1], chunk[2]]))
+        .collect::<GeometryTriangles>();
+    Ok(MeshAsset::Geometry(GeometryMeshAsset {
+        vertex_data: MeshVertexData {
+            texture,
+            color,
+            skinning,
+            deforming: false,
+        },
+        factory: GeometryFactory(Geometry::new(
+            vertices,
+            GeometryPrimitives::triangles(triangles),
+        )),
+    }))
+}
+
+fn convert_animations(
+    document: &Document,
+    buffers: &[gltf::buffer::Data],
+    skeleton: &GltfSkeleton,
+    params: &Params,
+) -> RigAnimationAsset {
+    let mut sequences = HashMap::with_capacity(document.animations().len());
+    for animation in document.animations() {
+        let name = animation
+            .name()
+            .map(|name| name.to_owned())
+            .unwrap_or_else(|| format!("animation-{}", animation.index()));
+        let mut bone_sheets = HashMap::<String, RigAnimationSequenceBoneSheet>::default();
+        for channel in animation.channels() {
+            let bone = match skeleton.bone_name(channel.target().node().index()) {
+                Some(bone) => bone,
+                None => continue,
+            };
+            let interpolation = channel.sampler().interpolation();
+            let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+            let times = match reader.read_inputs() {
+                Some(times) => times.collect::<Vec<_>>(),
+                None => continue,
+            };
+            let sheet = bone_sheets.entry(bone.to_owned()).or_default();
+            let property = channel.target().property();
+            let result = match (property, reader.read_outputs()) {
+                (Property::Translation, Some(ReadOutputs::Translations(values))) => {
+                    let values = keyframe_values(values.collect(), interpolation);
+                    extract_phases(&times, &values, interpolation).map(|[x, y, z]| {
+                        sheet.translation_x = x;
+                        sheet.translation_y = y;
+                        sheet.translation_z = z;
+                    })
+                }
+                (Property::Scale, Some(ReadOutputs::Scales(values))) => {
+                    let values = keyframe_values(values.collect(), interpolation);
+                    extract_phases(&times, &values, interpolation).map(|[x, y, z]| {
+                        sheet.scale_x = x;
+                        sheet.scale_y = y;
+                        sheet.scale_z = z;
+                    })
+                }
+                (Property::Rotation, Some(ReadOutputs::Rotations(values))) => {
+                    let values = keyframe_values(values.into_f32().collect(), interpolation);
+                    let values = unwrap_rotations(values);
+                    extract_phases(&times, &values, interpolation).map(|[roll, pitch, yaw]| {
+                        sheet.rotation_roll = roll;
+                        sheet.rotation_pitch = pitch;
+                        sheet.rotation_yaw = yaw;
+                    })
+                }
+                _ => Ok(()),
+            };
+            if let Err(error) = result {
+                eprintln!(
+                    "Skipping animation: {} bone: {} {:?} channel. {}",
+                    name, bone, property, error
+                );
+            }
+        }
+        let sequence = RigAnimationSequence {
+            speed: 1.0,
+            looping: params.loop_sequences.iter().any(|n| n == &name),
+            bounce: false,
+            bone_sheets,
+            signals: vec![],
+        };
+        sequences.insert(name, sequence);
+    }
+    let states = sequences
+        .keys()
+        .map(|name| {
+            let state = RigAnimationState {
+                sequences: RigAnimationStateSequences::Single(name.to_owned()),
+                rules: Default::default(),
+            };
+            (name.to_owned(), state)
+        })
+        .collect();
+    let default_state = params.default_state.to_owned().or_else(|| {
+        document.animations().next().map(|animation| {
+            animation
+                .name()
+                .map(|name| name.to_owned())
+                .unwrap_or_else(|| format!("animation-{}", animation.index()))
+        })
+    });
+    RigAnimationAsset {
+        default_state,
+        speed: 1.0,
+        sequences,
+        states,
+        rules: vec![],
+    }
+}
+
+/// Cubic spline keyframes store (in-tangent, value, out-tangent) triplets - we keep values only.
+fn keyframe_values<const N: usize>(
+    values: Vec<[Scalar; N]>,
+    interpolation: Interpolation,
+) -> Vec<[Scalar; N]> {
+    if interpolation == Interpolation::CubicSpline {
+        values.chunks_exact(3).map(|chunk| chunk[1]).collect()
+    } else {
+        values
+    }
+}
+
+/// Converts quaternions into Euler angles (roll, pitch, yaw) in degrees, without jumps between
+/// consecutive keyframes so interpolation does not spin the long way around.
+fn unwrap_rotations(values: Vec<[Scalar; 4]>) -> Vec<[Scalar; 3]> {
+    let mut result = Vec::<[Scalar; 3]>::with_capacity(values.len());
+    for [x, y, z, w] in values {
+        let eulers = Eulers::from(Quat::from_xyzw(x, y, z, w));
+        let mut current = [eulers.roll, eulers.pitch, eulers.yaw];
+        if let Some(prev) = result.last() {
+            for (value, prev) in current.iter_mut().zip(prev.iter()) {
+                while *value - prev > 180.0 {
+                    *value -= 360.0;
+                }
+                while *value - prev < -180.0 {
+                    *value += 360.0;
+                }
+            }
+        }
+        result.push(current);
+    }
+    result
+}
+
+fn extract_phases<const N: usize>(
+    times: &[Scalar],
+    values: &[[Scalar; N]],
+    interpolation: Interpolation,
+) -> Result<[Option<Phase>; 3], SplineError> {
+    let mut result = [None, None, None];
+    for (channel, phase) in result.iter_mut().enumerate() {
+        *phase = extract_phase(times, values, interpolation, channel)?;
+    }
+    Ok(result)
+}
+
+/// Single keyframe channels hold their value for the whole sequence.
+fn extract_phase<const N: usize>(
+    times: &[Scalar],
+    values: &[[Scalar; N]],
+    interpolation: Interpolation,
+    channel: usize,
+) -> Result<Option<Phase>, SplineError> {
+    if times.len() == 1 {
+        return match values.first() {
+            Some(value) => Phase::point(value[channel]).map(Some),
+            None => Ok(None),
+        };
+    }
+    let mut points = Vec::with_capacity(times.len() * 2);
+    for (index, (time, value)) in times.iter().zip(values.iter()).enumerate() {
+        if interpolation == Interpolation::Step && index > 0 {
+            let prev = values[index - 1][channel];
+            points.push(SplinePoint::point(((*time - 1.0e-4).max(0.0), prev)));
+        }
+        points.push(SplinePoint::point((*time, value[channel])));
+    }
+    if points.is_empty() {
+        return Ok(None);
+    }
+    Phase::new(points).map(Some)
+}
+
+fn convert_scene(
+    document: &Document,
+    meshes: &HashMap<(usize, Option<usize>), Vec<(String, Option<usize>)>>,
+    rigs: &HashMap<usize, String>,
+    images: &HashMap<usize, String>,
+    image_filtering: ImageFiltering,
+) -> PrefabScene {
+    let mut result = PrefabScene::default();
+    let scene = match document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+    {
+        Some(scene) => scene,
+        None => return result,
+    };
+    let mut stack = scene
+        .nodes()
+        .map(|node| (node, Mat4::identity()))
+        .collect::<Vec<_>>();
+    while let Some((node, parent_matrix)) = stack.pop() {
+        let matrix = parent_matrix * Mat4::from_col_arrays(node.transform().matrix());
+        let rig = node
+            .skin()
+            .and_then(|skin| Some((skin.index(), rigs.get(&skin.index())?)));
+        let primitives = node
+            .mesh()
+            .and_then(|mesh| meshes.get(&(mesh.index(), rig.map(|(index, _)| index))));
+        if let Some(primitives) = primitives {
+            for (mesh, image) in primitives {
+                let mut entity_data = PrefabSceneEntityData::default();
+                entity_data.components.insert(
+                    "HaTransform".to_owned(),
+                    HaTransform::from_matrix(matrix)
+                        .to_prefab()
+                        .unwrap_or_else(|_| panic!("Could not serialize HaTransform to prefab")),
+                );
+                entity_data.components.insert(
+                    "HaMeshInstance".to_owned(),
+                    HaMeshInstance {
+                        reference: MeshReference::Asset(mesh.to_owned()),
+                        override_draw_range: None,
+                    }
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize HaMeshInstance to prefab")),
+                );
+                let image = image.and_then(|index| images.get(&index));
+                let material = match image {
+                    Some(image) => HaMaterialInstance::new(MaterialReference::Asset(
+                        DEFAULT_TEXTURE_MATERIAL_ASSET.to_owned(),
+                    ))
+                    .with_value(
+                        "mainImage",
+                        MaterialValue::Sampler2d {
+                            reference: ImageReference::Asset(image.to_owned()),
+                            filtering: image_filtering,
+                        },
+                    ),
+                    None => HaMaterialInstance::new(MaterialReference::Asset(
+                        DEFAULT_MATERIAL_ASSET.to_owned(),
+                    )),
+                };
+                entity_data.components.insert(
+                    "HaMaterialInstance".to_owned(),
+                    material.to_prefab().unwrap_or_else(|_| {
+                        panic!("Could not serialize HaMaterialInstance to prefab")
+                    }),
+                );
+                if let Some((_, rig)) = rig {
+                    let mut rig_instance = HaRigInstance::default();
+                    rig_instance.set_asset(rig);
+                    entity_data.components.insert(
+                        "HaRigInstance".to_owned(),
+                        rig_instance.to_prefab().unwrap_or_else(|_| {
+                            panic!("Could not serialize HaRigInstance to prefab")
+                        }),
+                    );
+                }
+                result.entities.push(PrefabSceneEntity::Data(entity_data));
+            }
+        }
+        stack.extend(node.children().map(|child| (child, matrix)));
+    }
+    result
+}
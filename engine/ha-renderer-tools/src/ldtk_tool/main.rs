@@ -1,10 +1,13 @@
+#[path = "../level_prefab.rs"]
+mod level_prefab;
 pub mod schema;
 
+use crate::level_prefab::*;
 use image::*;
 use oxygengine_build_tools::*;
 use oxygengine_core::{
     ecs::components::{Name, NonPersistentPrefabProxy, Tag},
-    prefab::{Prefab, PrefabScene, PrefabSceneEntity, PrefabSceneEntityData, PrefabValue},
+    prefab::{Prefab, PrefabScene, PrefabSceneEntity, PrefabSceneEntityData},
     Scalar,
 };
 use oxygengine_ha_renderer::{
@@ -21,21 +24,15 @@ use oxygengine_ha_renderer::{
     render_target::*,
 };
 use schema::*;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::Value;
 use std::{
     collections::{HashMap, HashSet},
     fs::{create_dir_all, read_to_string, write},
     io::Error,
-    path::{Path, PathBuf},
+    path::Path,
 };
 
-const DEFAULT_SPRITE_MATERIAL_ASSET: &str = "@material/graph/surface/flat/texture-2d";
-const DEFAULT_SPRITE_UNIFORMS_MATERIAL_ASSET: &str =
-    "@material/graph/surface/flat/virtual-uniform-texture-2d";
-const DEFAULT_SPRITE_MESH_ASSET: &str = "@mesh/surface/quad/pt";
-const DEFAULT_SPRITE_IMAGE: &str = "Uniforms";
-
 impl Project {
     pub fn new(path: impl AsRef<Path>) -> Self {
         let path = path.as_ref();
@@ -308,6 +305,9 @@ fn bake_project(
                             col: col as _,
                             row: row as _,
                             atlas_item: format!("{}x{}", tcol, trow),
+                            flip_x: tile.f & 1 != 0,
+                            flip_y: tile.f & 2 != 0,
+                            ..Default::default()
                         }
                     })
                     .collect::<Vec<_>>();
@@ -917,90 +917,6 @@ fn entity_field_value<'a>(
         })
 }
 
-fn ensure_path(path: &Path) {
-    if path.extension().is_some() {
-        let path = path
-            .parent()
-            .map(|p| p.to_path_buf())
-            .unwrap_or_else(|| panic!("Could not get directory of file: {:?}", path));
-        create_dir_all(&path)
-            .unwrap_or_else(|_| panic!("Could not create directories: {:?}", path));
-    } else {
-        create_dir_all(path).unwrap_or_else(|_| panic!("Could not create directories: {:?}", path));
-    }
-}
-
-fn process_macro(content: &str, variables: HashMap<String, String>, name: &str) -> String {
-    chrobry_core::generate(content, "\n", variables, |_| Ok(Default::default())).unwrap_or_else(
-        |error| {
-            panic!(
-                "Could not process macro for component: {} | Error: {:?}",
-                name, error
-            )
-        },
-    )
-}
-
-fn is_separator(c: char) -> bool {
-    c == '\r' || c == '\n' || c == '|'
-}
-
-fn parse_viewport_value(v: &str) -> RenderTargetClipAreaValue {
-    let parts = v.trim().split(':').collect::<Vec<_>>();
-    match parts[0] {
-        "Exact" => RenderTargetClipAreaValue::Exact(
-            parts
-                .get(1)
-                .expect("Exact value expects a value!")
-                .trim()
-                .parse()
-                .expect("Exact value cannot be parsed!"),
-        ),
-        "Margin" => RenderTargetClipAreaValue::Margin(
-            parts
-                .get(1)
-                .expect("Margin value expects a value!")
-                .trim()
-                .parse()
-                .expect("Margin value cannot be parsed!"),
-        ),
-        "Anchor" => RenderTargetClipAreaValue::Anchor(
-            parts
-                .get(1)
-                .expect("Anchor value expects a value!")
-                .trim()
-                .parse()
-                .expect("Anchor value cannot be parsed!"),
-        ),
-        "AspectRatio" => {
-            let width = parts
-                .get(1)
-                .expect("AspectRatio first value expects a value!")
-                .trim()
-                .parse()
-                .expect("AspectRatio first value cannot be parsed!");
-            let height = parts
-                .get(2)
-                .expect("AspectRatio second value expects a value!")
-                .trim()
-                .parse()
-                .expect("AspectRatio second value cannot be parsed!");
-            RenderTargetClipAreaValue::AspectRatio { width, height }
-        }
-        _ => RenderTargetClipAreaValue::Full,
-    }
-}
-
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-struct ComponentsPrefab(pub HashMap<String, PrefabValue>);
-impl Prefab for ComponentsPrefab {}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ComponentRule {
-    pub name: String,
-    pub macro_file: PathBuf,
-}
-
 fn level_variables(level: &Level) -> HashMap<String, String> {
     let mut result = HashMap::with_capacity(5);
     result.insert("level_identifier".to_owned(), level.identifier.to_string());
@@ -0,0 +1,98 @@
+use oxygengine_core::prefab::{Prefab, PrefabValue};
+use oxygengine_ha_renderer::render_target::RenderTargetClipAreaValue;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+};
+
+pub const DEFAULT_SPRITE_MATERIAL_ASSET: &str = "@material/graph/surface/flat/texture-2d";
+pub const DEFAULT_SPRITE_UNIFORMS_MATERIAL_ASSET: &str =
+    "@material/graph/surface/flat/virtual-uniform-texture-2d";
+pub const DEFAULT_SPRITE_MESH_ASSET: &str = "@mesh/surface/quad/pt";
+pub const DEFAULT_SPRITE_IMAGE: &str = "Uniforms";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ComponentsPrefab(pub HashMap<String, PrefabValue>);
+impl Prefab for ComponentsPrefab {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentRule {
+    pub name: String,
+    pub macro_file: PathBuf,
+}
+
+pub fn ensure_path(path: &Path) {
+    if path.extension().is_some() {
+        let path = path
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| panic!("Could not get directory of file: {:?}", path));
+        create_dir_all(&path)
+            .unwrap_or_else(|_| panic!("Could not create directories: {:?}", path));
+    } else {
+        create_dir_all(path).unwrap_or_else(|_| panic!("Could not create directories: {:?}", path));
+    }
+}
+
+pub fn process_macro(content: &str, variables: HashMap<String, String>, name: &str) -> String {
+    chrobry_core::generate(content, "\n", variables, |_| Ok(Default::default())).unwrap_or_else(
+        |error| {
+            panic!(
+                "Could not process macro for component: {} | Error: {:?}",
+                name, error
+            )
+        },
+    )
+}
+
+pub fn is_separator(c: char) -> bool {
+    c == '\r' || c == '\n' || c == '|'
+}
+
+pub fn parse_viewport_value(v: &str) -> RenderTargetClipAreaValue {
+    let parts = v.trim().split(':').collect::<Vec<_>>();
+    match parts[0] {
+        "Exact" => RenderTargetClipAreaValue::Exact(
+            parts
+                .get(1)
+                .expect("Exact value expects a value!")
+                .trim()
+                .parse()
+                .expect("Exact value cannot be parsed!"),
+        ),
+        "Margin" => RenderTargetClipAreaValue::Margin(
+            parts
+                .get(1)
+                .expect("Margin value expects a value!")
+                .trim()
+                .parse()
+                .expect("Margin value cannot be parsed!"),
+        ),
+        "Anchor" => RenderTargetClipAreaValue::Anchor(
+            parts
+                .get(1)
+                .expect("Anchor value expects a value!")
+                .trim()
+                .parse()
+                .expect("Anchor value cannot be parsed!"),
+        ),
+        "AspectRatio" => {
+            let width = parts
+                .get(1)
+                .expect("AspectRatio first value expects a value!")
+                .trim()
+                .parse()
+                .expect("AspectRatio first value cannot be parsed!");
+            let height = parts
+                .get(2)
+                .expect("AspectRatio second value expects a value!")
+                .trim()
+                .parse()
+                .expect("AspectRatio second value cannot be parsed!");
+            RenderTargetClipAreaValue::AspectRatio { width, height }
+        }
+        _ => RenderTargetClipAreaValue::Full,
+    }
+}
@@ -413,7 +413,7 @@ fn convert_atlas(atlas: &Atlas, assets_path_prefix: &str) -> AtlasAssetSource {
             let id = id.to_owned();
             let region = AtlasRegion {
                 rect: region.rect(),
-                layer: 0,
+                ..Default::default()
             };
             (id, region)
         })
@@ -0,0 +1,997 @@
+#[path = "../level_prefab.rs"]
+mod level_prefab;
+mod schema;
+mod tmx;
+
+use crate::{level_prefab::*, schema::*};
+use image::open;
+use oxygengine_build_tools::*;
+use oxygengine_core::{
+    ecs::components::{Name, NonPersistentPrefabProxy, Tag},
+    prefab::{Prefab, PrefabScene, PrefabSceneEntity, PrefabSceneEntityData},
+};
+use oxygengine_ha_renderer::{
+    asset_protocols::{atlas::*, image::*, tilemap::*},
+    components::{
+        camera::*, gizmo::*, material_instance::*, mesh_instance::*, sprite_animation_instance::*,
+        tilemap_instance::*, transform::*, virtual_image_uniforms::*, visibility::*, volume::*,
+    },
+    ha_renderer::*,
+    image::*,
+    material::*,
+    math::*,
+    mesh::*,
+    render_target::*,
+};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{create_dir_all, read_to_string, write},
+    io::Error,
+    path::Path,
+};
+
+fn load_map(path: &Path) -> Map {
+    if path.extension().and_then(|extension| extension.to_str()) == Some("tmx") {
+        return tmx::load_map(path);
+    }
+    let content =
+        read_to_string(path).unwrap_or_else(|_| panic!("Could not load Tiled map: {:?}", path));
+    serde_json::from_str(&content).unwrap_or_else(|error| {
+        panic!("Could not parse Tiled map: {:?} | Error: {:?}", path, error)
+    })
+}
+
+fn load_tileset(path: &Path) -> Tileset {
+    if path.extension().and_then(|extension| extension.to_str()) == Some("tsx") {
+        return tmx::load_tileset(path);
+    }
+    let content =
+        read_to_string(path).unwrap_or_else(|_| panic!("Could not load Tiled tileset: {:?}", path));
+    serde_json::from_str(&content).unwrap_or_else(|error| {
+        panic!(
+            "Could not parse Tiled tileset: {:?} | Error: {:?}",
+            path, error
+        )
+    })
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Params {
+    #[serde(default)]
+    pub tile_margin: Vec2,
+    #[serde(default)]
+    pub image_filtering: ImageFiltering,
+    #[serde(default)]
+    pub material_name: Option<String>,
+    #[serde(default)]
+    pub image_folder_name: Option<String>,
+    #[serde(default)]
+    pub atlas_folder_name: Option<String>,
+    #[serde(default)]
+    pub prefab_folder_name: Option<String>,
+    #[serde(default)]
+    pub data_folder_name: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<ComponentRule>,
+}
+
+impl ParamsFromArgs for Params {}
+
+fn main() -> Result<(), Error> {
+    AssetPipelinePlugin::run::<Params, _>(|input| {
+        let AssetPipelineInput {
+            source,
+            target,
+            assets,
+            params,
+        } = input;
+        create_dir_all(&target)?;
+
+        let rules = params
+            .rules
+            .iter()
+            .map(|rule| {
+                let content = read_to_string(&rule.macro_file)
+                    .unwrap_or_else(|_| panic!("Could not open macro file: {:?}", rule.macro_file));
+                (rule.name.as_str(), content)
+            })
+            .collect::<Vec<_>>();
+
+        let mut baker = Baker {
+            output: &target,
+            tile_margin: params.tile_margin,
+            image_filtering: params.image_filtering,
+            material_name: params
+                .material_name
+                .as_deref()
+                .unwrap_or(DEFAULT_SPRITE_MATERIAL_ASSET),
+            assets_path_prefix: &assets,
+            image_folder_name: params.image_folder_name.as_deref().unwrap_or("images"),
+            atlas_folder_name: params.atlas_folder_name.as_deref().unwrap_or("atlases"),
+            prefab_folder_name: params.prefab_folder_name.as_deref().unwrap_or("prefabs"),
+            data_folder_name: params.data_folder_name.as_deref().unwrap_or("data"),
+            rules: &rules,
+            atlases: Default::default(),
+            instance_types_used: Default::default(),
+            assets_used: Default::default(),
+        };
+        // Maps share tilesets baking, so tilesets used by many maps are baked only once.
+        for path in &source {
+            baker.bake_map(path);
+        }
+        Ok(baker.assets_used.into_iter().collect())
+    })
+}
+
+struct MapTileset {
+    firstgid: u32,
+    tileset: Tileset,
+    atlas: String,
+}
+
+impl MapTileset {
+    /// Returns (tileset index, local tile id) of global tile id.
+    fn find(tilesets: &[Self], gid: u32) -> Option<(usize, u32)> {
+        tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, tileset)| tileset.firstgid <= gid)
+            .last()
+            .map(|(index, tileset)| (index, gid - tileset.firstgid))
+    }
+
+    fn atlas_item(&self, id: u32) -> String {
+        let columns = self.tileset.columns.max(1) as u32;
+        format!("{}x{}", id % columns, id / columns)
+    }
+}
+
+struct Baker<'a> {
+    output: &'a Path,
+    tile_margin: Vec2,
+    image_filtering: ImageFiltering,
+    material_name: &'a str,
+    assets_path_prefix: &'a str,
+    image_folder_name: &'a str,
+    atlas_folder_name: &'a str,
+    prefab_folder_name: &'a str,
+    data_folder_name: &'a str,
+    rules: &'a [(&'a str, String)],
+    /// { tileset name: atlas asset name }
+    atlases: HashMap<String, String>,
+    instance_types_used: HashSet<String>,
+    assets_used: HashSet<String>,
+}
+
+impl<'a> Baker<'a> {
+    fn bake_tileset(&mut self, tileset: &Tileset, input: &Path) -> String {
+        if let Some(atlas_name) = self.atlases.get(&tileset.name) {
+            return atlas_name.to_owned();
+        }
+        let rel_path = tileset.image.as_ref().unwrap_or_else(|| {
+            panic!(
+                "Tileset: {} is not based on single image - image collections are not supported",
+                tileset.name
+            )
+        });
+
+        let image_bytes_name = format!(
+            "{}/{}/{}.png",
+            self.assets_path_prefix, self.image_folder_name, tileset.name
+        );
+        let image_bytes_path = self
+            .output
+            .join(self.image_folder_name)
+            .join(&tileset.name)
+            .with_extension("png");
+        ensure_path(&image_bytes_path);
+        let source_path = input.join(rel_path);
+        open(&source_path)
+            .unwrap_or_else(|error| {
+                panic!(
+                    "Could not open file: {:?} | Error: {:?}",
+                    source_path, error,
+                )
+            })
+            .into_rgba8()
+            .save(&image_bytes_path)
+            .unwrap_or_else(|error| {
+                panic!(
+                    "Could not save file: {:?} | Error: {:?}",
+                    image_bytes_path, error,
+                )
+            });
+
+        let image_name = format!(
+            "{}/{}/{}.json",
+            self.assets_path_prefix, self.image_folder_name, tileset.name
+        );
+        let asset = ImageAssetSource::Png {
+            descriptor: ImageDescriptor::default(),
+            bytes_paths: vec![image_bytes_name],
+        };
+        let image_path = self
+            .output
+            .join(self.image_folder_name)
+            .join(&tileset.name)
+            .with_extension("json");
+        ensure_path(&image_path);
+        write(
+            &image_path,
+            serde_json::to_string_pretty(&asset).unwrap_or_else(|_| {
+                panic!(
+                    "Could not serialize image asset for tileset: {:?}",
+                    tileset.name
+                )
+            }),
+        )
+        .unwrap_or_else(|_| panic!("Could not write image asset to file: {:?}", image_path));
+
+        let atlas_name = format!(
+            "{}/{}/{}.json",
+            self.assets_path_prefix, self.atlas_folder_name, tileset.name
+        );
+        let cols = tileset.columns.max(1);
+        let asset = AtlasAssetSource::TileSet({
+            let mut map = HashMap::with_capacity(1);
+            map.insert(
+                image_name,
+                TileSetPage {
+                    cols,
+                    rows: tileset.tilecount.div_ceil(cols),
+                    layers: 1,
+                    cell_size: Vec2::new(tileset.tilewidth as _, tileset.tileheight as _),
+                    padding: Vec2::new(tileset.margin as _, tileset.margin as _),
+                    spacing: Vec2::new(tileset.spacing as _, tileset.spacing as _),
+                    tile_margin: self.tile_margin,
+                },
+            );
+            map
+        });
+        let atlas_path = self
+            .output
+            .join(self.atlas_folder_name)
+            .join(&tileset.name)
+            .with_extension("json");
+        ensure_path(&atlas_path);
+        write(
+            &atlas_path,
+            serde_json::to_string_pretty(&asset).unwrap_or_else(|_| {
+                panic!(
+                    "Could not serialize atlas asset for tileset: {}",
+                    tileset.name
+                )
+            }),
+        )
+        .unwrap_or_else(|_| panic!("Could not write tileset asset to file: {:?}", atlas_path));
+        self.atlases
+            .insert(tileset.name.to_owned(), atlas_name.to_owned());
+        atlas_name
+    }
+
+    fn bake_map(&mut self, path: &Path) {
+        let map = load_map(path);
+        let map_name = path
+            .file_stem()
+            .unwrap_or_else(|| panic!("Could not get map file name: {:?}", path))
+            .to_string_lossy()
+            .to_string();
+        if property_value("Ignore", &map.properties)
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default()
+        {
+            return;
+        }
+        if map.infinite {
+            panic!("Infinite maps are not supported: {:?}", path);
+        }
+        if !map.orientation.is_empty() && map.orientation != "orthogonal" {
+            panic!(
+                "Only orthogonal maps are supported, map: {:?} has orientation: {}",
+                path, map.orientation
+            );
+        }
+
+        let input = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        let mut tilesets = map
+            .tilesets
+            .iter()
+            .map(|reference| {
+                let (tileset, directory) = match &reference.source {
+                    Some(source) => {
+                        let path = input.join(source);
+                        let directory = path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+                        (load_tileset(&path), directory)
+                    }
+                    None => (reference.tileset.to_owned(), input.to_owned()),
+                };
+                let atlas = self.bake_tileset(&tileset, &directory);
+                MapTileset {
+                    firstgid: reference.firstgid,
+                    tileset,
+                    atlas,
+                }
+            })
+            .collect::<Vec<_>>();
+        tilesets.sort_by_key(|tileset| tileset.firstgid);
+
+        let mut layers = vec![];
+        flatten_layers(&map.layers, Vec2::zero(), &mut layers);
+        let variables = map_variables(&map_name, &map);
+        let cell_size = Vec2::new(map.tilewidth as _, map.tileheight as _);
+        let mut asset = PrefabScene {
+            template_name: Some(map_name.to_owned()),
+            ..Default::default()
+        };
+        let mut data_asset = Option::<TileMapAsset>::None;
+
+        for (layer, offset) in &layers {
+            if layer.kind != LayerKind::Tiles {
+                continue;
+            }
+            let data_asset = data_asset.get_or_insert_with(|| TileMapAsset {
+                x: 0,
+                y: 0,
+                cols: map.width,
+                rows: map.height,
+                cell_size,
+                values: vec![0; map.width * map.height],
+            });
+            let cols = if layer.width > 0 {
+                layer.width
+            } else {
+                map.width
+            };
+            // { tileset index: tiles }
+            let mut groups = BTreeMap::<usize, Vec<HaTileMapTile>>::new();
+            for (index, tile) in layer.tiles().into_iter().enumerate() {
+                if tile.gid == 0 {
+                    continue;
+                }
+                let (tileset_index, id) =
+                    MapTileset::find(&tilesets, tile.gid).unwrap_or_else(|| {
+                        panic!(
+                            "Tileset not found for tile: {} in layer: {}",
+                            tile.gid, layer.name
+                        )
+                    });
+                let tileset = &tilesets[tileset_index];
+                let col = index % cols;
+                let row = index / cols;
+                if let Some(value) = tileset
+                    .tileset
+                    .tile_property(id, "Value")
+                    .and_then(|v| v.as_u64())
+                {
+                    if value > 0 && col < map.width && row < map.height {
+                        let index = data_asset.index((col, row));
+                        data_asset.values[index] = value as usize;
+                    }
+                }
+                groups
+                    .entry(tileset_index)
+                    .or_default()
+                    .push(HaTileMapTile {
+                        col,
+                        row,
+                        atlas_item: tileset.atlas_item(id),
+                        flip_x: tile.flip_x,
+                        flip_y: tile.flip_y,
+                        flip_diagonal: tile.flip_diagonal,
+                    });
+            }
+
+            let mut variables = variables.to_owned();
+            variables.extend(layer_variables(layer, *offset));
+            let properties = layer
+                .properties
+                .iter()
+                .chain(map.properties.iter())
+                .cloned()
+                .collect::<Vec<_>>();
+
+            // Single tilemap instance uses single atlas, so tiles are split by their tilesets.
+            for (tileset_index, tiles) in groups {
+                let atlas = tilesets[tileset_index].atlas.to_owned();
+                self.assets_used.insert(format!("atlas://{}", atlas));
+                let mut entity_data = PrefabSceneEntityData::default();
+                self.apply_rules(&properties, &variables, &layer.name, &mut entity_data);
+                entity_data.components.insert(
+                    "NonPersistent".to_owned(),
+                    NonPersistentPrefabProxy
+                        .to_prefab()
+                        .unwrap_or_else(|_| panic!("Could not serialize NonPersistent to prefab")),
+                );
+                entity_data.components.insert(
+                    "HaTransform".to_owned(),
+                    HaTransform::default()
+                        .with_translation(Vec3::new(offset.x, offset.y, 0.0))
+                        .to_prefab()
+                        .unwrap_or_else(|_| panic!("Could not serialize HaTransform to prefab")),
+                );
+                let mut instance = HaTileMapInstance::default();
+                instance.filtering = self.image_filtering;
+                instance.set_atlas(atlas);
+                instance.set_cols(cols);
+                instance.set_rows(if layer.height > 0 {
+                    layer.height
+                } else {
+                    map.height
+                });
+                instance.set_tiles(tiles);
+                instance.set_cell_size(cell_size);
+                entity_data.components.insert(
+                    "HaTileMapInstance".to_owned(),
+                    instance.to_prefab().unwrap_or_else(|_| {
+                        panic!(
+                            "Could not serialize HaTileMapInstance to prefab for layer: {}",
+                            layer.name
+                        )
+                    }),
+                );
+                entity_data.components.insert(
+                    "HaMeshInstance".to_owned(),
+                    HaMeshInstance::default()
+                        .to_prefab()
+                        .unwrap_or_else(|_| panic!("Could not serialize HaMeshInstance to prefab")),
+                );
+                entity_data.components.insert(
+                    "HaMaterialInstance".to_owned(),
+                    HaMaterialInstance {
+                        reference: MaterialReference::Asset(self.material_name.to_owned()),
+                        ..Default::default()
+                    }
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize HaMaterialInstance to prefab")),
+                );
+                asset.entities.push(PrefabSceneEntity::Data(entity_data));
+            }
+        }
+
+        if let Some(data_asset) = data_asset {
+            let data_name = format!(
+                "{}/{}/{}.json",
+                self.assets_path_prefix, self.data_folder_name, map_name
+            );
+            let data_path = self
+                .output
+                .join(self.data_folder_name)
+                .join(&map_name)
+                .with_extension("json");
+            ensure_path(&data_path);
+            write(
+                &data_path,
+                serde_json::to_string_pretty(&data_asset).unwrap_or_else(|_| {
+                    panic!(
+                        "Could not serialize tilemap data asset for map: {:?}",
+                        map_name
+                    )
+                }),
+            )
+            .unwrap_or_else(|_| panic!("Could not write tilemap asset to file: {:?}", data_path));
+            self.assets_used.insert(format!("tilemap://{}", data_name));
+        }
+
+        for (layer, offset) in &layers {
+            if layer.kind != LayerKind::Objects {
+                continue;
+            }
+            let mut variables = variables.to_owned();
+            variables.extend(layer_variables(layer, *offset));
+            for object in &layer.objects {
+                if !object.visible {
+                    continue;
+                }
+                if let Some(entity) = self.bake_object(object, *offset, &tilesets, &variables) {
+                    asset.entities.push(entity);
+                }
+            }
+        }
+
+        self.assets_used.insert(format!(
+            "prefab://{}/{}/{}.json",
+            self.assets_path_prefix, self.prefab_folder_name, map_name
+        ));
+        let prefab_path = self
+            .output
+            .join(self.prefab_folder_name)
+            .join(&map_name)
+            .with_extension("json");
+        ensure_path(&prefab_path);
+        write(
+            &prefab_path,
+            asset.to_prefab_string().unwrap_or_else(|_| {
+                panic!("Could not serialize prefab asset for map: {}", map_name)
+            }),
+        )
+        .unwrap_or_else(|_| panic!("Could not write prefab asset to file: {:?}", prefab_path));
+    }
+
+    fn bake_object(
+        &mut self,
+        object: &Object,
+        offset: Vec2,
+        tilesets: &[MapTileset],
+        variables: &HashMap<String, String>,
+    ) -> Option<PrefabSceneEntity> {
+        let identifier = if object.name.is_empty() {
+            format!("#{}", object.id)
+        } else {
+            object.name.to_owned()
+        };
+        let tile = object.gid.map(GlobalTile::new);
+        let tile_source = tile.and_then(|tile| {
+            MapTileset::find(tilesets, tile.gid).map(|(index, id)| (&tilesets[index], id))
+        });
+        // Tile objects inherit properties of their tiles.
+        let properties = object
+            .properties
+            .iter()
+            .cloned()
+            .chain(
+                tile_source
+                    .and_then(|(tileset, id)| {
+                        tileset.tileset.tiles.iter().find(|tile| tile.id == id)
+                    })
+                    .map(|tile| tile.properties.to_owned())
+                    .unwrap_or_default(),
+            )
+            .collect::<Vec<_>>();
+        let field = |name: &str| property_value(name, &properties);
+
+        if let Some(value) = field("Assets") {
+            if let Some(value) = value.as_str() {
+                for name in value.split(is_separator) {
+                    self.assets_used.insert(name.to_owned());
+                }
+            } else {
+                for value in value.as_array().unwrap_or_else(|| {
+                    panic!("Assets property of object: {} is not an array!", identifier)
+                }) {
+                    let name = value.as_str().unwrap_or_else(|| {
+                        panic!(
+                            "Assets array item property of object: {} is not a string!",
+                            identifier
+                        )
+                    });
+                    self.assets_used.insert(name.to_owned());
+                }
+            }
+        }
+        if let Some(value) = field("Template") {
+            let name = value.as_str().unwrap_or_else(|| {
+                panic!(
+                    "Template property of object: {} is not a string!",
+                    identifier
+                )
+            });
+            return Some(PrefabSceneEntity::Template(name.to_owned()));
+        }
+
+        // Tile objects origin is at bottom-left corner, other shapes origin is at top-left corner.
+        let local_center = if object.point {
+            Vec2::zero()
+        } else if tile.is_some() {
+            Vec2::new(object.width * 0.5, -object.height * 0.5)
+        } else {
+            Vec2::new(object.width * 0.5, object.height * 0.5)
+        };
+        let (sin, cos) = object.rotation.to_radians().sin_cos();
+        let center = Vec2::new(
+            object.x + offset.x + local_center.x * cos - local_center.y * sin,
+            object.y + offset.y + local_center.x * sin + local_center.y * cos,
+        );
+
+        let mut variables = variables.to_owned();
+        variables.extend(object_variables(object, center));
+
+        let mut entity_data = PrefabSceneEntityData::default();
+        self.apply_rules(&properties, &variables, &identifier, &mut entity_data);
+        let type_name = if object.kind.is_empty() {
+            identifier.to_owned()
+        } else {
+            object.kind.to_owned()
+        };
+        if field("Singleton")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default()
+            && self.instance_types_used.contains(&type_name)
+        {
+            println!(
+                "Skipping more than one object instance of singleton: {}",
+                type_name
+            );
+            return None;
+        }
+        let persistent = field("Persistent")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default();
+        let no_transform = field("NoTransform")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default();
+        // Tile objects are rendered as sprites unless told otherwise.
+        let is_tile = tile_source.is_some();
+        let mesh_asset = field("MeshAsset")
+            .map(|v| v.as_str().unwrap_or(DEFAULT_SPRITE_MESH_ASSET))
+            .or(is_tile.then_some(DEFAULT_SPRITE_MESH_ASSET));
+        let sprite_image = field("SpriteImage")
+            .map(|v| v.as_str().unwrap_or(DEFAULT_SPRITE_IMAGE))
+            .or(is_tile.then_some(DEFAULT_SPRITE_IMAGE));
+        let material_asset = field("MaterialAsset")
+            .map(|v| v.as_str())
+            .or(is_tile.then_some(None))
+            .map(|v| {
+                v.unwrap_or(match sprite_image {
+                    Some("Uniforms") => DEFAULT_SPRITE_UNIFORMS_MATERIAL_ASSET,
+                    _ => DEFAULT_SPRITE_MATERIAL_ASSET,
+                })
+            });
+        let sprite_image_name = match (sprite_image, tile_source) {
+            (Some(_), Some((tileset, id))) => {
+                self.assets_used
+                    .insert(format!("atlas://{}", tileset.atlas));
+                Some(format!("{}@{}", tileset.atlas, tileset.atlas_item(id)))
+            }
+            _ => None,
+        };
+
+        let name = field("Name")
+            .map(|name| {
+                name.as_str().unwrap_or_else(|| {
+                    panic!("Could not get name string for object: {}", identifier)
+                })
+            })
+            .or_else(|| (!object.name.is_empty()).then_some(object.name.as_str()));
+        if let Some(name) = name {
+            entity_data.components.insert(
+                "Name".to_owned(),
+                Name(name.to_owned().into())
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize Name to prefab")),
+            );
+        }
+        if let Some(tag) = field("Tag") {
+            let tag = tag
+                .as_str()
+                .unwrap_or_else(|| panic!("Could not get tag string for object: {}", identifier));
+            entity_data.components.insert(
+                "Tag".to_owned(),
+                Tag(tag.to_owned().into())
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize Tag to prefab")),
+            );
+        }
+        if let Some(value) = field("Visibility") {
+            let visible = value.as_bool().unwrap_or_else(|| {
+                panic!("Could not get visibility bool for object: {}", identifier)
+            });
+            entity_data.components.insert(
+                "HaVisibility".to_owned(),
+                HaVisibility(visible)
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize HaVisibility to prefab")),
+            );
+        }
+        if let Some(value) = field("Gizmo") {
+            let visible = value
+                .as_bool()
+                .unwrap_or_else(|| panic!("Could not get gizmo bool for object: {}", identifier));
+            entity_data.components.insert(
+                "HaGizmo".to_owned(),
+                HaGizmo {
+                    visible,
+                    ..Default::default()
+                }
+                .to_prefab()
+                .unwrap_or_else(|_| panic!("Could not serialize HaGizmo to prefab")),
+            );
+        }
+        if !persistent {
+            entity_data.components.insert(
+                "NonPersistent".to_owned(),
+                NonPersistentPrefabProxy
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize NonPersistent to prefab")),
+            );
+        }
+        if let Some(pipeline) = field("Camera") {
+            let pipeline = pipeline
+                .as_str()
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Could not get camera pipeline string for object: {}",
+                        identifier
+                    )
+                })
+                .to_owned();
+            let inside = field("CameraInsideView")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default();
+            let clip_area = field("CameraClipArea")
+                .and_then(|v| v.as_str())
+                .map(|v| {
+                    let parts = v
+                        .trim()
+                        .split('|')
+                        .map(parse_viewport_value)
+                        .collect::<Vec<_>>();
+                    RenderTargetClipArea {
+                        left: parts.first().copied().unwrap_or_default(),
+                        right: parts.get(1).copied().unwrap_or_default(),
+                        top: parts.get(2).copied().unwrap_or_default(),
+                        bottom: parts.get(3).copied().unwrap_or_default(),
+                    }
+                })
+                .unwrap_or_default();
+            let camera = HaCamera::default()
+                .with_projection(HaCameraProjection::Orthographic(HaCameraOrthographic {
+                    scaling: HaCameraOrtographicScaling::FitToView(
+                        Vec2::new(object.width, object.height),
+                        inside,
+                    ),
+                    centered: true,
+                    ignore_depth_planes: false,
+                }))
+                .with_clip_area(clip_area)
+                .with_pipeline(PipelineSource::Registry(pipeline));
+            entity_data.components.insert(
+                "HaCamera".to_owned(),
+                camera
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize HaCamera to prefab")),
+            );
+            if field("DefaultCamera")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default()
+            {
+                entity_data.components.insert(
+                    "HaDefaultCamera".to_owned(),
+                    HaDefaultCamera.to_prefab().unwrap_or_else(|_| {
+                        panic!("Could not serialize HaDefaultCamera to prefab")
+                    }),
+                );
+            }
+        }
+        if !no_transform {
+            let scale = match (&sprite_image_name, tile) {
+                (Some(_), Some(tile)) => Vec3::new(
+                    if tile.flip_x {
+                        -object.width
+                    } else {
+                        object.width
+                    },
+                    if tile.flip_y {
+                        -object.height
+                    } else {
+                        object.height
+                    },
+                    1.0,
+                ),
+                (Some(_), None) => Vec3::new(object.width, object.height, 1.0),
+                _ => Vec3::one(),
+            };
+            entity_data.components.insert(
+                "HaTransform".to_owned(),
+                HaTransform::default()
+                    .with_translation(Vec3::new(center.x, center.y, 0.0))
+                    .with_rotation(Eulers {
+                        yaw: object.rotation,
+                        ..Default::default()
+                    })
+                    .with_scale(scale)
+                    .to_prefab()
+                    .unwrap_or_else(|_| panic!("Could not serialize HaTransform to prefab")),
+            );
+            if field("BoxVolume")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default()
+            {
+                let size = Vec3::new(object.width, object.height, 0.0);
+                let data = HaVolume::Box(size * 0.5);
+                entity_data.components.insert(
+                    "HaVolume".to_owned(),
+                    data.to_prefab()
+                        .unwrap_or_else(|_| panic!("Could not serialize HaVolume::Box to prefab")),
+                );
+            } else if field("SphereVolume")
+                .and_then(|v| v.as_bool())
+                .unwrap_or_default()
+                || object.ellipse
+            {
+                let radius = object.width.max(object.height) * 0.5;
+                let data = HaVolume::Sphere(radius);
+                entity_data.components.insert(
+                    "HaVolume".to_owned(),
+                    data.to_prefab().unwrap_or_else(|_| {
+                        panic!("Could not serialize HaVolume::Sphere to prefab")
+                    }),
+                );
+            }
+        }
+        if let Some(name) = mesh_asset {
+            entity_data.components.insert(
+                "HaMeshInstance".to_owned(),
+                HaMeshInstance {
+                    reference: MeshReference::Asset(name.to_owned()),
+                    override_draw_range: None,
+                }
+                .to_prefab()
+                .unwrap_or_else(|_| panic!("Could not serialize HaMeshInstance to prefab")),
+            );
+        }
+        if let Some(name) = material_asset {
+            entity_data.components.insert(
+                "HaMaterialInstance".to_owned(),
+                HaMaterialInstance {
+                    reference: MaterialReference::Asset(name.to_owned()),
+                    ..Default::default()
+                }
+                .to_prefab()
+                .unwrap_or_else(|_| panic!("Could not serialize HaMaterialInstance to prefab")),
+            );
+        }
+        if let (Some("Uniforms"), Some(name)) = (sprite_image, &sprite_image_name) {
+            let mut data = HaVirtualImageUniforms::default();
+            data.set(
+                "mainImage",
+                HaVirtualImageUniform {
+                    virtual_asset_name: name.to_owned(),
+                    filtering: self.image_filtering,
+                },
+            );
+            entity_data.components.insert(
+                "HaVirtualImageUniforms".to_owned(),
+                data.to_prefab().unwrap_or_else(|_| {
+                    panic!("Could not serialize HaVirtualImageUniforms to prefab")
+                }),
+            );
+        }
+        if let Some(value) = field("SpriteAnimation") {
+            let name = value.as_str().unwrap_or_else(|| {
+                panic!(
+                    "Could not get sprite animation string for object: {}",
+                    identifier
+                )
+            });
+            let mut data = HaSpriteAnimationInstance::default();
+            data.filtering = self.image_filtering;
+            data.playing = true;
+            data.set_animation(name);
+            entity_data.components.insert(
+                "HaSpriteAnimationInstance".to_owned(),
+                data.to_prefab().unwrap_or_else(|_| {
+                    panic!("Could not serialize HaSpriteAnimationInstance to prefab")
+                }),
+            );
+            self.assets_used.insert(format!("spriteanim://{}", name));
+        }
+        if let Some(value) = field("Components") {
+            let components = value.as_str().unwrap_or_else(|| {
+                panic!(
+                    "Could not get components map string for object: {}",
+                    identifier
+                )
+            });
+            let components = ComponentsPrefab::from_prefab_str(components).unwrap_or_else(|_| {
+                panic!(
+                    "Could not deserialize components map string for object: {}",
+                    identifier
+                )
+            });
+            for (name, data) in components.0 {
+                entity_data.components.insert(name, data);
+            }
+        }
+        self.instance_types_used.insert(type_name);
+        Some(PrefabSceneEntity::Data(entity_data))
+    }
+
+    /// Property named after rule holds `key: value` lines passed to rule macro as variables.
+    fn apply_rules(
+        &self,
+        properties: &[Property],
+        variables: &HashMap<String, String>,
+        owner: &str,
+        entity_data: &mut PrefabSceneEntityData,
+    ) {
+        for (name, content) in self.rules {
+            if content.is_empty() {
+                continue;
+            }
+            if let Some(value) = property_value(name, properties) {
+                let lines = value
+                    .as_str()
+                    .unwrap_or_else(|| panic!("{} property of: {} is not a string!", name, owner));
+                let variables = variables
+                    .iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                    .chain(
+                        lines
+                            .split(is_separator)
+                            .filter(|line| !line.is_empty())
+                            .filter_map(|line| {
+                                line.find(':').map(|index| {
+                                    (
+                                        line[..index].trim().to_owned(),
+                                        line[(index + 1)..].trim().to_owned(),
+                                    )
+                                })
+                            }),
+                    )
+                    .collect::<HashMap<_, _>>();
+                let components = process_macro(content, variables, name);
+                let components =
+                    ComponentsPrefab::from_prefab_str(&components).unwrap_or_else(|error| {
+                        panic!(
+                            "Could not deserialize {} macro components map string for: {}. Error: {:#?}",
+                            name, owner, error,
+                        )
+                    });
+                for (name, data) in components.0 {
+                    entity_data.components.insert(name, data);
+                }
+            }
+        }
+    }
+}
+
+/// Collects visible layers with their accumulated offsets, group layers get replaced by children.
+fn flatten_layers<'a>(layers: &'a [Layer], offset: Vec2, result: &mut Vec<(&'a Layer, Vec2)>) {
+    for layer in layers {
+        if !layer.visible {
+            continue;
+        }
+        let offset = offset + Vec2::new(layer.offsetx, layer.offsety);
+        if layer.kind == LayerKind::Group {
+            flatten_layers(&layer.layers, offset, result);
+        } else {
+            result.push((layer, offset));
+        }
+    }
+}
+
+fn map_variables(name: &str, map: &Map) -> HashMap<String, String> {
+    let mut result = HashMap::with_capacity(5);
+    result.insert("map_identifier".to_owned(), name.to_owned());
+    result.insert("map_width".to_owned(), map.width.to_string());
+    result.insert("map_height".to_owned(), map.height.to_string());
+    result.insert("map_tile_width".to_owned(), map.tilewidth.to_string());
+    result.insert("map_tile_height".to_owned(), map.tileheight.to_string());
+    for property in &map.properties {
+        result.insert(
+            format!("map_property_{}", property.name),
+            value_to_string(&property.value),
+        );
+    }
+    result
+}
+
+fn layer_variables(layer: &Layer, offset: Vec2) -> HashMap<String, String> {
+    let mut result = HashMap::with_capacity(3);
+    result.insert("layer_identifier".to_owned(), layer.name.to_owned());
+    result.insert("layer_offset_x".to_owned(), offset.x.to_string());
+    result.insert("layer_offset_y".to_owned(), offset.y.to_string());
+    result
+}
+
+fn object_variables(object: &Object, center: Vec2) -> HashMap<String, String> {
+    let mut result = HashMap::with_capacity(10);
+    result.insert("object_id".to_owned(), object.id.to_string());
+    result.insert("object_name".to_owned(), object.name.to_owned());
+    result.insert("object_type".to_owned(), object.kind.to_owned());
+    result.insert("object_x".to_owned(), object.x.to_string());
+    result.insert("object_y".to_owned(), object.y.to_string());
+    result.insert("object_width".to_owned(), object.width.to_string());
+    result.insert("object_height".to_owned(), object.height.to_string());
+    result.insert("object_rotation".to_owned(), object.rotation.to_string());
+    result.insert("object_center_x".to_owned(), center.x.to_string());
+    result.insert("object_center_y".to_owned(), center.y.to_string());
+    for property in &object.properties {
+        result.insert(
+            format!("object_property_{}", property.name),
+            value_to_string(&property.value),
+        );
+    }
+    result
+}
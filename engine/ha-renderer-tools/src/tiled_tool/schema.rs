@@ -0,0 +1,274 @@
+use base64::Engine;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use oxygengine_core::Scalar;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Read;
+
+pub const FLIPPED_HORIZONTALLY_FLAG: u32 = 0x80000000;
+pub const FLIPPED_VERTICALLY_FLAG: u32 = 0x40000000;
+pub const FLIPPED_DIAGONALLY_FLAG: u32 = 0x20000000;
+pub const ROTATED_HEXAGONAL_120_FLAG: u32 = 0x10000000;
+
+fn default_true() -> bool {
+    true
+}
+
+/// Global tile id with flip flags extracted from its highest bits.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct GlobalTile {
+    /// Zero means empty cell.
+    pub gid: u32,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub flip_diagonal: bool,
+}
+
+impl GlobalTile {
+    pub fn new(raw: u32) -> Self {
+        Self {
+            gid: raw
+                & !(FLIPPED_HORIZONTALLY_FLAG
+                    | FLIPPED_VERTICALLY_FLAG
+                    | FLIPPED_DIAGONALLY_FLAG
+                    | ROTATED_HEXAGONAL_120_FLAG),
+            flip_x: raw & FLIPPED_HORIZONTALLY_FLAG != 0,
+            flip_y: raw & FLIPPED_VERTICALLY_FLAG != 0,
+            flip_diagonal: raw & FLIPPED_DIAGONALLY_FLAG != 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Property {
+    pub name: String,
+    #[serde(default)]
+    pub value: Value,
+}
+
+pub fn property_value<'a>(name: &str, properties: &'a [Property]) -> Option<&'a Value> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .map(|property| &property.value)
+}
+
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.to_owned(),
+        value => value.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum LayerData {
+    Tiles(Vec<u32>),
+    Encoded(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub enum LayerKind {
+    #[serde(rename = "tilelayer")]
+    Tiles,
+    #[serde(rename = "objectgroup")]
+    Objects,
+    #[serde(rename = "imagelayer")]
+    Image,
+    #[serde(rename = "group")]
+    Group,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Layer {
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: LayerKind,
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    #[serde(default)]
+    pub offsetx: Scalar,
+    #[serde(default)]
+    pub offsety: Scalar,
+    #[serde(default)]
+    pub width: usize,
+    #[serde(default)]
+    pub height: usize,
+    #[serde(default)]
+    pub data: Option<LayerData>,
+    #[serde(default)]
+    pub encoding: Option<String>,
+    #[serde(default)]
+    pub compression: Option<String>,
+    #[serde(default)]
+    pub objects: Vec<Object>,
+    /// Children of group layer.
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+impl Layer {
+    /// Decodes tile layer cells in row-major order.
+    pub fn tiles(&self) -> Vec<GlobalTile> {
+        let data = match &self.data {
+            Some(LayerData::Tiles(data)) => data.to_owned(),
+            Some(LayerData::Encoded(data)) => self.decode(data),
+            None => panic!("Layer: {} has no tiles data", self.name),
+        };
+        data.into_iter().map(GlobalTile::new).collect()
+    }
+
+    fn decode(&self, data: &str) -> Vec<u32> {
+        match self.encoding.as_deref() {
+            Some("base64") => {}
+            Some("csv") | None => {
+                return data
+                    .split(',')
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| {
+                        value.parse().unwrap_or_else(|_| {
+                            panic!("Could not parse tile: {} of layer: {}", value, self.name)
+                        })
+                    })
+                    .collect();
+            }
+            Some(encoding) => panic!(
+                "Unsupported tile data encoding: {} of layer: {}",
+                encoding, self.name
+            ),
+        }
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(data.trim())
+            .unwrap_or_else(|_| panic!("Could not decode base64 tiles of layer: {}", self.name));
+        let bytes = match self.compression.as_deref() {
+            None | Some("") => bytes,
+            Some("zlib") => {
+                let mut result = vec![];
+                ZlibDecoder::new(bytes.as_slice())
+                    .read_to_end(&mut result)
+                    .unwrap_or_else(|_| {
+                        panic!("Could not decompress zlib tiles of layer: {}", self.name)
+                    });
+                result
+            }
+            Some("gzip") => {
+                let mut result = vec![];
+                GzDecoder::new(bytes.as_slice())
+                    .read_to_end(&mut result)
+                    .unwrap_or_else(|_| {
+                        panic!("Could not decompress gzip tiles of layer: {}", self.name)
+                    });
+                result
+            }
+            Some(compression) => panic!(
+                "Unsupported tile data compression: {} of layer: {}",
+                compression, self.name
+            ),
+        };
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Object {
+    #[serde(default)]
+    pub id: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, rename = "type", alias = "class")]
+    pub kind: String,
+    pub x: Scalar,
+    pub y: Scalar,
+    #[serde(default)]
+    pub width: Scalar,
+    #[serde(default)]
+    pub height: Scalar,
+    /// Clockwise degrees.
+    #[serde(default)]
+    pub rotation: Scalar,
+    /// Raw global tile id of tile objects, including flip flags.
+    #[serde(default)]
+    pub gid: Option<u32>,
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    #[serde(default)]
+    pub point: bool,
+    #[serde(default)]
+    pub ellipse: bool,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct TilesetTile {
+    pub id: u32,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Tileset {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub tilewidth: usize,
+    #[serde(default)]
+    pub tileheight: usize,
+    #[serde(default)]
+    pub spacing: usize,
+    #[serde(default)]
+    pub margin: usize,
+    #[serde(default)]
+    pub columns: usize,
+    #[serde(default)]
+    pub tilecount: usize,
+    /// Image path relative to tileset file.
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub tiles: Vec<TilesetTile>,
+}
+
+impl Tileset {
+    pub fn tile_property(&self, id: u32, name: &str) -> Option<&Value> {
+        self.tiles
+            .iter()
+            .find(|tile| tile.id == id)
+            .and_then(|tile| property_value(name, &tile.properties))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TilesetReference {
+    pub firstgid: u32,
+    /// Path to external tileset file, relative to map file.
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(flatten)]
+    pub tileset: Tileset,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Map {
+    #[serde(default)]
+    pub orientation: String,
+    pub width: usize,
+    pub height: usize,
+    pub tilewidth: usize,
+    pub tileheight: usize,
+    #[serde(default)]
+    pub infinite: bool,
+    #[serde(default)]
+    pub layers: Vec<Layer>,
+    #[serde(default)]
+    pub tilesets: Vec<TilesetReference>,
+    #[serde(default)]
+    pub properties: Vec<Property>,
+}
@@ -0,0 +1,172 @@
+//! Reads XML flavour of Tiled documents (TMX maps and TSX tilesets) into the same schema that
+//! JSON documents (TMJ maps and TSJ tilesets) deserialize into.
+
+use crate::schema::*;
+use serde_json::Value;
+use std::{fs::File, path::Path, str::FromStr};
+use xmltree::Element;
+
+pub fn load_map(path: &Path) -> Map {
+    let root = load_element(path);
+    Map {
+        orientation: attribute(&root, "orientation").unwrap_or_default(),
+        width: attribute(&root, "width").unwrap_or_default(),
+        height: attribute(&root, "height").unwrap_or_default(),
+        tilewidth: attribute(&root, "tilewidth").unwrap_or_default(),
+        tileheight: attribute(&root, "tileheight").unwrap_or_default(),
+        infinite: attribute::<u8>(&root, "infinite").unwrap_or_default() != 0,
+        layers: read_layers(&root),
+        tilesets: children(&root, "tileset")
+            .map(|element| TilesetReference {
+                firstgid: attribute(element, "firstgid").unwrap_or(1),
+                source: attribute(element, "source"),
+                tileset: read_tileset(element),
+            })
+            .collect(),
+        properties: read_properties(&root),
+    }
+}
+
+pub fn load_tileset(path: &Path) -> Tileset {
+    read_tileset(&load_element(path))
+}
+
+fn load_element(path: &Path) -> Element {
+    let file =
+        File::open(path).unwrap_or_else(|_| panic!("Could not open Tiled document: {:?}", path));
+    Element::parse(file).unwrap_or_else(|error| {
+        panic!(
+            "Could not parse Tiled document: {:?} | Error: {:?}",
+            path, error
+        )
+    })
+}
+
+fn attribute<T: FromStr>(element: &Element, name: &str) -> Option<T> {
+    element
+        .attributes
+        .get(name)
+        .and_then(|value| value.parse().ok())
+}
+
+fn children<'a>(element: &'a Element, name: &'a str) -> impl Iterator<Item = &'a Element> {
+    element
+        .children
+        .iter()
+        .filter_map(|node| node.as_element())
+        .filter(move |child| child.name == name)
+}
+
+fn read_properties(element: &Element) -> Vec<Property> {
+    children(element, "properties")
+        .flat_map(|properties| children(properties, "property"))
+        .map(|property| {
+            let kind = attribute::<String>(property, "type").unwrap_or_else(|| "string".to_owned());
+            // Multiline string values are stored as element text instead of attribute.
+            let text = attribute::<String>(property, "value")
+                .or_else(|| property.get_text().map(|text| text.to_string()))
+                .unwrap_or_default();
+            let value = match kind.as_str() {
+                "int" | "object" => text.parse::<i64>().map(Value::from).ok(),
+                "float" => text.parse::<f64>().map(Value::from).ok(),
+                "bool" => text.parse::<bool>().map(Value::from).ok(),
+                _ => None,
+            }
+            .unwrap_or(Value::String(text));
+            Property {
+                name: attribute(property, "name").unwrap_or_default(),
+                value,
+            }
+        })
+        .collect()
+}
+
+fn read_tileset(element: &Element) -> Tileset {
+    let image = children(element, "image").next();
+    Tileset {
+        name: attribute(element, "name").unwrap_or_default(),
+        tilewidth: attribute(element, "tilewidth").unwrap_or_default(),
+        tileheight: attribute(element, "tileheight").unwrap_or_default(),
+        spacing: attribute(element, "spacing").unwrap_or_default(),
+        margin: attribute(element, "margin").unwrap_or_default(),
+        columns: attribute(element, "columns").unwrap_or_default(),
+        tilecount: attribute(element, "tilecount").unwrap_or_default(),
+        image: image.and_then(|image| attribute(image, "source")),
+        tiles: children(element, "tile")
+            .map(|tile| TilesetTile {
+                id: attribute(tile, "id").unwrap_or_default(),
+                properties: read_properties(tile),
+            })
+            .collect(),
+    }
+}
+
+fn read_layers(element: &Element) -> Vec<Layer> {
+    element
+        .children
+        .iter()
+        .filter_map(|node| node.as_element())
+        .filter_map(|child| {
+            let kind = match child.name.as_str() {
+                "layer" => LayerKind::Tiles,
+                "objectgroup" => LayerKind::Objects,
+                "imagelayer" => LayerKind::Image,
+                "group" => LayerKind::Group,
+                _ => return None,
+            };
+            let data = children(child, "data").next();
+            Some(Layer {
+                name: attribute(child, "name").unwrap_or_default(),
+                kind,
+                visible: attribute::<u8>(child, "visible").unwrap_or(1) != 0,
+                offsetx: attribute(child, "offsetx").unwrap_or_default(),
+                offsety: attribute(child, "offsety").unwrap_or_default(),
+                width: attribute(child, "width").unwrap_or_default(),
+                height: attribute(child, "height").unwrap_or_default(),
+                data: data.map(read_layer_data),
+                encoding: data.and_then(|data| attribute(data, "encoding")),
+                compression: data.and_then(|data| attribute(data, "compression")),
+                objects: children(child, "object").map(read_object).collect(),
+                layers: read_layers(child),
+                properties: read_properties(child),
+            })
+        })
+        .collect()
+}
+
+fn read_layer_data(element: &Element) -> LayerData {
+    if element.attributes.contains_key("encoding") {
+        LayerData::Encoded(
+            element
+                .get_text()
+                .map(|text| text.to_string())
+                .unwrap_or_default(),
+        )
+    } else {
+        LayerData::Tiles(
+            children(element, "tile")
+                .map(|tile| attribute(tile, "gid").unwrap_or_default())
+                .collect(),
+        )
+    }
+}
+
+fn read_object(element: &Element) -> Object {
+    Object {
+        id: attribute(element, "id").unwrap_or_default(),
+        name: attribute(element, "name").unwrap_or_default(),
+        kind: attribute(element, "type")
+            .or_else(|| attribute(element, "class"))
+            .unwrap_or_default(),
+        x: attribute(element, "x").unwrap_or_default(),
+        y: attribute(element, "y").unwrap_or_default(),
+        width: attribute(element, "width").unwrap_or_default(),
+        height: attribute(element, "height").unwrap_or_default(),
+        rotation: attribute(element, "rotation").unwrap_or_default(),
+        gid: attribute(element, "gid"),
+        visible: attribute::<u8>(element, "visible").unwrap_or(1) != 0,
+        point: children(element, "point").next().is_some(),
+        ellipse: children(element, "ellipse").next().is_some(),
+        properties: read_properties(element),
+    }
+}
@@ -26,10 +26,19 @@ pub struct TileSetPage {
     pub tile_margin: Vec2,
 }
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 pub struct AtlasRegion {
     pub rect: Rect,
     pub layer: usize,
+    /// Region image is stored in page rotated by 90 degrees clockwise.
+    #[serde(default)]
+    pub rotated: bool,
+    /// Position of trimmed region within original image.
+    #[serde(default)]
+    pub offset: Vec2,
+    /// Original image size before trimming transparent pixels.
+    #[serde(default)]
+    pub source_size: Option<Vec2>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,7 +128,12 @@ impl AssetProtocol for AtlasAssetProtocol {
                                 page.cell_size.x - m.x - m.x,
                                 page.cell_size.y - m.y - m.y,
                             );
-                            (format!("{}x{}", col, row), AtlasRegion { rect, layer })
+                            let region = AtlasRegion {
+                                rect,
+                                layer,
+                                ..Default::default()
+                            };
+                            (format!("{}x{}", col, row), region)
                         })
                         .collect::<HashMap<_, _>>();
                     (k, mappings)
@@ -20,6 +20,11 @@ pub struct FontAssetSource {
     pub pages: Vec<FontAssetSourcePage>,
     #[serde(default)]
     pub filtering: ImageFiltering,
+    #[serde(default)]
+    pub kernings: Vec<FontAssetSourceKerning>,
+    /// Character used in place of characters missing in font.
+    #[serde(default)]
+    pub fallback_character: Option<char>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +44,13 @@ pub struct FontAssetSourceCharacter {
     pub xadvance: isize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FontAssetSourceKerning {
+    pub first: char,
+    pub second: char,
+    pub amount: isize,
+}
+
 #[derive(Debug, Clone)]
 pub struct FontAsset {
     pub line_height: usize,
@@ -48,6 +60,32 @@ pub struct FontAsset {
     /// [ (page image size, asset id) ]
     pub pages_image_assets: Vec<(Vec2, AssetId)>,
     pub filtering: ImageFiltering,
+    /// { (first, second): amount }
+    pub kernings: HashMap<(char, char), Scalar>,
+    pub fallback_character: Option<char>,
+}
+
+impl FontAsset {
+    /// Returns character data or fallback character data if font does not have it.
+    pub fn character(&self, character: char) -> Option<(char, &FontAssetCharacter)> {
+        self.characters
+            .get(&character)
+            .map(|data| (character, data))
+            .or_else(|| {
+                let character = self.fallback_character?;
+                self.characters
+                    .get(&character)
+                    .map(|data| (character, data))
+            })
+    }
+
+    /// Returns horizontal advance adjustment of `second` character placed after `first` one.
+    pub fn kerning(&self, first: char, second: char) -> Scalar {
+        self.kernings
+            .get(&(first, second))
+            .copied()
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -112,6 +150,11 @@ impl AssetProtocol for FontAssetProtocol {
             })
             .collect::<Vec<_>>();
         let sdf_resolution = source.sdf_resolution;
+        let kernings = source
+            .kernings
+            .iter()
+            .map(|kerning| ((kerning.first, kerning.second), kerning.amount as Scalar))
+            .collect();
         let characters = source
             .pages
             .into_iter()
@@ -150,6 +193,8 @@ impl AssetProtocol for FontAssetProtocol {
             characters,
             pages_image_assets,
             filtering: source.filtering,
+            kernings,
+            fallback_character: source.fallback_character,
         }))
     }
 
@@ -353,7 +353,8 @@ impl AssetProtocol for ImageAssetProtocol {
                             descriptor,
                             width: header.pixel_width as _,
                             height: header.pixel_height as _,
-                            depth: header.pixel_depth as _,
+                            // 2D textures have zero depth in KTX2 header.
+                            depth: header.pixel_depth.max(1) as _,
                             bytes,
                             content_assets: vec![asset.id()],
                         }))
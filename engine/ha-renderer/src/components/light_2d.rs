@@ -0,0 +1,190 @@
+use crate::{
+    components::material_instance::HaMaterialInstance, material::MaterialReference, math::*,
+};
+use core::{
+    prefab::{Prefab, PrefabComponent},
+    Scalar,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum HaLight2dKind {
+    Point,
+    /// (cone angle in radians)
+    Spot(Scalar),
+    Directional,
+}
+
+impl Default for HaLight2dKind {
+    fn default() -> Self {
+        Self::Point
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaLight2d {
+    #[serde(default)]
+    pub kind: HaLight2dKind,
+    #[serde(default = "HaLight2d::default_color")]
+    pub color: Rgba,
+    #[serde(default = "HaLight2d::default_intensity")]
+    pub intensity: Scalar,
+    /// Ignored by directional lights.
+    #[serde(default = "HaLight2d::default_radius")]
+    pub radius: Scalar,
+    #[serde(default = "HaLight2d::default_falloff")]
+    pub falloff: Scalar,
+    /// Distance of light above the scene plane, used for normal mapping.
+    #[serde(default = "HaLight2d::default_height")]
+    pub height: Scalar,
+    #[serde(default = "HaLight2d::default_shadows")]
+    pub shadows: bool,
+}
+
+impl Default for HaLight2d {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            color: Self::default_color(),
+            intensity: Self::default_intensity(),
+            radius: Self::default_radius(),
+            falloff: Self::default_falloff(),
+            height: Self::default_height(),
+            shadows: Self::default_shadows(),
+        }
+    }
+}
+
+impl HaLight2d {
+    fn default_color() -> Rgba {
+        Rgba::white()
+    }
+
+    fn default_intensity() -> Scalar {
+        1.0
+    }
+
+    fn default_radius() -> Scalar {
+        100.0
+    }
+
+    fn default_falloff() -> Scalar {
+        1.0
+    }
+
+    fn default_height() -> Scalar {
+        10.0
+    }
+
+    fn default_shadows() -> bool {
+        true
+    }
+
+    pub fn point(radius: Scalar) -> Self {
+        Self {
+            kind: HaLight2dKind::Point,
+            radius,
+            ..Default::default()
+        }
+    }
+
+    pub fn spot(radius: Scalar, angle: Scalar) -> Self {
+        Self {
+            kind: HaLight2dKind::Spot(angle),
+            radius,
+            ..Default::default()
+        }
+    }
+
+    pub fn directional() -> Self {
+        Self {
+            kind: HaLight2dKind::Directional,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_color(mut self, color: Rgba) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: Scalar) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_falloff(mut self, falloff: Scalar) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    pub fn with_height(mut self, height: Scalar) -> Self {
+        self.height = height;
+        self
+    }
+
+    pub fn with_shadows(mut self, shadows: bool) -> Self {
+        self.shadows = shadows;
+        self
+    }
+}
+
+impl Prefab for HaLight2d {}
+impl PrefabComponent for HaLight2d {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HaShadowCaster2dShape {
+    /// Outline of entity `HaVolume` component.
+    Volume,
+    /// Outline of entity `HaMeshInstance` mesh bounds.
+    MeshBounds,
+    /// Closed outline in entity local space.
+    Polygon(Vec<Vec2>),
+}
+
+impl Default for HaShadowCaster2dShape {
+    fn default() -> Self {
+        Self::Volume
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HaShadowCaster2d {
+    #[serde(default)]
+    pub shape: HaShadowCaster2dShape,
+}
+
+impl HaShadowCaster2d {
+    pub fn new(shape: HaShadowCaster2dShape) -> Self {
+        Self { shape }
+    }
+}
+
+impl Prefab for HaShadowCaster2d {}
+impl PrefabComponent for HaShadowCaster2d {}
+
+/// Put on camera entity to override material used to accumulate its 2D lights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaLighting2d {
+    #[serde(default = "HaLighting2d::default_material")]
+    pub material: HaMaterialInstance,
+}
+
+impl Default for HaLighting2d {
+    fn default() -> Self {
+        Self {
+            material: Self::default_material(),
+        }
+    }
+}
+
+impl HaLighting2d {
+    pub fn default_material() -> HaMaterialInstance {
+        HaMaterialInstance::new(MaterialReference::Asset(
+            "@material/graph/surface/flat/light-2d".to_owned(),
+        ))
+    }
+}
+
+impl Prefab for HaLighting2d {}
+impl PrefabComponent for HaLighting2d {}
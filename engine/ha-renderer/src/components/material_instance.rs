@@ -16,6 +16,9 @@ pub struct HaMaterialInstance {
     pub values: HashMap<String, MaterialValue>,
     #[serde(default)]
     pub override_draw_options: Option<MaterialDrawOptions>,
+    /// Extra material middlewares applied on top of ones required by mesh.
+    #[serde(default)]
+    pub middlewares: Vec<String>,
 }
 
 impl HaMaterialInstance {
@@ -24,6 +27,7 @@ impl HaMaterialInstance {
             reference,
             values: Default::default(),
             override_draw_options: None,
+            middlewares: Default::default(),
         }
     }
 
@@ -37,6 +41,11 @@ impl HaMaterialInstance {
         self
     }
 
+    pub fn with_middleware(mut self, name: impl ToString) -> Self {
+        self.middlewares.push(name.to_string());
+        self
+    }
+
     pub fn update_references(
         &mut self,
         material_mapping: &MaterialResourceMapping,
@@ -1,8 +1,10 @@
 pub mod camera;
 pub mod gizmo;
 pub mod immediate_batch;
+pub mod light_2d;
 pub mod material_instance;
 pub mod mesh_instance;
+pub mod particle_emitter;
 pub mod postprocess;
 pub mod rig_instance;
 pub mod sprite_animation_instance;
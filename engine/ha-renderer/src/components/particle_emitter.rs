@@ -0,0 +1,437 @@
+use crate::math::*;
+use animation::phase::Phase;
+use core::{
+    prefab::{Prefab, PrefabComponent},
+    Scalar,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HaParticleRange {
+    #[serde(default)]
+    pub min: Scalar,
+    #[serde(default)]
+    pub max: Scalar,
+}
+
+impl HaParticleRange {
+    pub fn new(min: Scalar, max: Scalar) -> Self {
+        Self { min, max }
+    }
+
+    pub fn value(value: Scalar) -> Self {
+        Self::new(value, value)
+    }
+
+    pub fn lerp(&self, factor: Scalar) -> Scalar {
+        self.min + (self.max - self.min) * factor
+    }
+}
+
+impl From<Scalar> for HaParticleRange {
+    fn from(value: Scalar) -> Self {
+        Self::value(value)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HaParticleBurst {
+    /// Emitter time at which burst happens.
+    #[serde(default)]
+    pub time: Scalar,
+    pub count: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HaParticleSpace {
+    /// Particles follow emitter transform.
+    Local,
+    /// Particles stay where they were spawned.
+    World,
+}
+
+impl Default for HaParticleSpace {
+    fn default() -> Self {
+        Self::World
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HaParticleFrameMode {
+    Random,
+    OverLifetime,
+}
+
+impl Default for HaParticleFrameMode {
+    fn default() -> Self {
+        Self::Random
+    }
+}
+
+/// Color multipliers sampled with particle lifetime factor.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HaParticleColorPhase {
+    #[serde(default)]
+    pub r: Phase,
+    #[serde(default)]
+    pub g: Phase,
+    #[serde(default)]
+    pub b: Phase,
+    #[serde(default)]
+    pub a: Phase,
+}
+
+impl HaParticleColorPhase {
+    pub fn sample(&self, factor: Scalar) -> Rgba {
+        Rgba::new(
+            self.r.sample(factor),
+            self.g.sample(factor),
+            self.b.sample(factor),
+            self.a.sample(factor),
+        )
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct HaParticle {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub rotation: Scalar,
+    pub angular_velocity: Scalar,
+    pub size: Scalar,
+    pub age: Scalar,
+    pub lifetime: Scalar,
+    pub frame: usize,
+    pub seed: Scalar,
+}
+
+impl HaParticle {
+    pub fn lifetime_factor(&self) -> Scalar {
+        if self.lifetime > 0.0 {
+            (self.age / self.lifetime).max(0.0).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+struct HaParticleEmitterState {
+    particles: Vec<HaParticle>,
+    time: Scalar,
+    spawn_accumulator: Scalar,
+    next_burst: usize,
+    pending_bursts: usize,
+    rng: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HaParticleEmitter {
+    #[serde(default = "HaParticleEmitter::default_playing")]
+    pub playing: bool,
+    /// Emission duration, `None` emits forever.
+    #[serde(default)]
+    pub duration: Option<Scalar>,
+    #[serde(default)]
+    pub looped: bool,
+    /// Particles spawned per second.
+    #[serde(default)]
+    pub spawn_rate: Scalar,
+    #[serde(default)]
+    pub bursts: Vec<HaParticleBurst>,
+    #[serde(default = "HaParticleEmitter::default_max_particles")]
+    pub max_particles: usize,
+    /// Half extents of box area where particles spawn.
+    #[serde(default)]
+    pub spawn_extents: Vec2,
+    #[serde(default = "HaParticleEmitter::default_lifetime")]
+    pub lifetime: HaParticleRange,
+    #[serde(default)]
+    pub speed: HaParticleRange,
+    /// Spawn direction angle range in radians, relative to emitter rotation.
+    #[serde(default = "HaParticleEmitter::default_direction")]
+    pub direction: HaParticleRange,
+    #[serde(default)]
+    pub gravity: Vec2,
+    #[serde(default)]
+    pub damping: Scalar,
+    #[serde(default)]
+    pub rotation: HaParticleRange,
+    #[serde(default)]
+    pub angular_velocity: HaParticleRange,
+    #[serde(default = "HaParticleEmitter::default_size")]
+    pub size: HaParticleRange,
+    #[serde(default)]
+    pub size_over_lifetime: Phase,
+    #[serde(default = "HaParticleEmitter::default_color")]
+    pub color: Rgba,
+    #[serde(default)]
+    pub color_over_lifetime: HaParticleColorPhase,
+    /// Virtual image names (usually atlas regions) used as particle frames.
+    #[serde(default)]
+    pub frames: Vec<String>,
+    #[serde(default)]
+    pub frame_mode: HaParticleFrameMode,
+    #[serde(default)]
+    pub space: HaParticleSpace,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(skip)]
+    state: HaParticleEmitterState,
+}
+
+impl Default for HaParticleEmitter {
+    fn default() -> Self {
+        Self {
+            playing: Self::default_playing(),
+            duration: None,
+            looped: false,
+            spawn_rate: 0.0,
+            bursts: vec![],
+            max_particles: Self::default_max_particles(),
+            spawn_extents: Vec2::zero(),
+            lifetime: Self::default_lifetime(),
+            speed: Default::default(),
+            direction: Self::default_direction(),
+            gravity: Vec2::zero(),
+            damping: 0.0,
+            rotation: Default::default(),
+            angular_velocity: Default::default(),
+            size: Self::default_size(),
+            size_over_lifetime: Default::default(),
+            color: Self::default_color(),
+            color_over_lifetime: Default::default(),
+            frames: vec![],
+            frame_mode: Default::default(),
+            space: Default::default(),
+            seed: 0,
+            state: Default::default(),
+        }
+    }
+}
+
+impl HaParticleEmitter {
+    fn default_playing() -> bool {
+        true
+    }
+
+    fn default_max_particles() -> usize {
+        1000
+    }
+
+    fn default_lifetime() -> HaParticleRange {
+        HaParticleRange::value(1.0)
+    }
+
+    fn default_direction() -> HaParticleRange {
+        HaParticleRange::new(0.0, std::f64::consts::TAU as Scalar)
+    }
+
+    fn default_size() -> HaParticleRange {
+        HaParticleRange::value(1.0)
+    }
+
+    fn default_color() -> Rgba {
+        Rgba::white()
+    }
+
+    pub fn particles(&self) -> &[HaParticle] {
+        &self.state.particles
+    }
+
+    pub fn time(&self) -> Scalar {
+        self.state.time
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.playing
+            && self
+                .duration
+                .map(|duration| self.looped || self.state.time < duration)
+                .unwrap_or(true)
+    }
+
+    /// True when emission ended and all particles died.
+    pub fn is_finished(&self) -> bool {
+        !self.is_emitting() && self.state.particles.is_empty()
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.playing = false;
+    }
+
+    /// Resets emitter time and kills all particles.
+    pub fn restart(&mut self) {
+        self.state = Default::default();
+        self.playing = true;
+    }
+
+    /// Spawns given number of particles on next simulation step.
+    pub fn burst(&mut self, count: usize) {
+        self.state.pending_bursts += count;
+    }
+
+    /// Advances simulation, `matrix` is emitter world matrix.
+    pub fn simulate(&mut self, delta_time: Scalar, matrix: &Mat4) {
+        if self.state.rng == 0 {
+            self.state.rng = self.seed.max(1);
+        }
+        let damping = (1.0 - self.damping * delta_time).max(0.0);
+        self.state.particles.retain_mut(|particle| {
+            particle.age += delta_time;
+            if particle.age >= particle.lifetime {
+                return false;
+            }
+            particle.velocity += self.gravity * delta_time;
+            particle.velocity *= damping;
+            particle.position += particle.velocity * delta_time;
+            particle.rotation += particle.angular_velocity * delta_time;
+            true
+        });
+
+        let mut count = std::mem::take(&mut self.state.pending_bursts);
+        if self.is_emitting() {
+            self.state.time += delta_time;
+            self.state.spawn_accumulator += self.spawn_rate * delta_time;
+            let spawned = self.state.spawn_accumulator.floor();
+            self.state.spawn_accumulator -= spawned;
+            count += spawned as usize;
+            while let Some(burst) = self.bursts.get(self.state.next_burst) {
+                if burst.time > self.state.time {
+                    break;
+                }
+                count += burst.count;
+                self.state.next_burst += 1;
+            }
+            if let Some(duration) = self.duration {
+                if self.looped && duration > 0.0 && self.state.time >= duration {
+                    self.state.time -= duration;
+                    self.state.next_burst = 0;
+                }
+            }
+        }
+
+        let count = count.min(
+            self.max_particles
+                .saturating_sub(self.state.particles.len()),
+        );
+        for _ in 0..count {
+            let particle = self.spawn_particle(matrix);
+            self.state.particles.push(particle);
+        }
+    }
+
+    fn spawn_particle(&mut self, matrix: &Mat4) -> HaParticle {
+        let offset = Vec2::new(
+            self.spawn_extents.x * (self.random() * 2.0 - 1.0),
+            self.spawn_extents.y * (self.random() * 2.0 - 1.0),
+        );
+        let angle = self.direction.lerp(self.random());
+        let speed = self.speed.lerp(self.random());
+        let mut position = offset;
+        let mut velocity = Vec2::new(angle.cos(), angle.sin()) * speed;
+        if self.space == HaParticleSpace::World {
+            position = Vec2::from(matrix.mul_point(Vec3::from(position)));
+            velocity = Vec2::from(matrix.mul_direction(Vec3::from(velocity)));
+        }
+        let frame = match self.frame_mode {
+            HaParticleFrameMode::Random if !self.frames.is_empty() => {
+                ((self.random() * self.frames.len() as Scalar) as usize).min(self.frames.len() - 1)
+            }
+            _ => 0,
+        };
+        HaParticle {
+            position,
+            velocity,
+            rotation: self.rotation.lerp(self.random()),
+            angular_velocity: self.angular_velocity.lerp(self.random()),
+            size: self.size.lerp(self.random()),
+            age: 0.0,
+            lifetime: self.lifetime.lerp(self.random()),
+            frame,
+            seed: self.random(),
+        }
+    }
+
+    /// Returns particle frame index into `frames`.
+    pub fn particle_frame(&self, particle: &HaParticle) -> usize {
+        match self.frame_mode {
+            HaParticleFrameMode::Random => particle.frame,
+            HaParticleFrameMode::OverLifetime => {
+                let count = self.frames.len();
+                ((particle.lifetime_factor() * count as Scalar) as usize).min(count.max(1) - 1)
+            }
+        }
+    }
+
+    pub fn particle_size(&self, particle: &HaParticle) -> Scalar {
+        particle.size * self.size_over_lifetime.sample(particle.lifetime_factor())
+    }
+
+    pub fn particle_color(&self, particle: &HaParticle) -> Rgba {
+        self.color * self.color_over_lifetime.sample(particle.lifetime_factor())
+    }
+
+    fn random(&mut self) -> Scalar {
+        // xorshift64*
+        let mut x = self.state.rng;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state.rng = x;
+        (x.wrapping_mul(0x2545F4914F6CDD1D) >> 40) as Scalar / (1u64 << 24) as Scalar
+    }
+}
+
+impl Prefab for HaParticleEmitter {}
+impl PrefabComponent for HaParticleEmitter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_particle_emitter() {
+        let mut emitter = HaParticleEmitter {
+            spawn_rate: 10.0,
+            lifetime: HaParticleRange::value(0.5),
+            speed: HaParticleRange::value(2.0),
+            direction: HaParticleRange::value(0.0),
+            bursts: vec![HaParticleBurst {
+                time: 0.0,
+                count: 5,
+            }],
+            duration: Some(1.0),
+            seed: 42,
+            ..Default::default()
+        };
+        let matrix = Mat4::identity();
+        emitter.simulate(0.1, &matrix);
+        assert_eq!(emitter.particles().len(), 6);
+        for _ in 0..3 {
+            emitter.simulate(0.1, &matrix);
+        }
+        assert_eq!(emitter.particles().len(), 9);
+        assert!(emitter
+            .particles()
+            .iter()
+            .all(|particle| particle.position.x >= 0.0 && particle.position.y.abs() < 1.0e-4));
+        for _ in 0..20 {
+            emitter.simulate(0.1, &matrix);
+        }
+        assert!(!emitter.is_emitting());
+        assert!(emitter.is_finished());
+
+        emitter.restart();
+        emitter.spawn_rate = 0.0;
+        emitter.bursts.clear();
+        emitter.max_particles = 3;
+        emitter.burst(10);
+        emitter.simulate(0.1, &matrix);
+        assert_eq!(emitter.particles().len(), 3);
+    }
+}
@@ -6,6 +6,8 @@ use core::{
 use pest::{iterators::Pair, Parser};
 use serde::{Deserialize, Serialize};
 use std::str::Chars;
+use unicode_bidi::{BidiInfo, Level};
+use unicode_segmentation::{Graphemes, UnicodeSegmentation};
 
 #[allow(clippy::upper_case_acronyms)]
 mod parser {
@@ -22,6 +24,8 @@ pub enum HaTextElementIter<'a> {
     Text {
         instance: &'a HaTextInstance,
         index: usize,
+        graphemes: Graphemes<'a>,
+        /// Remaining characters of current grapheme cluster.
         characters: Chars<'a>,
     },
     Done,
@@ -29,17 +33,19 @@ pub enum HaTextElementIter<'a> {
 
 impl<'a> HaTextElementIter<'a> {
     pub fn new(instance: &'a HaTextInstance) -> Self {
-        if let Some(fragment) = instance.content.0.get(0) {
-            match fragment {
-                HaTextFragment::NewLine => Self::NewLine { instance, index: 0 },
-                HaTextFragment::Text { text, .. } => Self::Text {
-                    instance,
-                    index: 0,
-                    characters: text.chars(),
-                },
-            }
-        } else {
-            Self::Done
+        Self::fragment(instance, 0)
+    }
+
+    fn fragment(instance: &'a HaTextInstance, index: usize) -> Self {
+        match instance.content.0.get(index) {
+            Some(HaTextFragment::NewLine) => Self::NewLine { instance, index },
+            Some(HaTextFragment::Text { text, .. }) => Self::Text {
+                instance,
+                index,
+                graphemes: text.graphemes(true),
+                characters: "".chars(),
+            },
+            None => Self::Done,
         }
     }
 }
@@ -50,76 +56,52 @@ impl<'a> Iterator for HaTextElementIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Self::NewLine { instance, index } => {
-                if let Some(fragment) = instance.content.0.get(*index + 1) {
-                    match fragment {
-                        HaTextFragment::NewLine => {
-                            *self = Self::NewLine {
-                                instance,
-                                index: *index + 1,
-                            };
-                        }
-                        HaTextFragment::Text { text, .. } => {
-                            *self = Self::Text {
-                                instance,
-                                index: *index + 1,
-                                characters: text.chars(),
-                            };
-                        }
-                    }
-                } else {
-                    *self = Self::Done;
-                }
+                *self = Self::fragment(*instance, *index + 1);
                 Some(HaTextElement::NewLine)
             }
             Self::Text {
                 instance,
                 index,
+                graphemes,
                 characters,
             } => {
-                if let Some(character) = characters.next() {
-                    if let Some(HaTextFragment::Text {
-                        params:
-                            HaTextFragmentParams {
-                                size,
-                                color,
-                                outline,
-                                thickness,
-                                cursive,
-                                wrapping,
-                            },
-                        ..
-                    }) = instance.content.0.get(*index)
-                    {
-                        Some(HaTextElement::Glyph {
-                            character,
-                            size: size.unwrap_or_else(|| instance.size),
-                            color: color.unwrap_or_else(|| instance.color),
-                            outline: outline.unwrap_or_else(|| instance.outline),
-                            thickness: thickness.unwrap_or_else(|| instance.thickness),
-                            cursive: cursive.unwrap_or_else(|| instance.cursive),
-                            wrapping: wrapping.as_ref().unwrap_or(&instance.wrapping),
-                        })
-                    } else {
-                        *self = Self::Done;
-                        None
+                let (character, combining) = if let Some(character) = characters.next() {
+                    (character, true)
+                } else if let Some(grapheme) = graphemes.next() {
+                    *characters = grapheme.chars();
+                    match characters.next() {
+                        Some(character) => (character, false),
+                        None => return self.next(),
                     }
-                } else if let Some(fragment) = instance.content.0.get(*index + 1) {
-                    match fragment {
-                        HaTextFragment::NewLine => {
-                            *self = Self::NewLine {
-                                instance,
-                                index: *index + 1,
-                            };
-                        }
-                        HaTextFragment::Text { text, .. } => {
-                            *self = Self::Text {
-                                instance,
-                                index: *index + 1,
-                                characters: text.chars(),
-                            };
-                        }
-                    }
-                    self.next()
+                } else {
+                    *self = Self::fragment(*instance, *index + 1);
+                    return self.next();
+                };
+                if let Some(HaTextFragment::Text {
+                    params:
+                        HaTextFragmentParams {
+                            size,
+                            color,
+                            outline,
+                            thickness,
+                            cursive,
+                            wrapping,
+                            direction,
+                        },
+                    ..
+                }) = instance.content.0.get(*index)
+                {
+                    Some(HaTextElement::Glyph {
+                        character,
+                        combining,
+                        size: size.unwrap_or_else(|| instance.size),
+                        color: color.unwrap_or_else(|| instance.color),
+                        outline: outline.unwrap_or_else(|| instance.outline),
+                        thickness: thickness.unwrap_or_else(|| instance.thickness),
+                        cursive: cursive.unwrap_or_else(|| instance.cursive),
+                        wrapping: wrapping.as_ref().unwrap_or(&instance.wrapping),
+                        direction: direction.unwrap_or(instance.direction),
+                    })
                 } else {
                     *self = Self::Done;
                     None
@@ -136,12 +118,15 @@ pub enum HaTextElement<'a> {
     NewLine,
     Glyph {
         character: char,
+        /// True for characters following first one in grapheme cluster (combining marks).
+        combining: bool,
         size: Scalar,
         color: Rgba,
         outline: Rgba,
         thickness: Scalar,
         cursive: Scalar,
         wrapping: &'a HaTextWrapping,
+        direction: HaTextDirection,
     },
 }
 
@@ -159,6 +144,8 @@ pub struct HaTextFragmentParams {
     cursive: Option<Scalar>,
     #[serde(default)]
     wrapping: Option<HaTextWrapping>,
+    #[serde(default)]
+    direction: Option<HaTextDirection>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -189,14 +176,21 @@ impl HaTextFragment {
     pub fn glyphs_count(&self) -> usize {
         match self {
             Self::NewLine => 0,
-            Self::Text { text, .. } => text.len(),
+            Self::Text { text, .. } => text.chars().count(),
+        }
+    }
+
+    pub fn graphemes_count(&self) -> usize {
+        match self {
+            Self::NewLine => 0,
+            Self::Text { text, .. } => text.graphemes(true).count(),
         }
     }
 
     pub fn elements_count(&self) -> usize {
         match self {
             Self::NewLine => 1,
-            Self::Text { text, .. } => text.len(),
+            Self::Text { text, .. } => text.chars().count(),
         }
     }
 }
@@ -274,6 +268,23 @@ impl HaTextContent {
                                             _ => {}
                                         }
                                     }
+                                    parser::Rule::direction => {
+                                        let pair = pair.into_inner().next().unwrap();
+                                        match pair.as_rule() {
+                                            parser::Rule::direction_auto => {
+                                                result.direction = Some(HaTextDirection::Auto);
+                                            }
+                                            parser::Rule::direction_ltr => {
+                                                result.direction =
+                                                    Some(HaTextDirection::LeftToRight);
+                                            }
+                                            parser::Rule::direction_rtl => {
+                                                result.direction =
+                                                    Some(HaTextDirection::RightToLeft);
+                                            }
+                                            _ => {}
+                                        }
+                                    }
                                     _ => {}
                                 }
                             }
@@ -349,6 +360,48 @@ impl HaTextWrapping {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HaTextDirection {
+    /// Direction is detected from first strong directional character.
+    Auto,
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Default for HaTextDirection {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+impl HaTextDirection {
+    pub fn level(self) -> Option<Level> {
+        match self {
+            Self::Auto => None,
+            Self::LeftToRight => Some(Level::ltr()),
+            Self::RightToLeft => Some(Level::rtl()),
+        }
+    }
+
+    /// Unicode isolate character that opens nested run of this direction.
+    fn isolate(self) -> char {
+        match self {
+            Self::Auto => '\u{2068}',
+            Self::LeftToRight => '\u{2066}',
+            Self::RightToLeft => '\u{2067}',
+        }
+    }
+}
+
+/// Unicode bidi embedding levels resolved for text - odd levels are right-to-left.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HaTextBidiLevels {
+    /// Level of every glyph element, in order of `HaTextInstance::iter`.
+    pub glyphs: Vec<u8>,
+    /// Base level of every line separated by new line element.
+    pub lines: Vec<u8>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HaTextInstance {
     font: String,
@@ -377,6 +430,8 @@ pub struct HaTextInstance {
     wrapping: HaTextWrapping,
     #[serde(default)]
     lines_extra_space: Scalar,
+    #[serde(default)]
+    direction: HaTextDirection,
     #[serde(skip)]
     pub(crate) dirty: bool,
 }
@@ -398,6 +453,7 @@ impl Default for HaTextInstance {
             bounds_height: None,
             wrapping: Default::default(),
             lines_extra_space: 0.0,
+            direction: Default::default(),
             dirty: true,
         }
     }
@@ -425,6 +481,13 @@ impl HaTextInstance {
         self.content.0.iter().fold(0, |a, v| a + v.glyphs_count())
     }
 
+    pub fn graphemes_count(&self) -> usize {
+        self.content
+            .0
+            .iter()
+            .fold(0, |a, v| a + v.graphemes_count())
+    }
+
     pub fn elements_count(&self) -> usize {
         self.content.0.iter().fold(0, |a, v| a + v.elements_count())
     }
@@ -433,6 +496,69 @@ impl HaTextInstance {
         HaTextElementIter::new(self)
     }
 
+    /// Runs Unicode bidi algorithm over every line, fragments with direction different than
+    /// instance direction are treated as isolated runs.
+    pub fn bidi_levels(&self) -> HaTextBidiLevels {
+        let mut result = HaTextBidiLevels {
+            glyphs: Vec::with_capacity(self.glyphs_count()),
+            lines: Vec::with_capacity(self.lines_count()),
+        };
+        let mut line = String::new();
+        let mut offsets = vec![];
+        let mut current = self.direction;
+        for element in self.iter() {
+            match element {
+                HaTextElement::Invalid => {}
+                HaTextElement::NewLine => {
+                    self.resolve_bidi_line(&mut line, &mut offsets, &mut result);
+                    current = self.direction;
+                }
+                HaTextElement::Glyph {
+                    character,
+                    direction,
+                    ..
+                } => {
+                    if direction != current {
+                        if current != self.direction {
+                            line.push('\u{2069}');
+                        }
+                        if direction != self.direction {
+                            line.push(direction.isolate());
+                        }
+                        current = direction;
+                    }
+                    offsets.push(line.len());
+                    line.push(character);
+                }
+            }
+        }
+        self.resolve_bidi_line(&mut line, &mut offsets, &mut result);
+        result
+    }
+
+    fn resolve_bidi_line(
+        &self,
+        line: &mut String,
+        offsets: &mut Vec<usize>,
+        result: &mut HaTextBidiLevels,
+    ) {
+        {
+            let info = BidiInfo::new(line, self.direction.level());
+            let level = info
+                .paragraphs
+                .first()
+                .map(|paragraph| paragraph.level)
+                .or_else(|| self.direction.level())
+                .unwrap_or_else(Level::ltr);
+            result.lines.push(level.number());
+            result
+                .glyphs
+                .extend(offsets.iter().map(|offset| info.levels[*offset].number()));
+        }
+        line.clear();
+        offsets.clear();
+    }
+
     pub fn font(&self) -> &str {
         &self.font
     }
@@ -554,6 +680,15 @@ impl HaTextInstance {
         self.dirty = true;
     }
 
+    pub fn direction(&self) -> HaTextDirection {
+        self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: HaTextDirection) {
+        self.direction = direction;
+        self.dirty = true;
+    }
+
     pub fn mark_dirty(&mut self) {
         self.dirty = true;
     }
@@ -576,6 +711,7 @@ pub struct HaRichTextBuilder {
     thickness: Vec<Scalar>,
     cursive: Vec<Scalar>,
     wrapping: Vec<HaTextWrapping>,
+    direction: Vec<HaTextDirection>,
     fragments: Vec<HaTextFragment>,
 }
 
@@ -602,6 +738,7 @@ impl HaRichTextBuilder {
             thickness: Vec::with_capacity(depth),
             cursive: Vec::with_capacity(depth),
             wrapping: Vec::with_capacity(depth),
+            direction: Vec::with_capacity(depth),
             fragments: Vec::with_capacity(count),
         }
     }
@@ -616,6 +753,7 @@ impl HaRichTextBuilder {
     impl_rich_text_group!(thickness: Scalar);
     impl_rich_text_group!(cursive: Scalar);
     impl_rich_text_group!(wrapping: HaTextWrapping);
+    impl_rich_text_group!(direction: HaTextDirection);
 
     pub fn new_line(mut self) -> Self {
         self.fragments.push(HaTextFragment::NewLine);
@@ -639,6 +777,7 @@ impl HaRichTextBuilder {
                     thickness: self.thickness.last().copied(),
                     cursive: self.cursive.last().copied(),
                     wrapping: self.wrapping.last().cloned(),
+                    direction: self.direction.last().copied(),
                 },
             });
         }
@@ -678,6 +817,18 @@ macro_rules! rich_text {
     ( @item($builder:expr) => [wrapping = $value:literal $( $item:tt )* ] ) => {
         $crate::rich_text! { @item($builder, wrapping, $crate::components::text_instance::HaTextWrapping::Set($value.to_string())) => ( $($item)* ) }
     };
+    ( @item($builder:expr) => [direction ( $value:expr ) $( $item:tt )* ] ) => {
+        $crate::rich_text! { @item($builder, direction, $value) => ( $($item)* ) }
+    };
+    ( @item($builder:expr) => [direction = auto $( $item:tt )* ] ) => {
+        $crate::rich_text! { @item($builder, direction, $crate::components::text_instance::HaTextDirection::Auto) => ( $($item)* ) }
+    };
+    ( @item($builder:expr) => [direction = ltr $( $item:tt )* ] ) => {
+        $crate::rich_text! { @item($builder, direction, $crate::components::text_instance::HaTextDirection::LeftToRight) => ( $($item)* ) }
+    };
+    ( @item($builder:expr) => [direction = rtl $( $item:tt )* ] ) => {
+        $crate::rich_text! { @item($builder, direction, $crate::components::text_instance::HaTextDirection::RightToLeft) => ( $($item)* ) }
+    };
     ( @item($builder:expr, $param:ident, $value:expr ) => ( $( $item:tt )* ) ) => {
         $builder.$param($value.into(), |mut builder| {
             $(
@@ -735,4 +886,78 @@ mod tests {
         assert_eq!(a, b);
         assert_eq!(a, c);
     }
+
+    #[test]
+    fn test_text_unicode() {
+        let text = HaTextFragment::text("zażółć e\u{0328}");
+        assert_eq!(text.glyphs_count(), 9);
+        assert_eq!(text.graphemes_count(), 8);
+
+        let mut instance = HaTextInstance::default();
+        instance.set_content(HaTextContent(vec![
+            text,
+            HaTextFragment::NewLine,
+            HaTextFragment::text("日本"),
+        ]));
+        assert_eq!(instance.glyphs_count(), 11);
+        let elements = instance
+            .iter()
+            .map(|element| match element {
+                HaTextElement::Glyph {
+                    character,
+                    combining,
+                    ..
+                } => Some((character, combining)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(elements.len(), 12);
+        assert_eq!(elements[2], Some(('ż', false)));
+        assert_eq!(elements[7], Some(('e', false)));
+        assert_eq!(elements[8], Some(('\u{0328}', true)));
+        assert_eq!(elements[9], None);
+        assert_eq!(elements[10], Some(('日', false)));
+    }
+
+    #[test]
+    fn test_text_bidi() {
+        let a = HaRichTextBuilder::default()
+            .text("שלום ")
+            .direction(HaTextDirection::LeftToRight, |b| b.text("(ok)"))
+            .build();
+        let b = rich_text! {
+            "שלום "
+            [direction=ltr "(ok)"]
+        };
+        let c = HaTextContent::parse("שלום [d=ltr](ok)[/]").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a, c);
+
+        let mut instance = HaTextInstance::default();
+        instance.set_content("abc אבג\nאבג abc");
+        assert_eq!(
+            instance.bidi_levels(),
+            HaTextBidiLevels {
+                glyphs: vec![0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2],
+                lines: vec![0, 1],
+            }
+        );
+        instance.set_direction(HaTextDirection::RightToLeft);
+        instance.set_content("abc אבג");
+        assert_eq!(
+            instance.bidi_levels(),
+            HaTextBidiLevels {
+                glyphs: vec![2, 2, 2, 1, 1, 1, 1],
+                lines: vec![1],
+            }
+        );
+        instance.set_content(a);
+        assert_eq!(
+            instance.bidi_levels(),
+            HaTextBidiLevels {
+                glyphs: vec![1, 1, 1, 1, 1, 2, 2, 2, 2],
+                lines: vec![1],
+            }
+        );
+    }
 }
@@ -2,11 +2,37 @@ use crate::{components::HaChangeFrequency, image::ImageFiltering, math::*};
 use core::prefab::{Prefab, PrefabComponent};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct HaTileMapTile {
     pub col: usize,
     pub row: usize,
     pub atlas_item: String,
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default)]
+    pub flip_y: bool,
+    /// Swaps tile image axes, applied to image before horizontal and vertical flips (Tiled
+    /// convention), so diagonal with horizontal flip rotates image by 90 degrees clockwise.
+    #[serde(default)]
+    pub flip_diagonal: bool,
+}
+
+impl HaTileMapTile {
+    /// Maps tile corner (each axis in 0-1 range) into its image corner, with flips applied.
+    /// Corners are mapped with inverse of image transform, so axis flips go before axes swap.
+    pub fn image_corner(&self, corner: Vec2) -> Vec2 {
+        let mut result = corner;
+        if self.flip_x {
+            result.x = 1.0 - result.x;
+        }
+        if self.flip_y {
+            result.y = 1.0 - result.y;
+        }
+        if self.flip_diagonal {
+            result = vec2(result.y, result.x);
+        }
+        result
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,3 +145,68 @@ impl Prefab for HaTileMapInstance {
     }
 }
 impl PrefabComponent for HaTileMapInstance {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image_corners(flip_x: bool, flip_y: bool, flip_diagonal: bool) -> [Vec2; 4] {
+        let tile = HaTileMapTile {
+            flip_x,
+            flip_y,
+            flip_diagonal,
+            ..Default::default()
+        };
+        [
+            vec2(0.0, 0.0),
+            vec2(1.0, 0.0),
+            vec2(1.0, 1.0),
+            vec2(0.0, 1.0),
+        ]
+        .map(|corner| tile.image_corner(corner))
+    }
+
+    #[test]
+    fn test_tile_image_corner() {
+        // (top-left, top-right, bottom-right, bottom-left) tile corners.
+        assert_eq!(
+            image_corners(false, false, false),
+            [
+                vec2(0.0, 0.0),
+                vec2(1.0, 0.0),
+                vec2(1.0, 1.0),
+                vec2(0.0, 1.0)
+            ]
+        );
+        // 90 degrees clockwise.
+        assert_eq!(
+            image_corners(true, false, true),
+            [
+                vec2(0.0, 1.0),
+                vec2(0.0, 0.0),
+                vec2(1.0, 0.0),
+                vec2(1.0, 1.0)
+            ]
+        );
+        // 180 degrees.
+        assert_eq!(
+            image_corners(true, true, false),
+            [
+                vec2(1.0, 1.0),
+                vec2(0.0, 1.0),
+                vec2(0.0, 0.0),
+                vec2(1.0, 0.0)
+            ]
+        );
+        // 270 degrees clockwise.
+        assert_eq!(
+            image_corners(false, true, true),
+            [
+                vec2(1.0, 0.0),
+                vec2(1.0, 1.0),
+                vec2(0.0, 1.0),
+                vec2(0.0, 0.0)
+            ]
+        );
+    }
+}
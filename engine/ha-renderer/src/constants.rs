@@ -6,4 +6,14 @@ pub mod material_uniforms {
     pub const MAIN_IMAGE_NAME: &str = "mainImage";
     pub const MAIN_IMAGE_OFFSET_NAME: &str = "mainImageOffset";
     pub const MAIN_IMAGE_SIZE_NAME: &str = "mainImageSize";
+    pub const MAIN_IMAGE_FRAME_NAME: &str = "mainImageFrame";
+    pub const MAIN_IMAGE_ROTATED_NAME: &str = "mainImageRotated";
+    pub const NORMALS_IMAGE_NAME: &str = "normalsImage";
+    pub const LIGHT_POSITION_NAME: &str = "lightPosition";
+    pub const LIGHT_DIRECTION_NAME: &str = "lightDirection";
+    pub const LIGHT_COLOR_NAME: &str = "lightColor";
+    pub const LIGHT_RADIUS_NAME: &str = "lightRadius";
+    pub const LIGHT_ANGLE_NAME: &str = "lightAngle";
+    pub const LIGHT_FALLOFF_NAME: &str = "lightFalloff";
+    pub const LIGHT_DIRECTIONAL_NAME: &str = "lightDirectional";
 }
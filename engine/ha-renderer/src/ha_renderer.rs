@@ -50,6 +50,8 @@ impl Default for PipelineSource {
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct RenderStats {
     pub draw_calls: usize,
+    /// Number of instances drawn by instanced draw calls.
+    pub instances: usize,
     pub mesh_changes: usize,
     pub material_changes: usize,
     pub uniform_changes: usize,
@@ -226,6 +228,7 @@ pub struct HaRenderer {
     pub virtual_meshes: Resources<VirtualMesh>,
     pub(crate) materials: Resources<Material>,
     cached_signatures: HashSet<MaterialSignature>,
+    extra_mesh_signatures: HashSet<(MaterialMeshSignature, StringSequence)>,
     dirty_signatures: bool,
     added_materials: HashSet<MaterialId>,
     pub(crate) stats_cache: RenderStats,
@@ -245,6 +248,7 @@ impl std::fmt::Debug for HaRenderer {
             .field("virtual_images", &self.virtual_images)
             .field("materials", &self.materials)
             .field("cached_signatures", &self.cached_signatures)
+            .field("extra_mesh_signatures", &self.extra_mesh_signatures)
             .field("dirty_signatures", &self.dirty_signatures)
             .field("added_materials", &self.added_materials)
             .field("stats_cache", &self.stats_cache)
@@ -273,6 +277,7 @@ impl HaRenderer {
             virtual_meshes: Default::default(),
             materials: Default::default(),
             cached_signatures: Default::default(),
+            extra_mesh_signatures: Default::default(),
             dirty_signatures: true,
             added_materials: Default::default(),
             stats_cache: Default::default(),
@@ -503,6 +508,27 @@ impl HaRenderer {
         Ok(())
     }
 
+    pub fn has_material_signature(&self, signature: &MaterialSignature) -> bool {
+        self.cached_signatures.contains(signature)
+    }
+
+    /// Makes materials compile version for signature that does not come from any mesh layout
+    /// alone, e.g. one extended with instance attributes or material instance middlewares.
+    /// Returns true if signature was not known yet and its material versions will be compiled.
+    pub fn register_material_signature(&mut self, signature: &MaterialSignature) -> bool {
+        if self.cached_signatures.contains(signature) {
+            return false;
+        }
+        let added = self.extra_mesh_signatures.insert((
+            signature.mesh().to_owned(),
+            signature.middlewares().to_owned(),
+        ));
+        if added {
+            self.dirty_signatures = true;
+        }
+        added
+    }
+
     pub fn pipelines(&self) -> impl Iterator<Item = PipelineId> + '_ {
         self.pipelines.keys().copied()
     }
@@ -565,6 +591,19 @@ impl HaRenderer {
         Ok(())
     }
 
+    /// Swaps data of existing mesh, keeping its id so references to it stay valid.
+    pub fn replace_mesh(&mut self, id: MeshId, mut data: Mesh) -> Result<(), MeshError> {
+        if let Some(current) = self.meshes.get_mut(id) {
+            if let Some(context) = self.platform_interface.context() {
+                current.context_release(context)?;
+                data.context_initialize(context)?;
+            }
+            *current = data;
+            self.dirty_signatures = true;
+        }
+        Ok(())
+    }
+
     pub fn mesh(&self, id: MeshId) -> Option<&Mesh> {
         self.meshes.get(id)
     }
@@ -623,10 +662,32 @@ impl HaRenderer {
         Ok(())
     }
 
+    /// Swaps data of existing material, keeping its id so references to it stay valid.
+    pub fn replace_material(
+        &mut self,
+        id: MaterialId,
+        mut data: Material,
+    ) -> Result<(), MaterialError> {
+        if let Some(current) = self.materials.get_mut(id) {
+            if let Some(context) = self.platform_interface.context() {
+                current.context_release(context)?;
+                data.context_initialize(context)?;
+            }
+            *current = data;
+            self.added_materials.insert(id);
+        }
+        Ok(())
+    }
+
     pub fn material(&self, id: MaterialId) -> Option<&Material> {
         self.materials.get(id)
     }
 
+    /// Forces every material to bake its versions again, e.g. after material library changes.
+    pub fn rebake_materials(&mut self) {
+        self.added_materials.extend(self.materials.ids());
+    }
+
     #[inline]
     pub fn error_reporter(&self) -> &dyn HaRendererErrorReporter {
         &*self.error_reporter
@@ -762,7 +823,8 @@ impl HaRenderer {
                         StringSequence::new(mesh.layout().middlewares()),
                     )
                 })
-                .collect::<HashMap<_, _>>();
+                .chain(self.extra_mesh_signatures.iter().cloned())
+                .collect::<HashSet<_>>();
             let count = self.cached_signatures.len();
             let old = std::mem::replace(&mut self.cached_signatures, HashSet::with_capacity(count));
             if !mesh_signatures_middlewares.is_empty() {
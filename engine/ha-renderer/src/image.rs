@@ -1,6 +1,9 @@
 use crate::{
-    ha_renderer::RenderStageResources, math::Rect, render_target::RenderTargetId,
-    resources::resource_mapping::ResourceMapping, HasContextResources, ResourceReference,
+    ha_renderer::RenderStageResources,
+    math::{vec2, Rect, Vec2},
+    render_target::RenderTargetId,
+    resources::resource_mapping::ResourceMapping,
+    HasContextResources, ResourceReference,
 };
 use core::id::ID;
 use glow::*;
@@ -102,7 +105,7 @@ impl ImageFormat {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ImageFiltering {
     Nearest,
     Linear,
@@ -423,10 +426,42 @@ impl VirtualImageSource {
     }
 }
 
+/// Placement of virtual image region content within its original image, for regions packed with
+/// trimmed transparent borders or rotated by 90 degrees clockwise.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VirtualImageFrame {
+    /// Area of region content within original image, in normalized original image space.
+    pub rect: Rect,
+    pub rotated: bool,
+}
+
+impl Default for VirtualImageFrame {
+    fn default() -> Self {
+        Self {
+            rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            rotated: false,
+        }
+    }
+}
+
+impl VirtualImageFrame {
+    /// Maps normalized original image coordinate into normalized region coordinate.
+    pub fn region_coord(&self, coord: Vec2) -> Vec2 {
+        let x = (coord.x - self.rect.x) / self.rect.w;
+        let y = (coord.y - self.rect.y) / self.rect.h;
+        if self.rotated {
+            vec2(1.0 - y, x)
+        } else {
+            vec2(x, y)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct VirtualImage {
     source: VirtualImageSource,
     uvs: HashMap<ImageId, (Rect, usize)>,
+    frames: HashMap<ImageId, VirtualImageFrame>,
     map: HashMap<String, ImageId>,
     table: HashMap<ImageId, String>,
 }
@@ -436,6 +471,7 @@ impl VirtualImage {
         Self {
             source,
             uvs: Default::default(),
+            frames: Default::default(),
             map: Default::default(),
             table: Default::default(),
         }
@@ -463,16 +499,32 @@ impl VirtualImage {
         id
     }
 
+    pub fn register_named_image_uvs_with_frame(
+        &mut self,
+        name: impl ToString,
+        uvs: Rect,
+        page: usize,
+        frame: VirtualImageFrame,
+    ) -> ImageId {
+        let id = self.register_named_image_uvs(name, uvs, page);
+        if frame != VirtualImageFrame::default() {
+            self.frames.insert(id, frame);
+        }
+        id
+    }
+
     pub fn unregister_image_uvs(&mut self, id: ImageId) -> Option<(Rect, usize)> {
         if let Some(name) = self.table.remove(&id) {
             self.map.remove(&name);
         }
+        self.frames.remove(&id);
         self.uvs.remove(&id)
     }
 
     pub fn unregister_named_image_uvs(&mut self, name: &str) -> Option<(Rect, usize)> {
         if let Some(id) = self.map.remove(name) {
             self.table.remove(&id);
+            self.frames.remove(&id);
             return self.uvs.remove(&id);
         }
         None
@@ -489,6 +541,17 @@ impl VirtualImage {
         None
     }
 
+    pub fn image_frame(&self, id: ImageId) -> VirtualImageFrame {
+        self.frames.get(&id).copied().unwrap_or_default()
+    }
+
+    pub fn named_image_frame(&self, name: &str) -> VirtualImageFrame {
+        self.map
+            .get(name)
+            .map(|id| self.image_frame(*id))
+            .unwrap_or_default()
+    }
+
     pub fn image_name(&self, id: ImageId) -> Option<&str> {
         self.table.get(&id).map(|name| name.as_str())
     }
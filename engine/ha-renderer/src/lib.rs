@@ -23,6 +23,8 @@ pub mod systems;
 pub mod prelude {
     #[cfg(feature = "desktop")]
     pub use crate::platform::desktop::*;
+    #[cfg(feature = "headless")]
+    pub use crate::platform::headless::*;
     #[cfg(feature = "web")]
     pub use crate::platform::web::*;
 
@@ -34,10 +36,11 @@ pub mod prelude {
         builtin_material_function, builtin_material_functions, code_material_function,
         code_material_functions,
         components::{
-            camera::*, gizmo::*, immediate_batch::*, material_instance::*, mesh_instance::*,
-            postprocess::*, rig_instance::*, sprite_animation_instance::*, text_instance::*,
-            tilemap_instance::*, transform::*, virtual_image_uniforms::*, visibility::*, volume::*,
-            volume_overlap::*, volume_visibility::*, *,
+            camera::*, gizmo::*, immediate_batch::*, light_2d::*, material_instance::*,
+            mesh_instance::*, particle_emitter::*, postprocess::*, rig_instance::*,
+            sprite_animation_instance::*, text_instance::*, tilemap_instance::*, transform::*,
+            virtual_image_uniforms::*, visibility::*, volume::*, volume_overlap::*,
+            volume_visibility::*, *,
         },
         constants::material_uniforms::*,
         graph_material_function,
@@ -74,10 +77,10 @@ pub mod prelude {
         rich_text,
         systems::{
             apply_sprite_animation_to_material::*, atlas::*, camera_cache::*, font::*,
-            immediate_batch::*, mesh_bounds_gizmo::*, render_forward_stage::*,
-            render_gizmo_stage::*, render_postprocess_stage::*, renderer::*, sprite_animation::*,
-            tilemap::*, transform::*, virtual_image_uniforms::*, volume_overlap::*,
-            volume_visibility::*, *,
+            immediate_batch::*, mesh_bounds_gizmo::*, particle_emitter::*, render_forward_stage::*,
+            render_gizmo_stage::*, render_lighting_stage::*, render_postprocess_stage::*,
+            renderer::*, sprite_animation::*, tilemap::*, transform::*, virtual_image_uniforms::*,
+            volume_overlap::*, volume_visibility::*, *,
         },
         Error, HaRendererBundleSetup, HasContextResources, ResourceReference, Resources,
     };
@@ -102,8 +105,10 @@ use crate::{
         camera::{HaCamera, HaDefaultCamera},
         gizmo::HaGizmo,
         immediate_batch::HaImmediateBatch,
+        light_2d::{HaLight2d, HaLighting2d, HaShadowCaster2d},
         material_instance::HaMaterialInstance,
         mesh_instance::HaMeshInstance,
+        particle_emitter::HaParticleEmitter,
         postprocess::HaPostProcess,
         rig_instance::HaRigInstance,
         sprite_animation_instance::HaSpriteAnimationInstance,
@@ -116,18 +121,24 @@ use crate::{
         volume_overlap::HaVolumeOverlap,
         volume_visibility::HaVolumeVisibility,
     },
+    constants::material_uniforms::{
+        MAIN_IMAGE_FRAME_NAME, MAIN_IMAGE_ROTATED_NAME, NORMALS_IMAGE_NAME,
+    },
     ha_renderer::HaRenderer,
     image::{ImageError, ImageId, ImageMode, ImageResourceMapping},
     material::{
+        common::MaterialValue,
         domains::{
             gizmo::{default_gizmo_color_material_graph, gizmo_domain_graph},
             screenspace::{
                 default_screenspace_color_material_graph,
+                default_screenspace_lighting_2d_material_graph,
                 default_screenspace_texture_material_graph, screenspace_domain_graph,
                 ScreenSpaceQuadFactory,
             },
             surface::{
-                default_surface_flat_color_material_graph, default_surface_flat_material_graph,
+                default_surface_flat_color_material_graph,
+                default_surface_flat_light_2d_material_graph, default_surface_flat_material_graph,
                 default_surface_flat_sdf_text_material_graph,
                 default_surface_flat_sdf_texture_2d_array_material_graph,
                 default_surface_flat_sdf_texture_2d_material_graph,
@@ -142,8 +153,9 @@ use crate::{
                 quad::SurfaceQuadFactory, surface_flat_domain_graph, SurfaceDomain,
             },
         },
-        MaterialDrawOptions, MaterialError, MaterialId, MaterialResourceMapping,
+        MaterialBlending, MaterialDrawOptions, MaterialError, MaterialId, MaterialResourceMapping,
     },
+    math::vec4,
     mesh::{controls::animation::AnimationRigControl, MeshError, MeshId, MeshResourceMapping},
     render_target::{RenderTargetError, RenderTargetId},
     resources::{camera_cache::CameraCache, gizmos::Gizmos, material_library::MaterialLibrary},
@@ -158,6 +170,10 @@ use crate::{
             ha_immediate_batch_system, HaImmediateBatchSystemCache, HaImmediateBatchSystemResources,
         },
         mesh_bounds_gizmo::{ha_mesh_bounds_gizmo_system, HaMeshBoundsGizmoSystemResources},
+        particle_emitter::{
+            ha_particle_emitter_system, HaParticleEmitterSystemCache,
+            HaParticleEmitterSystemResources,
+        },
         render_forward_stage::{
             ha_render_forward_stage_system, HaRenderForwardStageSystemResources,
         },
@@ -165,6 +181,10 @@ use crate::{
             ha_render_gizmo_stage_system, HaRenderGizmoStageSystemCache,
             HaRenderGizmoStageSystemResources,
         },
+        render_lighting_stage::{
+            ha_render_lighting_stage_system, HaRenderLightingStageSystemCache,
+            HaRenderLightingStageSystemResources,
+        },
         render_postprocess_stage::{
             ha_render_postprocess_stage_system, HaRenderPostProcessStageSystemCache,
             HaRenderPostProcessStageSystemResources,
@@ -218,7 +238,7 @@ where
     fn context_release(&mut self, context: &T) -> Result<(), Self::Error>;
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ResourceReference<ID, VID = ID> {
     None,
     Asset(String),
@@ -433,8 +453,10 @@ where
     builder.install_resource(HaVolumeVisibilitySystemCache::default());
     builder.install_resource(HaVolumeOverlapSystemCache::default());
     builder.install_resource(HaRenderGizmoStageSystemCache::default());
+    builder.install_resource(HaRenderLightingStageSystemCache::default());
     builder.install_resource(HaRenderPostProcessStageSystemCache::default());
     builder.install_resource(HaImmediateBatchSystemCache::default());
+    builder.install_resource(HaParticleEmitterSystemCache::default());
     builder.install_resource(MaterialLibrary::default());
     builder.install_resource(ImageResourceMapping::default());
     builder.install_resource(MeshResourceMapping::default());
@@ -474,6 +496,11 @@ where
         ha_render_forward_stage_system,
         &[],
     )?;
+    builder.install_system::<HaRenderLightingStageSystemResources>(
+        "renderer-lighting-stage",
+        ha_render_lighting_stage_system,
+        &[],
+    )?;
     builder.install_system::<HaRenderPostProcessStageSystemResources>(
         "renderer-postprocess-stage",
         ha_render_postprocess_stage_system,
@@ -518,6 +545,11 @@ where
         ha_mesh_bounds_gizmo_system,
         &[],
     )?;
+    builder.install_system::<HaParticleEmitterSystemResources>(
+        "particle-emitter",
+        ha_particle_emitter_system,
+        &[],
+    )?;
 
     Ok(())
 }
@@ -646,6 +678,11 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
             ImageMode::Image2d,
         ),
     ));
+    database.insert(Asset::new(
+        "image",
+        "@image/flat-normal-2d",
+        ImageAsset::color([128, 128, 255, 255], ImageMode::Image2d),
+    ));
     database.insert(Asset::new(
         "image",
         "@image/empty-2d-array",
@@ -778,7 +815,16 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
         "material",
         "@material/graph/surface/flat/virtual-uniform-texture-2d",
         MaterialAsset::Graph {
-            default_values: Default::default(),
+            default_values: HashMap::from([
+                (
+                    MAIN_IMAGE_FRAME_NAME.to_owned(),
+                    MaterialValue::Vec4F(vec4(0.0, 0.0, 1.0, 1.0)),
+                ),
+                (
+                    MAIN_IMAGE_ROTATED_NAME.to_owned(),
+                    MaterialValue::Scalar(0.0),
+                ),
+            ]),
             draw_options: MaterialDrawOptions::transparent(),
             content: default_surface_flat_virtual_uniform_texture_2d_material_graph(),
         },
@@ -819,6 +865,24 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
             content: default_surface_flat_sdf_text_material_graph(),
         },
     ));
+    database.insert(Asset::new(
+        "material",
+        "@material/graph/surface/flat/light-2d",
+        MaterialAsset::Graph {
+            default_values: HashMap::from([(
+                NORMALS_IMAGE_NAME.to_owned(),
+                MaterialValue::sampler_2d(ResourceReference::Asset(
+                    "@image/flat-normal-2d".to_owned(),
+                )),
+            )]),
+            draw_options: MaterialDrawOptions {
+                color_mask: [true, true, true, false],
+                depth_mask: false,
+                blending: MaterialBlending::Additive,
+            },
+            content: default_surface_flat_light_2d_material_graph(),
+        },
+    ));
     database.insert(Asset::new(
         "material",
         "@material/graph/screenspace/color",
@@ -837,6 +901,15 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
             content: default_screenspace_texture_material_graph(),
         },
     ));
+    database.insert(Asset::new(
+        "material",
+        "@material/graph/screenspace/lighting-2d",
+        MaterialAsset::Graph {
+            default_values: Default::default(),
+            draw_options: Default::default(),
+            content: default_screenspace_lighting_2d_material_graph(),
+        },
+    ));
     database.insert(Asset::new(
         "material",
         "@material/graph/gizmo/color",
@@ -865,6 +938,10 @@ pub fn prefabs_installer(prefabs: &mut PrefabManager) {
     prefabs.register_component_factory::<HaVolumeOverlap>("HaVolumeOverlap");
     prefabs.register_component_factory::<HaGizmo>("HaGizmo");
     prefabs.register_component_factory::<HaPostProcess>("HaPostProcess");
+    prefabs.register_component_factory::<HaLight2d>("HaLight2d");
+    prefabs.register_component_factory::<HaShadowCaster2d>("HaShadowCaster2d");
+    prefabs.register_component_factory::<HaLighting2d>("HaLighting2d");
+    prefabs.register_component_factory::<HaParticleEmitter>("HaParticleEmitter");
 }
 
 pub fn immediate_batch_prefab_installer<C>(postfix: &str, prefabs: &mut PrefabManager)
@@ -26,6 +26,22 @@ impl MaterialMeshSignature {
         )
     }
 
+    /// Signature of mesh drawn with per-instance attributes, placed right after mesh ones.
+    pub fn new_instanced(vertex_layout: &VertexLayout, instance_layout: &VertexLayout) -> Self {
+        let base_location = vertex_layout.locations();
+        Self(
+            vertex_layout
+                .vertex_attribs()
+                .map(|(_, id, chunk)| (id.to_owned(), chunk.location()))
+                .chain(
+                    instance_layout
+                        .vertex_attribs()
+                        .map(|(_, id, chunk)| (id.to_owned(), base_location + chunk.location())),
+                )
+                .collect(),
+        )
+    }
+
     /// # Safety
     /// Constructing signature from raw data might cause invalid signature.
     /// Consider using safe constructors.
@@ -121,6 +137,10 @@ impl MaterialSignature {
         MaterialHashedSignature(hasher.finish())
     }
 
+    pub fn mesh(&self) -> &MaterialMeshSignature {
+        &self.mesh
+    }
+
     pub fn sources(&self) -> impl Iterator<Item = (&str, usize)> {
         self.mesh.sources()
     }
@@ -136,6 +156,13 @@ impl MaterialSignature {
     pub fn middlewares(&self) -> StrSequence {
         self.middlewares.as_slice()
     }
+
+    pub fn with_middlewares<'a>(mut self, middlewares: impl IntoIterator<Item = &'a str>) -> Self {
+        for middleware in middlewares {
+            self.middlewares.append(middleware);
+        }
+        self
+    }
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -493,6 +520,59 @@ impl MaterialValue {
     }
 }
 
+/// Floats are hashed by their bits, with both zeros treated as the same value.
+impl Hash for MaterialValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        fn floats<H: Hasher>(values: &[f32], state: &mut H) {
+            for value in values {
+                let bits = if *value == 0.0 { 0 } else { value.to_bits() };
+                bits.hash(state);
+            }
+        }
+
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Bool(v) => v.hash(state),
+            Self::Vec2B(v) => v.into_array().hash(state),
+            Self::Vec3B(v) => v.into_array().hash(state),
+            Self::Vec4B(v) => v.into_array().hash(state),
+            Self::Mat2B(v) => v.into_col_array().hash(state),
+            Self::Mat3B(v) => v.into_col_array().hash(state),
+            Self::Mat4B(v) => v.into_col_array().hash(state),
+            Self::Scalar(v) => floats(&[*v], state),
+            Self::Vec2F(v) => floats(&v.into_array(), state),
+            Self::Vec3F(v) => floats(&v.into_array(), state),
+            Self::Vec4F(v) => floats(&v.into_array(), state),
+            Self::Mat2F(v) => floats(&v.into_col_array(), state),
+            Self::Mat3F(v) => floats(&v.into_col_array(), state),
+            Self::Mat4F(v) => floats(&v.into_col_array(), state),
+            Self::Integer(v) => v.hash(state),
+            Self::Vec2I(v) => v.into_array().hash(state),
+            Self::Vec3I(v) => v.into_array().hash(state),
+            Self::Vec4I(v) => v.into_array().hash(state),
+            Self::Mat2I(v) => v.into_col_array().hash(state),
+            Self::Mat3I(v) => v.into_col_array().hash(state),
+            Self::Mat4I(v) => v.into_col_array().hash(state),
+            Self::Sampler2d {
+                reference,
+                filtering,
+            }
+            | Self::Sampler2dArray {
+                reference,
+                filtering,
+            }
+            | Self::Sampler3d {
+                reference,
+                filtering,
+            } => {
+                reference.hash(state);
+                filtering.hash(state);
+            }
+            Self::Array(v) => v.hash(state),
+        }
+    }
+}
+
 impl ToString for MaterialValue {
     #[allow(clippy::many_single_char_names)]
     fn to_string(&self) -> String {
@@ -40,6 +40,29 @@ pub fn default_screenspace_texture_material_graph() -> MaterialGraph {
     }
 }
 
+pub fn default_screenspace_lighting_2d_material_graph() -> MaterialGraph {
+    material_graph! {
+        inputs {
+            [vertex] inout TextureCoord: vec2 = {vec2(0.0, 0.0)};
+
+            [fragment] uniform mainImage: sampler2D;
+            [fragment] uniform lightsImage: sampler2D;
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [coord = [TextureCoord => vTexCoord]]
+        [color = (texture2d, sampler: mainImage, coord: coord)]
+        [light = (texture2d, sampler: lightsImage, coord: coord)]
+        [(mul_vec4,
+            a: color,
+            b: (append_vec4, a: (truncate_vec4, v: light), b: {1.0})
+        ) -> BaseColor]
+    }
+}
+
 pub fn screenspace_domain_graph() -> MaterialGraph {
     material_graph! {
         inputs {
@@ -132,5 +155,12 @@ mod tests {
             &screenspace_domain_graph(),
             &default_screenspace_texture_material_graph(),
         );
+
+        MaterialLibrary::assert_material_compilation(
+            &ScreenSpaceVertex::vertex_layout().unwrap(),
+            RenderTargetDescriptor::Main,
+            &screenspace_domain_graph(),
+            &default_screenspace_lighting_2d_material_graph(),
+        );
     }
 }
@@ -174,6 +174,8 @@ pub fn default_surface_flat_virtual_uniform_texture_2d_material_graph() -> Mater
             [fragment] uniform mainImage: sampler2D;
             [fragment] uniform mainImageOffset: vec2;
             [fragment] uniform mainImageSize: vec2;
+            [fragment] uniform mainImageFrame: vec4;
+            [fragment] uniform mainImageRotated: float;
         }
 
         outputs {
@@ -181,11 +183,13 @@ pub fn default_surface_flat_virtual_uniform_texture_2d_material_graph() -> Mater
         }
 
         [coord = (truncate_vec3, v: [TextureCoord => vTexCoord])]
-        [color = (virtualTexture2d,
+        [color = (virtualFrameTexture2d,
             sampler: mainImage,
             coord: coord,
             offset: mainImageOffset,
-            size: mainImageSize
+            size: mainImageSize,
+            frame: mainImageFrame,
+            rotated: mainImageRotated
         )]
         [(mul_vec4, a: color, b: [TintColor => vColor]) -> BaseColor]
     }
@@ -289,6 +293,47 @@ pub fn default_surface_flat_sdf_text_material_graph() -> MaterialGraph {
     }
 }
 
+pub fn default_surface_flat_light_2d_material_graph() -> MaterialGraph {
+    material_graph! {
+        inputs {
+            [vertex] inout WorldPosition: vec3 = {vec3(0.0, 0.0, 0.0)};
+            [vertex] inout ScreenPosition: vec3 = {vec3(0.0, 0.0, 0.0)};
+
+            [fragment] uniform normalsImage: sampler2D;
+            [fragment] uniform lightPosition: vec3;
+            [fragment] uniform lightDirection: vec3;
+            [fragment] uniform lightColor: vec4;
+            [fragment] uniform lightRadius: float;
+            [fragment] uniform lightAngle: float;
+            [fragment] uniform lightFalloff: float;
+            [fragment] uniform lightDirectional: float;
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [coord = (add_vec2,
+            a: (mul_vec2,
+                a: (truncate_vec3, v: [ScreenPosition => vScreenPosition]),
+                b: {vec2(0.5, 0.5)}
+            ),
+            b: {vec2(0.5, 0.5)}
+        )]
+        [factor = (light_2d_factor,
+            world_position: [WorldPosition => vWorldPosition],
+            normal_data: (texture2d, sampler: normalsImage, coord: coord),
+            light_position: lightPosition,
+            light_direction: lightDirection,
+            radius: lightRadius,
+            angle: lightAngle,
+            falloff: lightFalloff,
+            directional: lightDirectional
+        )]
+        [(mul_vec4, a: lightColor, b: (fill_vec4, v: factor)) -> BaseColor]
+    }
+}
+
 pub fn surface_flat_domain_graph() -> MaterialGraph {
     material_graph! {
         inputs {
@@ -296,6 +341,7 @@ pub fn surface_flat_domain_graph() -> MaterialGraph {
             [fragment] inout BaseColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
             [fragment] inout ScreenDepthOffset: float = {0.0};
             [fragment] inout VisibilityMask: bool = {true};
+            [fragment] in BaseNormal: vec3 = {vec3(0.0, 0.0, 1.0)};
 
             [vertex] uniform model: mat4;
             [vertex] uniform view: mat4;
@@ -306,6 +352,8 @@ pub fn surface_flat_domain_graph() -> MaterialGraph {
             [vertex] in normal: vec3 = {vec3(0.0, 0.0, 1.0)};
             [vertex] in textureCoord: vec3 = {vec3(0.0, 0.0, 0.0)};
             [vertex] in color: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+
+            [vertex] in instanceModel: mat4 = {mat4(1.0)};
         }
 
         outputs {
@@ -329,23 +377,32 @@ pub fn surface_flat_domain_graph() -> MaterialGraph {
             [vertex] builtin gl_Position: vec4;
             [fragment] builtin gl_FragDepth: float;
             [fragment] out finalColor: vec4;
+            [fragment] out finalNormal: vec4;
         }
 
         [discarded = (discard_test, condition: (negate, v: VisibilityMask))]
         [local_position = position]
-        [model_dir = (cast_mat4_mat3, v: model)]
+        [instance_model = (mul_mat4, a: model, b: instanceModel)]
+        [model_dir = (cast_mat4_mat3, v: instance_model)]
         [view_projection = (mul_mat4, a: projection, b: view)]
-        [model_view_projection = (mul_mat4, a: view_projection, b: model)]
+        [model_view_projection = (mul_mat4, a: view_projection, b: instance_model)]
         [model_view_projection_dir = (cast_mat4_mat3, v: model_view_projection)]
         [pos = (append_vec4, a: local_position, b: {1.0})]
-        [world_position = (truncate_vec4, v: (mul_mat4_vec4, a: model, b: pos))]
+        [world_position = (truncate_vec4, v: (mul_mat4_vec4, a: instance_model, b: pos))]
         [world_position := (add_vec3, a: world_position, b: WorldPositionOffset)]
         [pos := (append_vec4, a: world_position, b: {1.0})]
         [screen_position = (truncate_vec4, v: (mul_mat4_vec4, a: view_projection, b: pos))]
         [world_normal = (mul_mat3_vec3, a: model_dir, b: normal)]
         [screen_normal = (mul_mat3_vec3, a: model_view_projection_dir, b: normal)]
+        [world_tangent = (mul_mat3_vec3, a: model_dir, b: {vec3(1.0, 0.0, 0.0)})]
+        [world_bitangent = (mul_mat3_vec3, a: model_dir, b: {vec3(0.0, 1.0, 0.0)})]
+        [final_normal = (tangent_to_world_normal,
+            normal: BaseNormal,
+            tangent: [world_tangent => vWorldTangent],
+            bitangent: [world_bitangent => vWorldBitangent]
+        )]
 
-        [model -> Model]
+        [instance_model -> Model]
         [view -> View]
         [projection -> Projection]
         [view_projection -> ViewProjection]
@@ -367,10 +424,19 @@ pub fn surface_flat_domain_graph() -> MaterialGraph {
             truthy: {vec4(0.0, 0.0, 0.0, 0.0)},
             falsy: BaseColor
         ) -> finalColor]
+        [(if_vec4,
+            condition: discarded,
+            truthy: {vec4(0.0, 0.0, 0.0, 0.0)},
+            falsy: (encode_normal, normal: final_normal, alpha: (maskW_vec4, v: BaseColor))
+        ) -> finalNormal]
         [(add_float, a: gl_FragDepth, b: ScreenDepthOffset) -> gl_FragDepth]
     }
 }
 
+fn default_instance_model() -> vek::Mat4<f32> {
+    vek::Mat4::identity()
+}
+
 fn default_position() -> vek::Vec3<f32> {
     vec3(0.0, 0.0, 0.0)
 }
@@ -449,6 +515,15 @@ vertex_type! {
     }
 }
 
+vertex_type! {
+    /// Per-instance data of instanced surface draw calls.
+    #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
+    pub struct SurfaceInstance {
+        #[serde(default = "default_instance_model")]
+        pub model: mat4 = instanceModel(0),
+    }
+}
+
 vertex_type! {
     #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
     @tags(SurfaceDomain)
@@ -12,9 +12,16 @@ use crate::{
         MeshError,
     },
 };
+use unicode_bidi_mirroring::get_mirrored;
 
 struct TextGlyph {
     character: char,
+    combining: bool,
+    /// Unicode bidi embedding level - odd levels are right-to-left.
+    level: u8,
+    kerning: f32,
+    advance: f32,
+    offset: f32,
     page: usize,
     position: Vec2,
     uvs: Rect,
@@ -26,6 +33,56 @@ struct TextGlyph {
     baseline: f32,
 }
 
+/// Reorders glyphs of line from logical to visual order (rules L1 and L2 of Unicode bidi
+/// algorithm), keeping combining marks attached to their base glyphs, then lays them out again.
+fn reorder_line(line: &mut Vec<TextGlyph>, base_level: u8) {
+    let mut clusters = Vec::<Vec<TextGlyph>>::with_capacity(line.len());
+    for glyph in line.drain(..) {
+        match clusters.last_mut() {
+            Some(cluster) if glyph.combining => cluster.push(glyph),
+            _ => clusters.push(vec![glyph]),
+        }
+    }
+    let mut levels = clusters
+        .iter()
+        .map(|cluster| cluster[0].level)
+        .collect::<Vec<_>>();
+    for (level, cluster) in levels.iter_mut().zip(clusters.iter()).rev() {
+        if !cluster[0].character.is_whitespace() {
+            break;
+        }
+        *level = base_level;
+    }
+    if let (Some(min), Some(max)) = (levels.iter().min(), levels.iter().max()) {
+        for level in ((*min | 1)..=*max).rev() {
+            let mut index = 0;
+            while index < levels.len() {
+                if levels[index] < level {
+                    index += 1;
+                    continue;
+                }
+                let start = index;
+                while index < levels.len() && levels[index] >= level {
+                    index += 1;
+                }
+                levels[start..index].reverse();
+                clusters[start..index].reverse();
+            }
+        }
+    }
+    let mut x = 0.0;
+    for mut glyph in clusters.into_iter().flatten() {
+        // kerning of right-to-left glyph applies to its right side.
+        if glyph.level % 2 == 1 {
+            glyph.position.x = x + glyph.offset;
+        } else {
+            glyph.position.x = x + glyph.kerning + glyph.offset;
+        }
+        x += glyph.kerning + glyph.advance;
+        line.push(glyph);
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct SurfaceTextFactory;
 
@@ -48,15 +105,22 @@ impl SurfaceTextFactory {
         let mut line_width: f32 = 0.0;
         let mut line_height: f32 = 0.0;
         let mut line_base: f32 = 0.0;
+        let mut previous: Option<char> = None;
+        let levels = text.bidi_levels();
+        let mut glyph_index = 0;
+        let mut line_index = 0;
 
         macro_rules! move_to_new_line {
             (@push) => {
                 {
+                    let base_level = levels.lines.get(line_index).copied().unwrap_or_default();
+                    reorder_line(&mut line_cache, base_level);
                     for glyph in &mut line_cache {
                         glyph.position.y += line_base - glyph.baseline;
                     }
                     lines.push((
                         line_width,
+                        base_level % 2 == 1,
                         std::mem::replace(&mut line_cache, Vec::with_capacity(count)),
                     ));
                     y += line_height;
@@ -69,6 +133,7 @@ impl SurfaceTextFactory {
                     line_width = 0.0;
                     line_height = 0.0;
                     line_base = 0.0;
+                    previous = None;
                 }
             };
             () => {
@@ -84,9 +149,11 @@ impl SurfaceTextFactory {
                 HaTextElement::Invalid => {}
                 HaTextElement::NewLine => {
                     move_to_new_line!();
+                    line_index += 1;
                 }
                 HaTextElement::Glyph {
                     character,
+                    combining,
                     size,
                     color,
                     outline,
@@ -94,16 +161,47 @@ impl SurfaceTextFactory {
                     cursive,
                     ..
                 } => {
-                    if let Some(c) = font.characters.get(&character) {
+                    let level = levels.glyphs.get(glyph_index).copied().unwrap_or_default();
+                    glyph_index += 1;
+                    let rtl = level % 2 == 1;
+                    // right-to-left runs use mirrored glyphs of paired punctuation.
+                    let character = match get_mirrored(character) {
+                        Some(mirrored) if rtl && font.characters.contains_key(&mirrored) => {
+                            mirrored
+                        }
+                        _ => character,
+                    };
+                    let found = if combining {
+                        font.characters.get(&character).map(|c| (character, c))
+                    } else {
+                        font.character(character)
+                    };
+                    if let Some((character, c)) = found {
                         if let Some((page_size, _)) = font.pages_image_assets.get(c.page) {
                             let scale = size / font.line_height as f32;
-                            let xadvance = c.line_advance * scale;
+                            let mut kerning = match previous {
+                                Some(previous) if !combining && rtl => {
+                                    font.kerning(character, previous) * scale
+                                }
+                                Some(previous) if !combining => {
+                                    font.kerning(previous, character) * scale
+                                }
+                                _ => 0.0,
+                            };
+                            // combining marks are placed over their base glyph without advance.
+                            let xadvance = if combining {
+                                0.0
+                            } else {
+                                c.line_advance * scale
+                            };
                             let yadvance = (font.line_height as f32 + extra_y) * scale;
-                            if x + xadvance > bounds_width {
+                            if !combining && x + kerning + xadvance > bounds_width {
                                 move_to_new_line!();
+                                kerning = 0.0;
                                 // TODO: use wrapping to break lines: `wrapping.can_wrap(character)`
                             }
-                            if x + xadvance > bounds_width || y + yadvance > bounds_height {
+                            if x + kerning + xadvance > bounds_width || y + yadvance > bounds_height
+                            {
                                 break;
                             }
                             let baseline = (font.line_base as f32 + extra_y) * scale;
@@ -113,8 +211,13 @@ impl SurfaceTextFactory {
                             line_height = line_height.max(yadvance);
                             line_cache.push(TextGlyph {
                                 character,
+                                combining,
+                                level,
+                                kerning,
+                                advance: xadvance,
+                                offset: offset.x,
                                 page: c.page as _,
-                                position: Vec2::new(x, y) + offset,
+                                position: Vec2::new(x + kerning, y) + offset,
                                 uvs: Rect::new(
                                     c.image_location.x / page_size.x,
                                     c.image_location.y / page_size.y,
@@ -128,10 +231,13 @@ impl SurfaceTextFactory {
                                 cursive_shift,
                                 baseline,
                             });
-                            x += xadvance;
+                            x += kerning + xadvance;
                             line_width = line_width.max(x);
                             width = width.max(line_width);
                             line_base = line_base.max(baseline);
+                            if !combining {
+                                previous = Some(character);
+                            }
                         }
                     }
                 }
@@ -142,8 +248,13 @@ impl SurfaceTextFactory {
         let yalign = (height - y) * text.alignment().y;
         let xpivot = width * text.pivot().x;
         let ypivot = height * text.pivot().y;
-        for (line_width, line) in &mut lines {
-            let xalign = (width - *line_width) * text.alignment().x;
+        for (line_width, rtl, line) in &mut lines {
+            // right-to-left lines mirror horizontal alignment.
+            let xalign = if *rtl {
+                (width - *line_width) * (1.0 - text.alignment().x)
+            } else {
+                (width - *line_width) * text.alignment().x
+            };
             for glyph in line {
                 glyph.position.x += xalign - xpivot;
                 glyph.position.y += yalign - ypivot;
@@ -156,7 +267,7 @@ impl SurfaceTextFactory {
                     "position",
                     lines
                         .iter()
-                        .flat_map(|(_, glyphs)| glyphs)
+                        .flat_map(|(_, _, glyphs)| glyphs)
                         .flat_map(|glyph| {
                             [
                                 Vec2::new(glyph.position.x + glyph.cursive_shift, glyph.position.y),
@@ -180,7 +291,7 @@ impl SurfaceTextFactory {
                     "textureCoord",
                     lines
                         .iter()
-                        .flat_map(|(_, glyphs)| glyphs)
+                        .flat_map(|(_, _, glyphs)| glyphs)
                         .flat_map(|glyph| {
                             [
                                 Vec3::new(glyph.uvs.x, glyph.uvs.y, glyph.page as _),
@@ -199,7 +310,7 @@ impl SurfaceTextFactory {
                     "color",
                     lines
                         .iter()
-                        .flat_map(|(_, glyphs)| glyphs)
+                        .flat_map(|(_, _, glyphs)| glyphs)
                         .flat_map(|glyph| {
                             [
                                 Vec4::from(glyph.color),
@@ -214,7 +325,7 @@ impl SurfaceTextFactory {
                     "outline",
                     lines
                         .iter()
-                        .flat_map(|(_, glyphs)| glyphs)
+                        .flat_map(|(_, _, glyphs)| glyphs)
                         .flat_map(|glyph| {
                             [
                                 Vec4::from(glyph.outline),
@@ -229,7 +340,7 @@ impl SurfaceTextFactory {
             GeometryPrimitives::triangles(
                 lines
                     .iter()
-                    .flat_map(|(_, glyphs)| glyphs)
+                    .flat_map(|(_, _, glyphs)| glyphs)
                     .enumerate()
                     .flat_map(|(index, glyph)| {
                         let i = index * 4;
@@ -1,6 +1,6 @@
 use crate::{
-    components::tilemap_instance::HaTileMapInstance,
-    image::VirtualImage,
+    components::tilemap_instance::{HaTileMapInstance, HaTileMapTile},
+    image::{VirtualImage, VirtualImageFrame},
     material::domains::surface::SurfaceTexturedDomain,
     math::*,
     mesh::{
@@ -40,9 +40,10 @@ impl SurfaceTileMapFactory {
             .tiles()
             .iter()
             .filter_map(|tile| {
-                virtual_image
-                    .named_image_uvs(&tile.atlas_item)
-                    .map(|uvs| (tile, uvs))
+                virtual_image.named_image_uvs(&tile.atlas_item).map(|uvs| {
+                    let frame = virtual_image.named_image_frame(&tile.atlas_item);
+                    (tile, uvs, frame, tile_area(tile, &frame))
+                })
             })
             .collect::<Vec<_>>();
         let offset = Vec2::new(tilemap.cols() as Scalar, tilemap.rows() as Scalar)
@@ -55,11 +56,10 @@ impl SurfaceTileMapFactory {
                     "position",
                     tiles
                         .iter()
-                        .flat_map(|(tile, _)| {
-                            let from = Vec2::new(tile.col as Scalar, tile.row as Scalar)
-                                * cell_size
-                                - offset;
-                            let to = Vec2::new((tile.col + 1) as Scalar, (tile.row + 1) as Scalar)
+                        .flat_map(|(tile, _, _, area)| {
+                            let cell = Vec2::new(tile.col as Scalar, tile.row as Scalar);
+                            let from = (cell + Vec2::new(area.x, area.y)) * cell_size - offset;
+                            let to = (cell + Vec2::new(area.x + area.w, area.y + area.h))
                                 * cell_size
                                 - offset;
                             [
@@ -75,13 +75,21 @@ impl SurfaceTileMapFactory {
                     "textureCoord",
                     tiles
                         .iter()
-                        .flat_map(|(_, (uvs, layer))| {
+                        .flat_map(|(tile, (uvs, layer), frame, area)| {
                             [
-                                vec3(uvs.x, uvs.y, *layer as _),
-                                vec3(uvs.x + uvs.w, uvs.y, *layer as _),
-                                vec3(uvs.x + uvs.w, uvs.y + uvs.h, *layer as _),
-                                vec3(uvs.x, uvs.y + uvs.h, *layer as _),
+                                vec2(area.x, area.y),
+                                vec2(area.x + area.w, area.y),
+                                vec2(area.x + area.w, area.y + area.h),
+                                vec2(area.x, area.y + area.h),
                             ]
+                            .map(|corner| {
+                                let corner = frame.region_coord(tile.image_corner(corner));
+                                vec3(
+                                    uvs.x + uvs.w * corner.x,
+                                    uvs.y + uvs.h * corner.y,
+                                    *layer as _,
+                                )
+                            })
                         })
                         .collect(),
                 ),
@@ -90,7 +98,7 @@ impl SurfaceTileMapFactory {
                 tiles
                     .iter()
                     .enumerate()
-                    .flat_map(|(index, (tile, _))| {
+                    .flat_map(|(index, (tile, ..))| {
                         let i = index * 4;
                         let tl = i;
                         let tr = i + 1;
@@ -125,3 +133,22 @@ impl SurfaceTileMapFactory {
         Self::geometry(tilemap, resources, false)?.factory::<T>()
     }
 }
+
+/// Returns area of tile cell (each axis in 0-1 range) covered by trimmed tile image content.
+fn tile_area(tile: &HaTileMapTile, frame: &VirtualImageFrame) -> Rect {
+    // Tile flips are symmetries of unit square, so their inverse is the transposed transform.
+    let origin = tile.image_corner(Vec2::zero());
+    let axis_x = tile.image_corner(Vec2::unit_x()) - origin;
+    let axis_y = tile.image_corner(Vec2::unit_y()) - origin;
+    let [a, b] = [
+        vec2(frame.rect.x, frame.rect.y),
+        vec2(frame.rect.x + frame.rect.w, frame.rect.y + frame.rect.h),
+    ]
+    .map(|point| {
+        let point = point - origin;
+        vec2(point.dot(axis_x), point.dot(axis_y))
+    });
+    let min = Vec2::partial_min(a, b);
+    let max = Vec2::partial_max(a, b);
+    Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+}
@@ -126,7 +126,7 @@ impl Default for MaterialBlending {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MaterialDrawOptions {
     #[serde(default = "MaterialDrawOptions::default_color_mask")]
     pub color_mask: [bool; 4],
@@ -428,6 +428,11 @@ impl VertexLayout {
         &self.buffers
     }
 
+    /// Total number of attribute locations used by all buffers.
+    pub fn locations(&self) -> usize {
+        self.locations
+    }
+
     pub fn bounds(&self) -> Option<&str> {
         self.bounds.as_deref()
     }
@@ -520,10 +525,51 @@ impl MeshDrawMode {
     }
 }
 
+/// Per-instance vertex data used by instanced draw calls.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeshInstances {
+    layout: VertexLayout,
+    data: Vec<u8>,
+}
+
+impl MeshInstances {
+    pub fn new<T>(instances: &[T]) -> Result<Self, MeshError>
+    where
+        T: VertexType,
+    {
+        let layout = T::vertex_layout()?;
+        if !layout.is_compact() {
+            return Err(MeshError::LayoutIsNotCompact(Box::new(layout)));
+        }
+        let data = unsafe { instances.align_to::<u8>().1.to_owned() };
+        Ok(Self { layout, data })
+    }
+
+    pub fn layout(&self) -> &VertexLayout {
+        &self.layout
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn len(&self) -> usize {
+        match self.layout.buffers.first() {
+            Some(buffer) if buffer.bytesize > 0 => self.data.len() / buffer.bytesize,
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 #[derive(Debug)]
 pub struct MeshResources {
     pub vertices_handles: Vec<<Context as HasContext>::Buffer>,
     pub indices_handle: <Context as HasContext>::Buffer,
+    pub instances_handle: <Context as HasContext>::Buffer,
     pub array_handle: <Context as HasContext>::VertexArray,
 }
 
@@ -577,6 +623,10 @@ impl HasContextResources<Context> for Mesh {
             Ok(handle) => handle,
             Err(error) => return Err(MeshError::Internal(error)),
         };
+        let instances_handle = match unsafe { context.create_buffer() } {
+            Ok(handle) => handle,
+            Err(error) => return Err(MeshError::Internal(error)),
+        };
         let mut vertices_handles = Vec::with_capacity(self.layout.buffers.len());
         for _ in 0..self.layout.buffers.len() {
             match unsafe { context.create_buffer() } {
@@ -633,6 +683,7 @@ impl HasContextResources<Context> for Mesh {
         self.resources = Some(MeshResources {
             vertices_handles,
             indices_handle,
+            instances_handle,
             array_handle,
         });
         self.maintain(context)
@@ -642,6 +693,7 @@ impl HasContextResources<Context> for Mesh {
         if let Some(resources) = std::mem::take(&mut self.resources) {
             unsafe {
                 context.delete_buffer(resources.indices_handle);
+                context.delete_buffer(resources.instances_handle);
                 for handle in resources.vertices_handles {
                     context.delete_buffer(handle);
                 }
@@ -934,6 +986,111 @@ impl Mesh {
         }
     }
 
+    /// Draws mesh once per instance, feeding per-instance attributes at locations placed
+    /// right after ones used by mesh vertex layout.
+    pub(crate) fn draw_instanced(
+        &self,
+        range: MeshDrawRange,
+        instances: &MeshInstances,
+        context: &Context,
+        render_stats: &mut RenderStats,
+    ) -> Result<(), MeshError> {
+        let resources = match &self.resources {
+            Some(resources) => resources,
+            None => return Err(MeshError::NoResources),
+        };
+        if instances.is_empty() {
+            return Ok(());
+        }
+        let base_location = self.layout.locations;
+        let mut locations = Vec::with_capacity(instances.layout.locations);
+        unsafe {
+            context.bind_buffer(ARRAY_BUFFER, Some(resources.instances_handle));
+            context.buffer_data_u8_slice(ARRAY_BUFFER, &instances.data, STREAM_DRAW);
+            for buffer in &instances.layout.buffers {
+                let stride = buffer.bytesize;
+                let mut location = base_location + buffer.base_location;
+                for (attribute, offset) in &buffer.attributes {
+                    let channels = attribute.value_type.channels();
+                    let is_integer = attribute.value_type.is_integer();
+                    for index in 0..attribute.locations() {
+                        let offset = offset + index * channels * std::mem::size_of::<f32>();
+                        if is_integer {
+                            context.vertex_attrib_pointer_i32(
+                                location as _,
+                                channels as _,
+                                INT,
+                                stride as _,
+                                offset as _,
+                            );
+                        } else {
+                            context.vertex_attrib_pointer_f32(
+                                location as _,
+                                channels as _,
+                                FLOAT,
+                                attribute.normalized,
+                                stride as _,
+                                offset as _,
+                            );
+                        }
+                        context.vertex_attrib_divisor(location as _, 1);
+                        context.enable_vertex_attrib_array(location as _);
+                        locations.push(location);
+                        location += 1;
+                    }
+                }
+            }
+        }
+        let count = instances.len();
+        let result = match range {
+            MeshDrawRange::All => {
+                self.draw_range_instanced(0..self.index_data.0.len(), count, context, render_stats)
+            }
+            MeshDrawRange::Range(range) => {
+                self.draw_range_instanced(range, count, context, render_stats)
+            }
+            MeshDrawRange::Chunks(chunks) => {
+                for range in chunks {
+                    self.draw_range_instanced(range, count, context, render_stats)?;
+                }
+                Ok(())
+            }
+        };
+        unsafe {
+            for location in locations {
+                context.vertex_attrib_divisor(location as _, 0);
+                context.disable_vertex_attrib_array(location as _);
+            }
+        }
+        result
+    }
+
+    fn draw_range_instanced(
+        &self,
+        range: Range<usize>,
+        instances: usize,
+        context: &Context,
+        render_stats: &mut RenderStats,
+    ) -> Result<(), MeshError> {
+        let count = range.end - range.start;
+        if count == 0 || range.end > self.index_data.0.len() {
+            return Ok(());
+        }
+        let offset = range.start;
+        unsafe {
+            context.draw_elements_instanced(
+                self.draw_mode.as_gl(),
+                count as i32,
+                UNSIGNED_INT,
+                (offset * std::mem::size_of::<u32>()) as i32,
+                instances as i32,
+            );
+            render_stats.draw_calls += 1;
+            render_stats.instances += instances;
+        }
+        Ok(())
+    }
+
     fn draw_range(
         &self,
         range: Range<usize>,
@@ -5,7 +5,7 @@ use crate::{
         MaterialDrawOptions, MaterialError, MaterialId,
     },
     math::*,
-    mesh::{MeshDrawRange, MeshError, MeshId},
+    mesh::{MeshDrawRange, MeshError, MeshId, MeshInstances},
 };
 use glow::*;
 use serde::{Deserialize, Serialize};
@@ -33,6 +33,8 @@ pub enum RenderCommand {
     ApplyDrawOptions(MaterialDrawOptions),
     ActivateMesh(MeshId),
     DrawMesh(MeshDrawRange),
+    /// Draws active mesh once per instance with per-instance vertex attributes.
+    DrawMeshInstanced(MeshDrawRange, MeshInstances),
     /// (x, y, width, height, clipped)
     PushScissor(usize, usize, usize, usize, bool),
     PopScissor,
@@ -203,7 +205,7 @@ impl RenderQueue {
                 },
                 RenderCommand::ActivateMaterial(id, signature) => {
                     if current_material
-                        .map(|(cid, _, _)| cid == id)
+                        .map(|(cid, csignature, _)| cid == id && csignature == signature)
                         .unwrap_or_default()
                     {
                         continue;
@@ -248,7 +250,7 @@ impl RenderQueue {
                         None => return Err(RenderQueueError::MeshDoesNotExist(*id)),
                     }
                 }
-                RenderCommand::DrawMesh(draw_range) => {
+                RenderCommand::DrawMesh(_) | RenderCommand::DrawMeshInstanced(_, _) => {
                     let (mesh_id, mesh) = match current_mesh {
                         Some((id, mesh)) => (id, mesh),
                         None => return Err(RenderQueueError::NoMeshActive),
@@ -298,7 +300,16 @@ impl RenderQueue {
                             }
                         }
                     }
-                    if let Err(error) = mesh.draw(draw_range.clone(), context, stats) {
+                    let result = match command {
+                        RenderCommand::DrawMesh(draw_range) => {
+                            mesh.draw(draw_range.clone(), context, stats)
+                        }
+                        RenderCommand::DrawMeshInstanced(draw_range, instances) => {
+                            mesh.draw_instanced(draw_range.clone(), instances, context, stats)
+                        }
+                        _ => unreachable!(),
+                    };
+                    if let Err(error) = result {
                         return Err(RenderQueueError::Mesh(*mesh_id, Box::new(error)));
                     }
                     last_uniforms.clear();
@@ -37,6 +37,25 @@ impl StageProcessInfo {
             vertex_layout.middlewares().into(),
         )
     }
+
+    pub fn make_instanced_material_signature(
+        &self,
+        vertex_layout: &VertexLayout,
+        instance_layout: &VertexLayout,
+    ) -> MaterialSignature {
+        MaterialSignature::new(
+            MaterialMeshSignature::new_instanced(vertex_layout, instance_layout),
+            self.material_render_target_signature.to_owned(),
+            self.domain.to_owned(),
+            vertex_layout.middlewares().into(),
+        )
+        .with_middlewares(
+            instance_layout
+                .middlewares()
+                .iter()
+                .map(|name| name.as_str()),
+        )
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -0,0 +1,98 @@
+#![cfg(feature = "headless")]
+
+use crate::platform::{HaPlatformInterface, HaPlatformInterfaceProcessResult};
+use glow::Context;
+use glutin::{
+    dpi::PhysicalSize, ContextBuilder, GlProfile, GlRequest, NotCurrent, PossiblyCurrent,
+};
+
+#[derive(Debug, Clone)]
+pub enum HeadlessPlatformError {
+    Unsupported,
+    ContextCreation(String),
+    ContextActivation(String),
+}
+
+/// Renders into offscreen software GL context (OSMesa) so frames can be read back without GPU
+/// or windowing system - useful for CI rendering tests.
+#[derive(Debug)]
+pub struct HeadlessPlatformInterface {
+    context: Option<Context>,
+    gl_context: glutin::Context<PossiblyCurrent>,
+    width: usize,
+    height: usize,
+    context_lost: bool,
+}
+
+// TODO: this is a hack and works only if single threaded or pinned render thread.
+unsafe impl Send for HeadlessPlatformInterface {}
+unsafe impl Sync for HeadlessPlatformInterface {}
+
+impl HeadlessPlatformInterface {
+    pub fn new(width: usize, height: usize) -> Result<Self, HeadlessPlatformError> {
+        let builder = ContextBuilder::new()
+            .with_gl(GlRequest::Latest)
+            .with_gl_profile(GlProfile::Core)
+            .with_depth_buffer(24)
+            .with_stencil_buffer(8);
+        let gl_context = build_context(builder, PhysicalSize::new(width as _, height as _))?;
+        let gl_context = unsafe { gl_context.make_current() }
+            .map_err(|(_, error)| HeadlessPlatformError::ContextActivation(error.to_string()))?;
+        let context = unsafe {
+            Context::from_loader_function(|name| gl_context.get_proc_address(name) as *const _)
+        };
+        Ok(Self {
+            context: Some(context),
+            gl_context,
+            width,
+            height,
+            context_lost: false,
+        })
+    }
+
+    pub fn gl_context(&self) -> &glutin::Context<PossiblyCurrent> {
+        &self.gl_context
+    }
+}
+
+impl HaPlatformInterface for HeadlessPlatformInterface {
+    fn context(&self) -> Option<&Context> {
+        self.context.as_ref()
+    }
+
+    fn screen_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn maintain(&mut self) -> HaPlatformInterfaceProcessResult<'_> {
+        let mut result = HaPlatformInterfaceProcessResult::default();
+        if self.context_lost {
+            result.context_lost = std::mem::take(&mut self.context);
+        }
+        result
+    }
+
+    fn lose_context(&mut self) {
+        self.context_lost = true;
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn build_context(
+    builder: ContextBuilder<NotCurrent>,
+    size: PhysicalSize<u32>,
+) -> Result<glutin::Context<NotCurrent>, HeadlessPlatformError> {
+    use glutin::platform::unix::HeadlessContextExt;
+
+    builder
+        .build_osmesa(size)
+        .map_err(|error| HeadlessPlatformError::ContextCreation(error.to_string()))
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn build_context(
+    _: ContextBuilder<NotCurrent>,
+    _: PhysicalSize<u32>,
+) -> Result<glutin::Context<NotCurrent>, HeadlessPlatformError> {
+    Err(HeadlessPlatformError::Unsupported)
+}
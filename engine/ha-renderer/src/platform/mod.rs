@@ -1,5 +1,7 @@
 #[cfg(feature = "desktop")]
 pub mod desktop;
+#[cfg(feature = "headless")]
+pub mod headless;
 #[cfg(feature = "web")]
 pub mod web;
 
@@ -558,9 +558,15 @@ impl RenderTarget {
         let size = width * height * channel_size * 4;
         let mut result = vec![0u8; size];
         unsafe {
-            if !self.backbuffer {
+            if let Some(resources) = &self.resources {
+                context.bind_framebuffer(READ_FRAMEBUFFER, Some(resources.buffer_handle));
                 context.read_buffer(COLOR_ATTACHMENT0 + index as u32);
+            } else if self.backbuffer {
+                context.bind_framebuffer(READ_FRAMEBUFFER, None);
+            } else {
+                return Err(RenderTargetError::NoResources);
             }
+            context.pixel_store_i32(PACK_ALIGNMENT, 1);
             context.read_pixels(
                 0,
                 0,
@@ -571,12 +577,64 @@ impl RenderTarget {
                 PixelPackData::Slice(&mut result),
             );
             if !self.backbuffer {
+                context.bind_framebuffer(READ_FRAMEBUFFER, None);
                 context.read_buffer(BACK);
             }
         }
         Ok(result)
     }
 
+    /// Reads color buffer as RGBA bytes with rows ordered from top to bottom.
+    /// Float color buffers get their values clamped to 0-1 range.
+    pub fn read_rgba(&self, index: usize, context: &Context) -> Result<Vec<u8>, RenderTargetError> {
+        let data = self.query_color_data(index, context)?;
+        let float = !self.backbuffer
+            && self
+                .buffers
+                .colors
+                .get(index)
+                .map(|buffer| buffer.value_type == TargetValueType::FloatColor)
+                .unwrap_or_default();
+        let data = if float {
+            data.chunks_exact(std::mem::size_of::<f32>())
+                .map(|bytes| {
+                    let value = f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+                })
+                .collect::<Vec<_>>()
+        } else {
+            data
+        };
+        let stride = self.cached_width * 4;
+        if stride == 0 {
+            return Ok(data);
+        }
+        Ok(data
+            .chunks_exact(stride)
+            .rev()
+            .flat_map(|row| row.iter().copied())
+            .collect())
+    }
+
+    /// Reads color buffer as PNG file bytes.
+    pub fn read_png(&self, index: usize, context: &Context) -> Result<Vec<u8>, RenderTargetError> {
+        let data = self.read_rgba(index, context)?;
+        let mut result = Vec::with_capacity(data.len());
+        {
+            let mut encoder =
+                png::Encoder::new(&mut result, self.cached_width as _, self.cached_height as _);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder
+                .write_header()
+                .map_err(|error| RenderTargetError::Internal(error.to_string()))?;
+            writer
+                .write_image_data(&data)
+                .map_err(|error| RenderTargetError::Internal(error.to_string()))?;
+        }
+        Ok(result)
+    }
+
     pub(crate) fn fragment_buffers(&self) -> impl Iterator<Item = &'_ str> + '_ {
         self.buffers.colors.iter().map(|buffer| buffer.id.as_str())
     }
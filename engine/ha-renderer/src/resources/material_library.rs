@@ -729,6 +729,23 @@ impl MaterialLibrary {
             {fn virtualTexture3d(sampler: sampler3D, coord: vec3, offset: vec3, size: vec3) -> vec4}
             { "return texture(sampler, clamp(mix(offset, offset + size, coord), 0.0, 1.0));" }
         });
+        self.add_function(code_material_function! {
+            fn virtualFrameTexture2d(
+                sampler: sampler2D,
+                coord: vec2,
+                offset: vec2,
+                size: vec2,
+                frame: vec4,
+                rotated: float
+            ) -> vec4 {
+                "vec2 local = (coord - frame.xy) / frame.zw;\
+                if (any(lessThan(local, vec2(0.0))) || any(greaterThan(local, vec2(1.0)))) {\
+                    return vec4(0.0);\
+                }\
+                local = mix(local, vec2(1.0 - local.y, local.x), rotated);\
+                return texture(sampler, clamp(mix(offset, offset + size, local), 0.0, 1.0));"
+            }
+        });
         self
     }
 
@@ -1442,6 +1459,57 @@ impl MaterialLibrary {
         self
     }
 
+    fn with_lighting_functions(mut self) -> Self {
+        self.add_function(code_material_function! {
+            fn tangent_to_world_normal(normal: vec3, tangent: vec3, bitangent: vec3) -> vec3 {
+                "vec3 _tangent = normalize(tangent);
+                vec3 _bitangent = normalize(bitangent);
+                vec3 _normal = cross(_tangent, _bitangent);
+                return normalize(_tangent * normal.x + _bitangent * normal.y + _normal * normal.z);"
+            }
+        });
+        self.add_function(code_material_function! {
+            fn encode_normal(normal: vec3, alpha: float) -> vec4 {
+                "return vec4(normal * 0.5 + 0.5, alpha);"
+            }
+        });
+        self.add_function(code_material_function! {
+            fn decode_normal(data: vec4) -> vec3 {
+                "return normalize(data.xyz * 2.0 - 1.0);"
+            }
+        });
+        self.add_function(code_material_function! {
+            fn light_2d_factor(
+                world_position: vec3,
+                normal_data: vec4,
+                light_position: vec3,
+                light_direction: vec3,
+                radius: float,
+                angle: float,
+                falloff: float,
+                directional: float
+            ) -> float {
+                "vec3 _normal = normal_data.w > 0.0
+                    ? normalize(normal_data.xyz * 2.0 - 1.0)
+                    : vec3(0.0, 0.0, 1.0);
+                vec3 _to_light = light_position - world_position;
+                float _distance = length(_to_light.xy);
+                vec3 _direction = directional > 0.5 ? -light_direction : normalize(_to_light);
+                float _attenuation = directional > 0.5
+                    ? 1.0
+                    : pow(clamp(1.0 - _distance / max(radius, 0.0001), 0.0, 1.0), falloff);
+                float _cone = 1.0;
+                if (directional < 0.5 && angle > 0.0 && _distance > 0.0001) {
+                    float _limit = cos(angle * 0.5);
+                    float _dot = dot(-_to_light.xy / _distance, normalize(light_direction.xy));
+                    _cone = smoothstep(_limit, mix(_limit, 1.0, 0.1), _dot);
+                }
+                return _attenuation * _cone * max(dot(_normal, _direction), 0.0);"
+            }
+        });
+        self
+    }
+
     fn with_vertanim_middleware(mut self) -> Self {
         self.add_middleware(
             "vertanim".to_owned(),
@@ -1653,6 +1721,29 @@ impl MaterialLibrary {
         );
         self
     }
+    fn with_normalmap_middleware(mut self) -> Self {
+        self.add_middleware(
+            "normalmap".to_owned(),
+            material_graph! {
+                inputs {
+                    [vertex] in textureCoord as in_textureCoord: vec3 = {vec3(0.0, 0.0, 0.0)};
+
+                    [fragment] uniform normalImage: sampler2D;
+                }
+
+                outputs {
+                    [vertex] out textureCoord as out_textureCoord: vec3;
+                    [fragment] out BaseNormal: vec3;
+                }
+
+                [in_textureCoord -> out_textureCoord]
+                [coord = (truncate_vec3, v: [in_textureCoord => vNormalCoord])]
+                [data = (texture2d, sampler: normalImage, coord: coord)]
+                [(decode_normal, data: data) -> BaseNormal]
+            },
+        );
+        self
+    }
 }
 
 impl Default for MaterialLibrary {
@@ -1678,8 +1769,10 @@ impl Default for MaterialLibrary {
         .with_append_functions()
         .with_truncate_functions()
         .with_dithering()
+        .with_lighting_functions()
         .with_vertanim_middleware()
         .with_skinning_middleware()
         .with_deformer_middleware()
+        .with_normalmap_middleware()
     }
 }
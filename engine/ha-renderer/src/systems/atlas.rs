@@ -1,7 +1,9 @@
 use crate::{
     asset_protocols::atlas::AtlasAsset,
     ha_renderer::HaRenderer,
-    image::{ImageResourceMapping, VirtualImage, VirtualImageId, VirtualImageSource},
+    image::{
+        ImageResourceMapping, VirtualImage, VirtualImageFrame, VirtualImageId, VirtualImageSource,
+    },
     math::*,
 };
 use core::{
@@ -27,54 +29,96 @@ pub fn ha_atlas_system(universe: &mut Universe) {
         universe.query_resources::<HaAtlasSystemResources>();
 
     for id in assets.lately_loaded_protocol("atlas") {
-        if let Some(asset) = assets.asset_by_id(*id) {
-            let path = asset.path();
-            if let Some(asset) = asset.get::<AtlasAsset>() {
-                for (page, mappings) in &asset.page_mappings {
-                    if let Some((page_size, image_asset_id)) = asset.pages_image_assets.get(page) {
-                        if let Some(image_id) = image_mapping.resource_by_asset(*image_asset_id) {
-                            let virtual_image_id = renderer.virtual_images.add_named(
-                                path.to_owned(),
-                                VirtualImage::new(VirtualImageSource::Image(image_id)),
+        add_atlas(*id, &mut renderer, &assets, &mut cache, &mut image_mapping);
+    }
+    for id in assets.lately_reloaded_protocol("atlas") {
+        remove_atlas(*id, &mut renderer, &mut cache, &mut image_mapping);
+        add_atlas(*id, &mut renderer, &assets, &mut cache, &mut image_mapping);
+    }
+    for id in assets.lately_unloaded_protocol("atlas") {
+        remove_atlas(*id, &mut renderer, &mut cache, &mut image_mapping);
+    }
+}
+
+fn add_atlas(
+    id: AssetId,
+    renderer: &mut HaRenderer,
+    assets: &AssetsDatabase,
+    cache: &mut HaAtlasSystemCache,
+    image_mapping: &mut ImageResourceMapping,
+) {
+    if let Some(asset) = assets.asset_by_id(id) {
+        let path = asset.path();
+        if let Some(asset) = asset.get::<AtlasAsset>() {
+            for (page, mappings) in &asset.page_mappings {
+                if let Some((page_size, image_asset_id)) = asset.pages_image_assets.get(page) {
+                    if let Some(image_id) = image_mapping.resource_by_asset(*image_asset_id) {
+                        let virtual_image_id = renderer.virtual_images.add_named(
+                            path.to_owned(),
+                            VirtualImage::new(VirtualImageSource::Image(image_id)),
+                        );
+                        let virtual_images = cache.atlas_map.entry(id).or_default();
+                        let virtual_image =
+                            renderer.virtual_images.get_mut(virtual_image_id).unwrap();
+                        let mut subimages = Vec::with_capacity(mappings.len());
+                        for (image, region) in mappings {
+                            let uvs = rect(
+                                region.rect.x / page_size.x,
+                                region.rect.y / page_size.y,
+                                region.rect.w / page_size.x,
+                                region.rect.h / page_size.y,
                             );
-                            let virtual_images = cache.atlas_map.entry(*id).or_default();
-                            let virtual_image =
-                                renderer.virtual_images.get_mut(virtual_image_id).unwrap();
-                            let mut subimages = Vec::with_capacity(mappings.len());
-                            for (image, region) in mappings {
-                                let uvs = rect(
-                                    region.rect.x / page_size.x,
-                                    region.rect.y / page_size.y,
-                                    region.rect.w / page_size.x,
-                                    region.rect.h / page_size.y,
-                                );
-                                let image_id = virtual_image.register_named_image_uvs(
-                                    image,
-                                    uvs,
-                                    region.layer,
-                                );
-                                let name = format!("{}@{}", path, image);
-                                subimages.push(name.to_owned());
-                                image_mapping.map_virtual_resource(
-                                    name,
-                                    virtual_image_id,
-                                    image_id,
-                                );
-                            }
-                            virtual_images.insert(virtual_image_id, subimages);
+                            let frame = match region.source_size {
+                                Some(source_size) => {
+                                    let (w, h) = if region.rotated {
+                                        (region.rect.h, region.rect.w)
+                                    } else {
+                                        (region.rect.w, region.rect.h)
+                                    };
+                                    VirtualImageFrame {
+                                        rect: rect(
+                                            region.offset.x / source_size.x,
+                                            region.offset.y / source_size.y,
+                                            w / source_size.x,
+                                            h / source_size.y,
+                                        ),
+                                        rotated: region.rotated,
+                                    }
+                                }
+                                None => VirtualImageFrame {
+                                    rotated: region.rotated,
+                                    ..Default::default()
+                                },
+                            };
+                            let image_id = virtual_image.register_named_image_uvs_with_frame(
+                                image,
+                                uvs,
+                                region.layer,
+                                frame,
+                            );
+                            let name = format!("{}@{}", path, image);
+                            subimages.push(name.to_owned());
+                            image_mapping.map_virtual_resource(name, virtual_image_id, image_id);
                         }
+                        virtual_images.insert(virtual_image_id, subimages);
                     }
                 }
             }
         }
     }
-    for id in assets.lately_unloaded_protocol("atlas") {
-        if let Some(ids) = cache.atlas_map.remove(id) {
-            for (id, names) in ids {
-                renderer.virtual_images.remove(id);
-                for name in names {
-                    image_mapping.unmap_name(&name);
-                }
+}
+
+fn remove_atlas(
+    id: AssetId,
+    renderer: &mut HaRenderer,
+    cache: &mut HaAtlasSystemCache,
+    image_mapping: &mut ImageResourceMapping,
+) {
+    if let Some(ids) = cache.atlas_map.remove(&id) {
+        for (id, names) in ids {
+            renderer.virtual_images.remove(id);
+            for name in names {
+                image_mapping.unmap_name(&name);
             }
         }
     }
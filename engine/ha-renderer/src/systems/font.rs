@@ -55,6 +55,18 @@ pub fn ha_font_system(universe: &mut Universe) {
             cache.fonts_map.remove(&name);
         }
     }
+    let reloaded = assets
+        .lately_reloaded_protocol("font")
+        .filter_map(|id| cache.fonts_table.get(id))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !reloaded.is_empty() {
+        for (_, text) in world.query::<&mut HaTextInstance>().iter() {
+            if reloaded.iter().any(|name| name == text.font()) {
+                text.dirty = true;
+            }
+        }
+    }
 
     for entity in changes.despawned() {
         if let Some(id) = cache.meshes.remove(&entity) {
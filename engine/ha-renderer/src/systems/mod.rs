@@ -4,8 +4,10 @@ pub mod camera_cache;
 pub mod font;
 pub mod immediate_batch;
 pub mod mesh_bounds_gizmo;
+pub mod particle_emitter;
 pub mod render_forward_stage;
 pub mod render_gizmo_stage;
+pub mod render_lighting_stage;
 pub mod render_postprocess_stage;
 pub mod renderer;
 pub mod rig;
@@ -0,0 +1,157 @@
+use crate::{
+    components::{
+        material_instance::HaMaterialInstance,
+        mesh_instance::HaMeshInstance,
+        particle_emitter::{HaParticleEmitter, HaParticleSpace},
+        transform::HaTransform,
+    },
+    constants::material_uniforms::*,
+    ha_renderer::HaRenderer,
+    image::{ImageReference, ImageResourceMapping},
+    material::{
+        common::MaterialValue,
+        domains::surface::{immediate::SurfaceImmediateFactory, SurfaceVertexPTC},
+    },
+    math::*,
+    mesh::{BufferStorage, Mesh, MeshId, MeshReference},
+};
+use core::{
+    app::AppLifeCycle,
+    ecs::{life_cycle::EntityChanges, Comp, Entity, Universe, WorldRef},
+};
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct HaParticleEmitterSystemCache {
+    meshes: HashMap<Entity, MeshId>,
+}
+
+pub type HaParticleEmitterSystemResources<'a> = (
+    WorldRef,
+    &'a mut HaRenderer,
+    &'a AppLifeCycle,
+    &'a EntityChanges,
+    &'a ImageResourceMapping,
+    &'a mut HaParticleEmitterSystemCache,
+    Comp<&'a mut HaParticleEmitter>,
+    Comp<&'a HaTransform>,
+    Comp<&'a mut HaMeshInstance>,
+    Comp<&'a mut HaMaterialInstance>,
+);
+
+pub fn ha_particle_emitter_system(universe: &mut Universe) {
+    let (world, mut renderer, lifecycle, changes, image_mapping, mut cache, ..) =
+        universe.query_resources::<HaParticleEmitterSystemResources>();
+
+    for entity in changes.despawned() {
+        if let Some(id) = cache.meshes.remove(&entity) {
+            let _ = renderer.remove_mesh(id);
+        }
+    }
+
+    let delta_time = lifecycle.delta_time_seconds();
+    let mut factory = SurfaceImmediateFactory::<SurfaceVertexPTC>::default();
+
+    for (entity, (emitter, transform, mesh, material)) in world
+        .query::<(
+            &mut HaParticleEmitter,
+            &HaTransform,
+            &mut HaMeshInstance,
+            &mut HaMaterialInstance,
+        )>()
+        .iter()
+    {
+        let matrix = transform.world_matrix();
+        emitter.simulate(delta_time, &matrix);
+
+        let frames = emitter
+            .frames
+            .iter()
+            .filter_map(|name| {
+                let (owner, id) = image_mapping.virtual_resource_by_name(name)?;
+                let (rect, layer) = renderer.virtual_images.get(owner)?.image_uvs(id)?;
+                Some((owner, id, rect, layer))
+            })
+            .collect::<Vec<_>>();
+        if let Some((owner, id, _, _)) = frames.first() {
+            let reference = ImageReference::VirtualId {
+                owner: *owner,
+                id: *id,
+            };
+            let value = match material.values.get(MAIN_IMAGE_NAME) {
+                Some(MaterialValue::Sampler2dArray { filtering, .. }) => {
+                    MaterialValue::Sampler2dArray {
+                        reference,
+                        filtering: *filtering,
+                    }
+                }
+                Some(MaterialValue::Sampler2d { filtering, .. }) => MaterialValue::Sampler2d {
+                    reference,
+                    filtering: *filtering,
+                },
+                _ => MaterialValue::sampler_2d(reference),
+            };
+            material.values.insert(MAIN_IMAGE_NAME.to_owned(), value);
+        }
+
+        let inverse = if emitter.space == HaParticleSpace::World {
+            Some(matrix.inverted())
+        } else {
+            None
+        };
+        factory.clear();
+        factory.reserve(emitter.particles().len() * 4, emitter.particles().len() * 2);
+        for particle in emitter.particles() {
+            let center = match &inverse {
+                Some(inverse) => Vec2::from(inverse.mul_point(Vec3::from(particle.position))),
+                None => particle.position,
+            };
+            let half_size = emitter.particle_size(particle) * 0.5;
+            let (sin, cos) = particle.rotation.sin_cos();
+            let color = emitter.particle_color(particle);
+            let color = vec4(color.r, color.g, color.b, color.a);
+            let (rect, layer) = frames
+                .get(emitter.particle_frame(particle))
+                .map(|(_, _, rect, layer)| (*rect, *layer as f32))
+                .unwrap_or_else(|| (rect(0.0, 0.0, 1.0, 1.0), 0.0));
+            let vertices = [
+                (vec2(-half_size, -half_size), vec2(0.0, 0.0)),
+                (vec2(half_size, -half_size), vec2(1.0, 0.0)),
+                (vec2(half_size, half_size), vec2(1.0, 1.0)),
+                (vec2(-half_size, half_size), vec2(0.0, 1.0)),
+            ]
+            .map(|(offset, uv)| SurfaceVertexPTC {
+                position: vec3(
+                    center.x + offset.x * cos - offset.y * sin,
+                    center.y + offset.x * sin + offset.y * cos,
+                    0.0,
+                ),
+                texture_coord: vec3(rect.x + uv.x * rect.w, rect.y + uv.y * rect.h, layer),
+                color,
+            });
+            factory.quad(vertices);
+        }
+
+        mesh.reference = MeshReference::None;
+        if let Ok(factory) = factory.factory() {
+            if let Some(id) = cache.meshes.get(&entity) {
+                if let Some(m) = renderer.mesh_mut(*id) {
+                    if factory.write_into(m).is_ok() {
+                        mesh.reference = MeshReference::Id(*id);
+                    }
+                }
+            } else {
+                let mut m = Mesh::new(factory.layout().to_owned());
+                m.set_regenerate_bounds(false);
+                m.set_vertex_storage_all(BufferStorage::Dynamic);
+                m.set_index_storage(BufferStorage::Dynamic);
+                if factory.write_into(&mut m).is_ok() {
+                    if let Ok(id) = renderer.add_mesh(m) {
+                        mesh.reference = MeshReference::Id(id);
+                        cache.meshes.insert(entity, id);
+                    }
+                }
+            }
+        }
+    }
+}
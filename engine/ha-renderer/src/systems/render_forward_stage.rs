@@ -5,17 +5,26 @@ use crate::{
     },
     constants::material_uniforms::*,
     ha_renderer::HaRenderer,
+    material::{common::MaterialSignature, domains::surface::SurfaceInstance, MaterialId},
     math::*,
-    pipeline::render_queue::RenderCommand,
+    mesh::{vertex_factory::VertexType, MeshId, MeshInstances},
+    pipeline::{
+        render_queue::{RenderCommand, RenderQueueAutoRecorder},
+        stage::StageProcessInfo,
+    },
 };
 use core::{
     app::AppLifeCycle,
     ecs::{components::Tag, Comp, Universe, WorldRef},
 };
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
 
 pub type HaRenderForwardStageSystemResources<'a> = (
     WorldRef,
-    &'a HaRenderer,
+    &'a mut HaRenderer,
     &'a AppLifeCycle,
     Comp<&'a mut HaCamera>,
     Comp<&'a Tag>,
@@ -27,8 +36,56 @@ pub type HaRenderForwardStageSystemResources<'a> = (
 
 pub struct RenderForwardStage;
 
+/// Consecutive entities sharing mesh, material and its instance settings, drawn with single
+/// draw call. Only consecutive entities are merged so drawing order stays the same.
+struct ForwardBatch<'a> {
+    mesh_id: MeshId,
+    material_id: MaterialId,
+    /// Hash of material instance settings, compared before settings themselves.
+    key: u64,
+    mesh: &'a HaMeshInstance,
+    material: &'a HaMaterialInstance,
+    instances: Vec<SurfaceInstance>,
+}
+
+impl<'a> ForwardBatch<'a> {
+    fn key(material: &HaMaterialInstance) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        material.middlewares.hash(&mut hasher);
+        // Values order is not stable so their hashes get combined in order-independent way.
+        let values = material
+            .values
+            .iter()
+            .map(|item| {
+                let mut hasher = DefaultHasher::new();
+                item.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0u64, |accum, hash| accum.wrapping_add(hash));
+        values.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn accepts(
+        &self,
+        mesh_id: MeshId,
+        material_id: MaterialId,
+        key: u64,
+        mesh: &HaMeshInstance,
+        material: &HaMaterialInstance,
+    ) -> bool {
+        self.mesh_id == mesh_id
+            && self.material_id == material_id
+            && self.key == key
+            && self.mesh.override_draw_range == mesh.override_draw_range
+            && self.material.override_draw_options == material.override_draw_options
+            && self.material.middlewares == material.middlewares
+            && self.material.values == material.values
+    }
+}
+
 pub fn ha_render_forward_stage_system(universe: &mut Universe) {
-    let (world, renderer, lifecycle, ..) =
+    let (world, mut renderer, lifecycle, ..) =
         universe.query_resources::<HaRenderForwardStageSystemResources>();
 
     let time = vec4(
@@ -37,6 +94,11 @@ pub fn ha_render_forward_stage_system(universe: &mut Universe) {
         lifecycle.time_seconds().fract(),
         0.0,
     );
+    let instance_layout = match SurfaceInstance::vertex_layout() {
+        Ok(layout) => layout,
+        Err(_) => return,
+    };
+    let mut missing_signatures = Vec::<MaterialSignature>::new();
 
     for (_, (visibility, camera, transform)) in world
         .query::<(Option<&HaVisibility>, &HaCamera, &HaTransform)>()
@@ -57,14 +119,15 @@ pub fn ha_render_forward_stage_system(universe: &mut Universe) {
             };
             let mut recorder = render_queue.auto_recorder(None);
 
-            for (transform, mesh, material) in world
-                .query::<(
-                    Option<&Tag>,
-                    Option<&HaVisibility>,
-                    &HaTransform,
-                    &HaMeshInstance,
-                    &HaMaterialInstance,
-                )>()
+            let mut query = world.query::<(
+                Option<&Tag>,
+                Option<&HaVisibility>,
+                &HaTransform,
+                &HaMeshInstance,
+                &HaMaterialInstance,
+            )>();
+            let mut batches = Vec::<ForwardBatch>::new();
+            for (transform, mesh, material) in query
                 .iter()
                 .filter(|(_, (tag, visibility, _, _, _))| {
                     visibility.map(|v| v.0).unwrap_or(true)
@@ -72,7 +135,6 @@ pub fn ha_render_forward_stage_system(universe: &mut Universe) {
                 })
                 .map(|(_, (_, _, transform, mesh, material))| (transform, mesh, material))
             {
-                recorder.next_group();
                 let mesh_id = match mesh.reference.id() {
                     Some(id) => *id,
                     None => continue,
@@ -81,52 +143,128 @@ pub fn ha_render_forward_stage_system(universe: &mut Universe) {
                     Some(id) => *id,
                     None => continue,
                 };
-                let current_mesh = match renderer.mesh(mesh_id) {
+                let instance = SurfaceInstance {
+                    model: transform.world_matrix(),
+                };
+                let key = ForwardBatch::key(material);
+                match batches.last_mut() {
+                    Some(batch) if batch.accepts(mesh_id, material_id, key, mesh, material) => {
+                        batch.instances.push(instance);
+                    }
+                    _ => batches.push(ForwardBatch {
+                        mesh_id,
+                        material_id,
+                        key,
+                        mesh,
+                        material,
+                        instances: vec![instance],
+                    }),
+                }
+            }
+
+            for batch in batches {
+                let current_mesh = match renderer.mesh(batch.mesh_id) {
                     Some(mesh) => mesh,
                     None => continue,
                 };
-                let _ = recorder.record(RenderCommand::ActivateMesh(mesh_id));
-                let signature = info.make_material_signature(current_mesh.layout());
-                let _ = recorder.record(RenderCommand::ActivateMaterial(
-                    material_id,
-                    signature.to_owned(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    MODEL_MATRIX_NAME.into(),
-                    transform.world_matrix().into(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    VIEW_MATRIX_NAME.into(),
-                    info.view_matrix.into(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    PROJECTION_MATRIX_NAME.into(),
-                    info.projection_matrix.into(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    TIME_NAME.into(),
-                    time.into(),
-                ));
-                for (key, value) in &material.values {
-                    let _ = recorder.record(RenderCommand::OverrideUniform(
-                        key.to_owned().into(),
-                        value.to_owned(),
-                    ));
+                let middlewares = batch.material.middlewares.iter().map(|name| name.as_str());
+                let mut instanced = batch.instances.len() > 1;
+                let mut signature = info
+                    .make_material_signature(current_mesh.layout())
+                    .with_middlewares(middlewares.clone());
+                if instanced {
+                    let instanced_signature = info
+                        .make_instanced_material_signature(current_mesh.layout(), &instance_layout)
+                        .with_middlewares(middlewares);
+                    if renderer.has_material_signature(&instanced_signature) {
+                        signature = instanced_signature;
+                    } else {
+                        // Until instanced version gets compiled, instances are drawn one by one.
+                        missing_signatures.push(instanced_signature);
+                        instanced = false;
+                    }
                 }
-                if let Some(draw_options) = &material.override_draw_options {
-                    let _ =
-                        recorder.record(RenderCommand::ApplyDrawOptions(draw_options.to_owned()));
+                if !renderer.has_material_signature(&signature) {
+                    missing_signatures.push(signature);
+                    continue;
                 }
-                let draw_range = mesh
+                let draw_range = batch
+                    .mesh
                     .override_draw_range
                     .as_ref()
                     .cloned()
                     .unwrap_or_default();
-                let _ = recorder.record(RenderCommand::DrawMesh(draw_range));
-                let _ = recorder.record(RenderCommand::ResetUniforms);
+                if instanced {
+                    recorder.next_group();
+                    record_activation(&mut recorder, &batch, signature);
+                    record_uniforms(&mut recorder, &batch, Mat4::identity(), &info, time);
+                    if let Ok(instances) = MeshInstances::new(&batch.instances) {
+                        let _ = recorder
+                            .record(RenderCommand::DrawMeshInstanced(draw_range, instances));
+                    }
+                    let _ = recorder.record(RenderCommand::ResetUniforms);
+                } else {
+                    for instance in &batch.instances {
+                        recorder.next_group();
+                        record_activation(&mut recorder, &batch, signature.to_owned());
+                        record_uniforms(&mut recorder, &batch, instance.model, &info, time);
+                        let _ = recorder.record(RenderCommand::DrawMesh(draw_range.to_owned()));
+                        let _ = recorder.record(RenderCommand::ResetUniforms);
+                    }
+                }
             }
 
             let _ = recorder.record(RenderCommand::SortingBarrier);
         }
     }
+
+    for signature in missing_signatures {
+        renderer.register_material_signature(&signature);
+    }
+}
+
+fn record_activation(
+    recorder: &mut RenderQueueAutoRecorder,
+    batch: &ForwardBatch,
+    signature: MaterialSignature,
+) {
+    let _ = recorder.record(RenderCommand::ActivateMesh(batch.mesh_id));
+    let _ = recorder.record(RenderCommand::ActivateMaterial(
+        batch.material_id,
+        signature,
+    ));
+}
+
+fn record_uniforms(
+    recorder: &mut RenderQueueAutoRecorder,
+    batch: &ForwardBatch,
+    model: Mat4,
+    info: &StageProcessInfo,
+    time: Vec4,
+) {
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        MODEL_MATRIX_NAME.into(),
+        model.into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        VIEW_MATRIX_NAME.into(),
+        info.view_matrix.into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        PROJECTION_MATRIX_NAME.into(),
+        info.projection_matrix.into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        TIME_NAME.into(),
+        time.into(),
+    ));
+    for (key, value) in &batch.material.values {
+        let _ = recorder.record(RenderCommand::OverrideUniform(
+            key.to_owned().into(),
+            value.to_owned(),
+        ));
+    }
+    if let Some(draw_options) = &batch.material.override_draw_options {
+        let _ = recorder.record(RenderCommand::ApplyDrawOptions(draw_options.to_owned()));
+    }
 }
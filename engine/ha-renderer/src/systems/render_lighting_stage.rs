@@ -0,0 +1,629 @@
+use crate::{
+    components::{
+        camera::HaCamera,
+        light_2d::{
+            HaLight2d, HaLight2dKind, HaLighting2d, HaShadowCaster2d, HaShadowCaster2dShape,
+        },
+        material_instance::HaMaterialInstance,
+        mesh_instance::HaMeshInstance,
+        transform::HaTransform,
+        visibility::HaVisibility,
+        volume::HaVolume,
+    },
+    constants::material_uniforms::*,
+    ha_renderer::HaRenderer,
+    image::ImageResourceMapping,
+    material::{
+        common::MaterialSignature, domains::surface::SurfaceVertexP, MaterialResourceMapping,
+    },
+    math::*,
+    mesh::{
+        vertex_factory::{StaticVertexFactory, VertexType},
+        BufferStorage, Mesh, MeshDrawMode, MeshDrawRange, MeshId,
+    },
+    pipeline::{render_queue::RenderCommand, stage::StageProcessInfo},
+};
+use core::{
+    app::AppLifeCycle,
+    ecs::{components::Tag, Comp, Universe, WorldRef},
+    Scalar,
+};
+use std::{cmp::Ordering, ops::Range};
+
+const ANGLE_EPSILON: Scalar = 0.0001;
+const CIRCLE_SEGMENTS: usize = 16;
+const DIRECTIONAL_DISTANCE_FACTOR: Scalar = 10.0;
+
+#[derive(Debug, Clone)]
+pub struct HaRenderLightingStageSystemCache {
+    mesh: Option<MeshId>,
+    material: HaMaterialInstance,
+}
+
+impl Default for HaRenderLightingStageSystemCache {
+    fn default() -> Self {
+        Self {
+            mesh: None,
+            material: HaLighting2d::default_material(),
+        }
+    }
+}
+
+pub type HaRenderLightingStageSystemResources<'a> = (
+    WorldRef,
+    &'a mut HaRenderer,
+    &'a AppLifeCycle,
+    &'a MaterialResourceMapping,
+    &'a ImageResourceMapping,
+    &'a mut HaRenderLightingStageSystemCache,
+    Comp<&'a mut HaCamera>,
+    Comp<&'a mut HaLighting2d>,
+    Comp<&'a Tag>,
+    Comp<&'a HaVisibility>,
+    Comp<&'a HaTransform>,
+    Comp<&'a HaLight2d>,
+    Comp<&'a HaShadowCaster2d>,
+    Comp<&'a HaVolume>,
+    Comp<&'a HaMeshInstance>,
+);
+
+/// Accumulates 2D lights into render target, which cleared color acts as ambient light.
+/// Lights read surface normals from `normalsImage` (rendered by forward stage into `finalNormal`
+/// buffer) and result gets composed with scene color using `@material/graph/screenspace/lighting-2d`
+/// post process material.
+pub struct RenderLightingStage;
+
+struct LightDraw {
+    range: Range<usize>,
+    position: Vec3,
+    direction: Vec3,
+    color: Vec4,
+    radius: Scalar,
+    angle: Scalar,
+    falloff: Scalar,
+    directional: Scalar,
+}
+
+pub fn ha_render_lighting_stage_system(universe: &mut Universe) {
+    type V = SurfaceVertexP;
+
+    let (world, mut renderer, lifecycle, material_mapping, image_mapping, mut cache, ..) =
+        universe.query_resources::<HaRenderLightingStageSystemResources>();
+
+    if world.query::<&HaLight2d>().iter().next().is_none() {
+        return;
+    }
+
+    let layout = match V::vertex_layout() {
+        Ok(layout) => layout,
+        Err(_) => return,
+    };
+
+    let mesh_id = match cache.mesh {
+        Some(mesh_id) => mesh_id,
+        None => {
+            let mut m = Mesh::new(layout.to_owned());
+            m.set_regenerate_bounds(false);
+            m.set_vertex_storage_all(BufferStorage::Dynamic);
+            m.set_index_storage(BufferStorage::Dynamic);
+            match renderer.add_mesh(m) {
+                Ok(mesh_id) => {
+                    cache.mesh = Some(mesh_id);
+                    mesh_id
+                }
+                Err(_) => return,
+            }
+        }
+    };
+
+    cache
+        .material
+        .update_references(&material_mapping, &image_mapping);
+    for (_, lighting) in world.query::<&mut HaLighting2d>().iter() {
+        lighting
+            .material
+            .update_references(&material_mapping, &image_mapping);
+    }
+
+    let segments = shadow_caster_segments(&world, &renderer);
+    let mut positions = vec![];
+    let mut triangles = vec![];
+    let mut batches = vec![];
+    for (_, (visibility, camera, transform)) in world
+        .query::<(Option<&HaVisibility>, &HaCamera, &HaTransform)>()
+        .iter()
+    {
+        if !visibility.map(|v| v.0).unwrap_or(true) {
+            continue;
+        }
+        let iter =
+            match camera.record_to_pipeline_stage::<RenderLightingStage>(&renderer, transform) {
+                Some(iter) => iter,
+                None => continue,
+            };
+        for (info, _) in iter {
+            batches.push(collect_light_draws(
+                &world,
+                &info,
+                &segments,
+                &mut positions,
+                &mut triangles,
+            ));
+        }
+    }
+    if batches.iter().all(|draws| draws.is_empty()) {
+        return;
+    }
+
+    let mut factory = StaticVertexFactory::new(
+        layout.to_owned(),
+        positions.len(),
+        triangles.len(),
+        MeshDrawMode::Triangles,
+    );
+    if factory
+        .vertices_vec3f("position", &positions, None)
+        .is_err()
+        || factory.triangles(&triangles, None).is_err()
+    {
+        return;
+    }
+    match renderer.mesh_mut(mesh_id) {
+        Some(mesh) => {
+            if factory.write_into(mesh).is_err() {
+                return;
+            }
+        }
+        None => return,
+    }
+
+    let time = vec4(
+        lifecycle.time_seconds(),
+        lifecycle.delta_time_seconds(),
+        lifecycle.time_seconds().fract(),
+        0.0,
+    );
+    let mut batches = batches.into_iter();
+    let mut missing_signatures = Vec::<MaterialSignature>::new();
+
+    for (_, (visibility, camera, transform, lighting)) in world
+        .query::<(
+            Option<&HaVisibility>,
+            &HaCamera,
+            &HaTransform,
+            Option<&HaLighting2d>,
+        )>()
+        .iter()
+    {
+        if !visibility.map(|v| v.0).unwrap_or(true) {
+            continue;
+        }
+        let iter =
+            match camera.record_to_pipeline_stage::<RenderLightingStage>(&renderer, transform) {
+                Some(iter) => iter,
+                None => continue,
+            };
+        let material = lighting
+            .map(|lighting| &lighting.material)
+            .unwrap_or(&cache.material);
+        for (info, render_queue) in iter {
+            let draws = match batches.next() {
+                Some(draws) => draws,
+                None => break,
+            };
+            if draws.is_empty() {
+                continue;
+            }
+            let material_id = match material.reference.id().copied() {
+                Some(material_id) => material_id,
+                None => continue,
+            };
+            let mut render_queue = match render_queue.write() {
+                Ok(render_queue) => render_queue,
+                Err(_) => continue,
+            };
+            let signature = info
+                .make_material_signature(&layout)
+                .with_middlewares(material.middlewares.iter().map(|name| name.as_str()));
+            if !renderer.has_material_signature(&signature) {
+                missing_signatures.push(signature);
+                continue;
+            }
+            let mut recorder = render_queue.auto_recorder(None);
+
+            let _ = recorder.record(RenderCommand::ActivateMesh(mesh_id));
+            let _ = recorder.record(RenderCommand::ActivateMaterial(
+                material_id,
+                signature.to_owned(),
+            ));
+            for draw in draws {
+                recorder.next_group();
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    MODEL_MATRIX_NAME.into(),
+                    Mat4::identity().into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    VIEW_MATRIX_NAME.into(),
+                    info.view_matrix.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    PROJECTION_MATRIX_NAME.into(),
+                    info.projection_matrix.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    TIME_NAME.into(),
+                    time.into(),
+                ));
+                for (key, value) in &material.values {
+                    let _ = recorder.record(RenderCommand::OverrideUniform(
+                        key.to_owned().into(),
+                        value.to_owned(),
+                    ));
+                }
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    LIGHT_POSITION_NAME.into(),
+                    draw.position.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    LIGHT_DIRECTION_NAME.into(),
+                    draw.direction.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    LIGHT_COLOR_NAME.into(),
+                    draw.color.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    LIGHT_RADIUS_NAME.into(),
+                    draw.radius.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    LIGHT_ANGLE_NAME.into(),
+                    draw.angle.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    LIGHT_FALLOFF_NAME.into(),
+                    draw.falloff.into(),
+                ));
+                let _ = recorder.record(RenderCommand::OverrideUniform(
+                    LIGHT_DIRECTIONAL_NAME.into(),
+                    draw.directional.into(),
+                ));
+                if let Some(draw_options) = &material.override_draw_options {
+                    let _ =
+                        recorder.record(RenderCommand::ApplyDrawOptions(draw_options.to_owned()));
+                }
+                let _ = recorder.record(RenderCommand::DrawMesh(MeshDrawRange::Range(draw.range)));
+                let _ = recorder.record(RenderCommand::ResetUniforms);
+            }
+            let _ = recorder.record(RenderCommand::SortingBarrier);
+        }
+    }
+
+    for signature in missing_signatures {
+        renderer.register_material_signature(&signature);
+    }
+}
+
+fn collect_light_draws(
+    world: &WorldRef,
+    info: &StageProcessInfo,
+    segments: &[(Vec2, Vec2)],
+    positions: &mut Vec<vek::Vec3<f32>>,
+    triangles: &mut Vec<(u32, u32, u32)>,
+) -> Vec<LightDraw> {
+    let screen_to_world = (info.projection_matrix * info.view_matrix).inverted();
+    let view_points = [
+        screen_to_world.mul_point(Vec3::new(-1.0, -1.0, 0.0)),
+        screen_to_world.mul_point(Vec3::new(1.0, -1.0, 0.0)),
+        screen_to_world.mul_point(Vec3::new(1.0, 1.0, 0.0)),
+        screen_to_world.mul_point(Vec3::new(-1.0, 1.0, 0.0)),
+    ];
+    let view_center = view_points
+        .iter()
+        .fold(Vec2::zero(), |accum, point| accum + Vec2::from(*point))
+        / view_points.len() as Scalar;
+    let view_extent = view_points
+        .iter()
+        .map(|point| Vec2::from(*point).distance(view_center))
+        .fold(0.0, Scalar::max);
+
+    let mut result = vec![];
+    for (_, (tag, visibility, transform, light)) in world
+        .query::<(
+            Option<&Tag>,
+            Option<&HaVisibility>,
+            &HaTransform,
+            &HaLight2d,
+        )>()
+        .iter()
+    {
+        if !visibility.map(|v| v.0).unwrap_or(true)
+            || !tag.map(|t| info.filters.validate_tag(&t.0)).unwrap_or(true)
+        {
+            continue;
+        }
+        let matrix = transform.world_matrix();
+        let position = matrix.mul_point(Vec3::zero());
+        let forward = Vec2::from(matrix.mul_direction(Vec3::unit_x()));
+        let forward = if forward.magnitude_squared() > 0.0 {
+            forward.normalized()
+        } else {
+            Vec2::unit_x()
+        };
+        let (origin, extent) = match light.kind {
+            HaLight2dKind::Directional => {
+                let distance = view_extent * DIRECTIONAL_DISTANCE_FACTOR;
+                (view_center - forward * distance, distance + view_extent)
+            }
+            _ => (Vec2::from(position), light.radius),
+        };
+        if extent <= 0.0 {
+            continue;
+        }
+        let polygon = if light.shadows {
+            light_2d_visibility_polygon(origin, extent, segments)
+        } else {
+            light_2d_visibility_polygon(origin, extent, &[])
+        };
+        let start = triangles.len() * 3;
+        light_2d_triangle_fan(origin, &polygon, position.z, positions, triangles);
+        let end = triangles.len() * 3;
+        if start == end {
+            continue;
+        }
+        let (direction, angle, directional) = match light.kind {
+            HaLight2dKind::Point => (Vec3::from(forward), 0.0, 0.0),
+            HaLight2dKind::Spot(angle) => (Vec3::from(forward), angle, 0.0),
+            HaLight2dKind::Directional => (
+                Vec3::new(forward.x, forward.y, -light.height).normalized(),
+                0.0,
+                1.0,
+            ),
+        };
+        result.push(LightDraw {
+            range: start..end,
+            position: Vec3::new(position.x, position.y, position.z + light.height),
+            direction,
+            color: Vec4::new(
+                light.color.r * light.intensity,
+                light.color.g * light.intensity,
+                light.color.b * light.intensity,
+                light.color.a,
+            ),
+            radius: light.radius,
+            angle,
+            falloff: light.falloff,
+            directional,
+        });
+    }
+    result
+}
+
+fn shadow_caster_segments(world: &WorldRef, renderer: &HaRenderer) -> Vec<(Vec2, Vec2)> {
+    let mut result = vec![];
+    for (_, (visibility, transform, caster, volume, mesh)) in world
+        .query::<(
+            Option<&HaVisibility>,
+            &HaTransform,
+            &HaShadowCaster2d,
+            Option<&HaVolume>,
+            Option<&HaMeshInstance>,
+        )>()
+        .iter()
+    {
+        if !visibility.map(|v| v.0).unwrap_or(true) {
+            continue;
+        }
+        let outline = match &caster.shape {
+            HaShadowCaster2dShape::Volume => match volume {
+                Some(HaVolume::Sphere(radius)) => (0..CIRCLE_SEGMENTS)
+                    .map(|index| {
+                        let angle = std::f64::consts::TAU as Scalar * index as Scalar
+                            / CIRCLE_SEGMENTS as Scalar;
+                        Vec2::new(angle.cos(), angle.sin()) * *radius
+                    })
+                    .collect(),
+                Some(HaVolume::Box(half_extents)) => rectangle_outline(
+                    Vec2::new(-half_extents.x, -half_extents.y),
+                    Vec2::new(half_extents.x, half_extents.y),
+                ),
+                None => continue,
+            },
+            HaShadowCaster2dShape::MeshBounds => {
+                let bounds = match mesh
+                    .and_then(|mesh| mesh.reference.id())
+                    .and_then(|id| renderer.mesh(*id))
+                    .and_then(|mesh| mesh.bounds())
+                {
+                    Some(bounds) => bounds,
+                    None => continue,
+                };
+                let origin = Vec2::from(bounds.origin);
+                let half_extents = Vec2::from(bounds.half_extents());
+                rectangle_outline(origin - half_extents, origin + half_extents)
+            }
+            HaShadowCaster2dShape::Polygon(points) => points.to_owned(),
+        };
+        if outline.len() < 2 {
+            continue;
+        }
+        let matrix = transform.world_matrix();
+        let outline = outline
+            .into_iter()
+            .map(|point| Vec2::from(matrix.mul_point(Vec3::from(point))))
+            .collect::<Vec<_>>();
+        result.extend(
+            outline
+                .iter()
+                .zip(outline.iter().cycle().skip(1))
+                .map(|(from, to)| (*from, *to)),
+        );
+    }
+    result
+}
+
+fn rectangle_outline(from: Vec2, to: Vec2) -> Vec<Vec2> {
+    vec![
+        Vec2::new(from.x, from.y),
+        Vec2::new(to.x, from.y),
+        Vec2::new(to.x, to.y),
+        Vec2::new(from.x, to.y),
+    ]
+}
+
+/// Casts ray and returns distance factor along `direction` to closest hit with segment.
+pub fn ray_segment_intersection(
+    origin: Vec2,
+    direction: Vec2,
+    from: Vec2,
+    to: Vec2,
+) -> Option<Scalar> {
+    let v1 = origin - from;
+    let v2 = to - from;
+    let v3 = Vec2::new(-direction.y, direction.x);
+    let denominator = v2.dot(v3);
+    if denominator.abs() < Scalar::EPSILON {
+        return None;
+    }
+    let t1 = (v2.x * v1.y - v2.y * v1.x) / denominator;
+    let t2 = v1.dot(v3) / denominator;
+    if t1 >= 0.0 && (0.0..=1.0).contains(&t2) {
+        Some(t1)
+    } else {
+        None
+    }
+}
+
+/// Computes polygon of area lit by light at `origin`, bounded by square of given half `extent`,
+/// with points sorted by angle around the origin.
+pub fn light_2d_visibility_polygon(
+    origin: Vec2,
+    extent: Scalar,
+    segments: &[(Vec2, Vec2)],
+) -> Vec<Vec2> {
+    let min = origin - extent;
+    let max = origin + extent;
+    let corners = rectangle_outline(min, max);
+    let boundary = [
+        (corners[0], corners[1]),
+        (corners[1], corners[2]),
+        (corners[2], corners[3]),
+        (corners[3], corners[0]),
+    ];
+    let casters = segments
+        .iter()
+        .copied()
+        .filter(|(from, to)| {
+            from.x.max(to.x) >= min.x
+                && from.x.min(to.x) <= max.x
+                && from.y.max(to.y) >= min.y
+                && from.y.min(to.y) <= max.y
+        })
+        .collect::<Vec<_>>();
+    let inside =
+        |point: &Vec2| point.x >= min.x && point.x <= max.x && point.y >= min.y && point.y <= max.y;
+    let crossings = casters.iter().flat_map(|(from, to)| {
+        boundary.iter().filter_map(move |(a, b)| {
+            let direction = *to - *from;
+            ray_segment_intersection(*from, direction, *a, *b)
+                .filter(|t| *t <= 1.0)
+                .map(|t| *from + direction * t)
+        })
+    });
+    let mut angles = casters
+        .iter()
+        .flat_map(|(from, to)| [*from, *to])
+        .filter(inside)
+        .chain(crossings)
+        .chain(corners.iter().copied())
+        .flat_map(|point| {
+            let diff = point - origin;
+            let angle = diff.y.atan2(diff.x);
+            [angle - ANGLE_EPSILON, angle, angle + ANGLE_EPSILON]
+        })
+        .collect::<Vec<_>>();
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    angles.dedup_by(|a, b| (*a - *b).abs() < Scalar::EPSILON);
+    angles
+        .into_iter()
+        .filter_map(|angle| {
+            let direction = Vec2::new(angle.cos(), angle.sin());
+            casters
+                .iter()
+                .chain(boundary.iter())
+                .filter_map(|(from, to)| ray_segment_intersection(origin, direction, *from, *to))
+                .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .map(|t| origin + direction * t)
+        })
+        .collect()
+}
+
+/// Appends triangle fan covering polygon around `origin` into output buffers.
+pub fn light_2d_triangle_fan(
+    origin: Vec2,
+    polygon: &[Vec2],
+    depth: Scalar,
+    positions: &mut Vec<vek::Vec3<f32>>,
+    triangles: &mut Vec<(u32, u32, u32)>,
+) {
+    if polygon.len() < 2 {
+        return;
+    }
+    let offset = positions.len() as u32;
+    let count = polygon.len() as u32;
+    positions.push(vek::Vec3::new(
+        origin.x as f32,
+        origin.y as f32,
+        depth as f32,
+    ));
+    positions.extend(
+        polygon
+            .iter()
+            .map(|point| vek::Vec3::new(point.x as f32, point.y as f32, depth as f32)),
+    );
+    triangles.extend(
+        (0..count).map(|index| (offset, offset + 1 + index, offset + 1 + (index + 1) % count)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_2d_visibility_polygon() {
+        let polygon = light_2d_visibility_polygon(Vec2::zero(), 10.0, &[]);
+        assert!(!polygon.is_empty());
+        for point in &polygon {
+            let distance = point.x.abs().max(point.y.abs());
+            assert!((distance - 10.0).abs() < 0.001);
+        }
+
+        let wall = (Vec2::new(5.0, -20.0), Vec2::new(5.0, 20.0));
+        let polygon = light_2d_visibility_polygon(Vec2::zero(), 10.0, &[wall]);
+        assert!(polygon.iter().all(|point| point.x <= 5.001));
+        assert!(polygon.iter().any(|point| (point.x - 5.0).abs() < 0.001));
+
+        let block = [
+            (Vec2::new(2.0, -1.0), Vec2::new(2.0, 1.0)),
+            (Vec2::new(2.0, 1.0), Vec2::new(3.0, 1.0)),
+            (Vec2::new(3.0, 1.0), Vec2::new(3.0, -1.0)),
+            (Vec2::new(3.0, -1.0), Vec2::new(2.0, -1.0)),
+        ];
+        let polygon = light_2d_visibility_polygon(Vec2::zero(), 10.0, &block);
+        let shadowed = polygon
+            .iter()
+            .filter(|point| point.x > 0.0 && point.y.abs() < point.x * 0.25)
+            .all(|point| point.x <= 2.001);
+        assert!(shadowed);
+
+        let mut positions = vec![];
+        let mut triangles = vec![];
+        light_2d_triangle_fan(Vec2::zero(), &polygon, 0.0, &mut positions, &mut triangles);
+        assert_eq!(positions.len(), polygon.len() + 1);
+        assert_eq!(triangles.len(), polygon.len());
+        assert!(triangles.iter().all(|(a, b, c)| *a == 0
+            && (*b as usize) < positions.len()
+            && (*c as usize) < positions.len()));
+    }
+}
@@ -12,6 +12,7 @@ use crate::{
     pipeline::{stage::StageQueueSorting, PipelineId},
     render_target::RenderTargetDescriptor,
     resources::material_library::MaterialLibrary,
+    Error,
 };
 use core::{
     app::AppLifeCycle,
@@ -159,6 +160,25 @@ fn sync_image_assets(
             }
         }
     }
+    for id in assets.lately_reloaded_protocol("image") {
+        if let Some(asset) = assets.asset_by_id(*id) {
+            if let Some(asset) = asset.get::<ImageAsset>() {
+                if let Some(image_id) = image_mapping.resource_by_asset(*id) {
+                    if let Some(image) = renderer.image_mut(image_id) {
+                        let result = image.overwrite(
+                            asset.width,
+                            asset.height,
+                            asset.depth,
+                            asset.bytes.to_owned(),
+                        );
+                        if let Err(error) = result {
+                            renderer.report_error(Error::Image(image_id, error));
+                        }
+                    }
+                }
+            }
+        }
+    }
     for id in assets.lately_unloaded_protocol("image") {
         if let Some(image_id) = image_mapping.unmap_asset_resource(*id) {
             let _ = renderer.remove_image(image_id);
@@ -186,6 +206,25 @@ fn sync_mesh_assets(
             }
         }
     }
+    for id in assets.lately_reloaded_protocol("mesh") {
+        if let Some(asset) = assets.asset_by_id(*id) {
+            let path = asset.path();
+            if let Some(asset) = asset.get::<MeshAsset>() {
+                if let Ok(factory) = asset.factory(assets) {
+                    let mut mesh = Mesh::new(factory.layout().to_owned());
+                    if factory.write_into(&mut mesh).is_ok() {
+                        if let Some(mesh_id) = mesh_mapping.resource_by_asset(*id) {
+                            if let Err(error) = renderer.replace_mesh(mesh_id, mesh) {
+                                renderer.report_error(Error::Mesh(mesh_id, error));
+                            }
+                        } else if let Ok(mesh_id) = renderer.add_mesh(mesh) {
+                            mesh_mapping.map_asset_resource(path, *id, mesh_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
     for id in assets.lately_unloaded_protocol("mesh") {
         if let Some(mesh_id) = mesh_mapping.unmap_asset_resource(*id) {
             let _ = renderer.remove_mesh(mesh_id);
@@ -201,63 +240,146 @@ fn sync_material_assets(
     material_mapping: &mut MaterialResourceMapping,
 ) {
     for id in assets.lately_loaded_protocol("material") {
-        if let Some(asset) = assets.asset_by_id(*id) {
-            let path = asset.path();
-            if let Some(asset) = asset.get::<MaterialAsset>() {
-                match asset {
-                    MaterialAsset::Graph {
-                        default_values,
-                        draw_options,
-                        content,
-                    } => {
-                        let mut material = Material::new_graph(content.to_owned());
-                        material.default_values = default_values.to_owned();
-                        material.draw_options = draw_options.to_owned();
-                        if let Ok(material_id) = renderer.add_material(material) {
-                            material_mapping.map_asset_resource(path, *id, material_id);
-                        }
-                    }
-                    MaterialAsset::Domain(graph) => {
-                        cache.material_domain_map.insert(*id, path.to_owned());
-                        material_library.add_domain(path.to_owned(), graph.to_owned());
-                    }
-                    MaterialAsset::Baked {
-                        default_values,
-                        draw_options,
-                        content,
-                    } => {
-                        let baked = content
-                            .iter()
-                            .map(|baked| (baked.signature.to_owned(), baked.baked.to_owned()))
-                            .collect();
-                        let mut material = Material::new_baked(baked);
-                        material.default_values = default_values.to_owned();
-                        material.draw_options = draw_options.to_owned();
-                        if let Ok(material_id) = renderer.add_material(material) {
-                            material_mapping.map_asset_resource(path, *id, material_id);
-                        }
-                    }
-                    MaterialAsset::Function(function) => {
-                        cache
-                            .material_function_map
-                            .insert(*id, function.name.to_owned());
-                        material_library.add_function(function.to_owned());
-                    }
-                    MaterialAsset::None => {}
-                }
+        add_material_asset(
+            *id,
+            renderer,
+            assets,
+            material_library,
+            cache,
+            material_mapping,
+        );
+    }
+    let mut rebake = false;
+    for id in assets.lately_reloaded_protocol("material") {
+        rebake = rebake
+            || cache.material_function_map.contains_key(id)
+            || cache.material_domain_map.contains_key(id);
+        // Material resources get replaced in place to keep their ids, library entries are
+        // registered again since their names might have changed.
+        remove_material_library_entries(*id, material_library, cache);
+        let resource = assets
+            .asset_by_id(*id)
+            .and_then(|asset| asset.get::<MaterialAsset>())
+            .map(|asset| {
+                matches!(
+                    asset,
+                    MaterialAsset::Graph { .. } | MaterialAsset::Baked { .. }
+                )
+            })
+            .unwrap_or_default();
+        if !resource {
+            if let Some(material_id) = material_mapping.unmap_asset_resource(*id) {
+                let _ = renderer.remove_material(material_id);
             }
         }
+        add_material_asset(
+            *id,
+            renderer,
+            assets,
+            material_library,
+            cache,
+            material_mapping,
+        );
+    }
+    if rebake {
+        renderer.rebake_materials();
     }
     for id in assets.lately_unloaded_protocol("material") {
-        if let Some(material_id) = material_mapping.unmap_asset_resource(*id) {
-            let _ = renderer.remove_material(material_id);
-        }
-        if let Some(material_function_id) = cache.material_function_map.remove(id) {
-            material_library.remove_function(&material_function_id);
+        remove_material_asset(*id, renderer, material_library, cache, material_mapping);
+    }
+}
+
+fn add_material_asset(
+    id: AssetId,
+    renderer: &mut HaRenderer,
+    assets: &AssetsDatabase,
+    material_library: &mut MaterialLibrary,
+    cache: &mut HaRendererMaintenanceSystemCache,
+    material_mapping: &mut MaterialResourceMapping,
+) {
+    if let Some(asset) = assets.asset_by_id(id) {
+        let path = asset.path();
+        if let Some(asset) = asset.get::<MaterialAsset>() {
+            match asset {
+                MaterialAsset::Graph {
+                    default_values,
+                    draw_options,
+                    content,
+                } => {
+                    let mut material = Material::new_graph(content.to_owned());
+                    material.default_values = default_values.to_owned();
+                    material.draw_options = draw_options.to_owned();
+                    store_material(id, path, material, renderer, material_mapping);
+                }
+                MaterialAsset::Domain(graph) => {
+                    cache.material_domain_map.insert(id, path.to_owned());
+                    material_library.add_domain(path.to_owned(), graph.to_owned());
+                }
+                MaterialAsset::Baked {
+                    default_values,
+                    draw_options,
+                    content,
+                } => {
+                    let baked = content
+                        .iter()
+                        .map(|baked| (baked.signature.to_owned(), baked.baked.to_owned()))
+                        .collect();
+                    let mut material = Material::new_baked(baked);
+                    material.default_values = default_values.to_owned();
+                    material.draw_options = draw_options.to_owned();
+                    store_material(id, path, material, renderer, material_mapping);
+                }
+                MaterialAsset::Function(function) => {
+                    cache
+                        .material_function_map
+                        .insert(id, function.name.to_owned());
+                    material_library.add_function(function.to_owned());
+                }
+                MaterialAsset::None => {}
+            }
         }
-        if let Some(material_domain_id) = cache.material_domain_map.remove(id) {
-            material_library.remove_domain(&material_domain_id);
+    }
+}
+
+fn remove_material_asset(
+    id: AssetId,
+    renderer: &mut HaRenderer,
+    material_library: &mut MaterialLibrary,
+    cache: &mut HaRendererMaintenanceSystemCache,
+    material_mapping: &mut MaterialResourceMapping,
+) {
+    if let Some(material_id) = material_mapping.unmap_asset_resource(id) {
+        let _ = renderer.remove_material(material_id);
+    }
+    remove_material_library_entries(id, material_library, cache);
+}
+
+fn remove_material_library_entries(
+    id: AssetId,
+    material_library: &mut MaterialLibrary,
+    cache: &mut HaRendererMaintenanceSystemCache,
+) {
+    if let Some(material_function_id) = cache.material_function_map.remove(&id) {
+        material_library.remove_function(&material_function_id);
+    }
+    if let Some(material_domain_id) = cache.material_domain_map.remove(&id) {
+        material_library.remove_domain(&material_domain_id);
+    }
+}
+
+fn store_material(
+    id: AssetId,
+    path: &str,
+    material: Material,
+    renderer: &mut HaRenderer,
+    material_mapping: &mut MaterialResourceMapping,
+) {
+    if let Some(material_id) = material_mapping.resource_by_asset(id) {
+        if let Err(error) = renderer.replace_material(material_id, material) {
+            renderer.report_error(Error::Material(material_id, error));
         }
+    } else if let Ok(material_id) = renderer.add_material(material) {
+        material_mapping.map_asset_resource(path, id, material_id);
     }
 }
 
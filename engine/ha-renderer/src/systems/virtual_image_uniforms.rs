@@ -31,6 +31,7 @@ pub fn ha_virtual_image_uniforms(universe: &mut Universe) {
                 {
                     if let Some(virtual_image) = renderer.virtual_images.get(owner) {
                         if let Some((rect, _)) = virtual_image.image_uvs(image) {
+                            let frame = virtual_image.image_frame(image);
                             material.values.insert(
                                 key.to_owned(),
                                 MaterialValue::Sampler2d {
@@ -46,6 +47,19 @@ pub fn ha_virtual_image_uniforms(universe: &mut Universe) {
                                 format!("{}Size", key),
                                 MaterialValue::Vec2F(vec2(rect.w, rect.h)),
                             );
+                            material.values.insert(
+                                format!("{}Frame", key),
+                                MaterialValue::Vec4F(vec4(
+                                    frame.rect.x,
+                                    frame.rect.y,
+                                    frame.rect.w,
+                                    frame.rect.h,
+                                )),
+                            );
+                            material.values.insert(
+                                format!("{}Rotated", key),
+                                MaterialValue::Scalar(if frame.rotated { 1.0 } else { 0.0 }),
+                            );
                             changed += 1;
                         }
                     }
@@ -2,10 +2,13 @@
 
 use crate::{
     graph_material_function,
+    ha_renderer::HaRenderer,
+    image::VirtualImageFrame,
     material::{common::*, domains::surface::*},
     material_graph,
     math::*,
     mesh::vertex_factory::*,
+    pipeline::stage::StageProcessInfo,
     render_target::*,
     resources::material_library::*,
 };
@@ -327,6 +330,126 @@ fn test_material_middlewares() {
     );
 }
 
+#[test]
+fn test_material_normalmap() {
+    let render_target = RenderTargetDescriptor::simple("finalNormal").unwrap();
+
+    MaterialLibrary::assert_material_compilation(
+        &SurfaceVertexPT::vertex_layout()
+            .unwrap()
+            .with_middlewares(vec!["normalmap".to_owned()]),
+        render_target,
+        &surface_flat_domain_graph(),
+        &default_surface_flat_texture_2d_material_graph(),
+    );
+
+    MaterialLibrary::assert_material_compilation(
+        &SurfaceVertexP::vertex_layout().unwrap(),
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &default_surface_flat_light_2d_material_graph(),
+    );
+}
+
+#[test]
+fn test_material_instance_middlewares() {
+    let library = MaterialLibrary::default();
+    let domain = surface_flat_domain_graph();
+    let graph = default_surface_flat_texture_2d_material_graph();
+    let vertex_layout = SurfaceVertexPT::vertex_layout().unwrap();
+    let info = StageProcessInfo {
+        x: 0,
+        y: 0,
+        width: 1,
+        height: 1,
+        transform_matrix: Mat4::identity(),
+        view_matrix: Mat4::identity(),
+        projection_matrix: Mat4::identity(),
+        material_render_target_signature: unsafe {
+            MaterialRenderTargetSignature::from_raw(vec!["finalNormal".to_owned()])
+        },
+        domain: Some("surface".to_owned()),
+        filters: Default::default(),
+    };
+
+    // Stages extend mesh signature with middlewares requested by material instance, and such
+    // signature has to be registered in renderer to get its material version compiled.
+    let base = info.make_material_signature(&vertex_layout);
+    let extended = info
+        .make_material_signature(&vertex_layout)
+        .with_middlewares(["normalmap"]);
+    assert_ne!(base, extended);
+    assert_eq!(
+        extended.middlewares().parts().collect::<Vec<_>>(),
+        vec!["normalmap"]
+    );
+    let mut renderer = HaRenderer::new(());
+    assert!(!renderer.has_material_signature(&extended));
+    assert!(renderer.register_material_signature(&extended));
+    assert!(!renderer.register_material_signature(&extended));
+
+    let baked = graph
+        .bake(&base, Some(&domain), &library, true)
+        .unwrap()
+        .unwrap();
+    assert!(!baked.fragment.contains("normalImage"));
+    let baked = graph
+        .bake(&extended, Some(&domain), &library, true)
+        .unwrap()
+        .unwrap();
+    println!("* VS extended:\n{}", baked.vertex);
+    println!("* FS extended:\n{}", baked.fragment);
+    assert!(baked.fragment.contains("normalImage"));
+}
+
+#[test]
+fn test_material_instancing() {
+    let vertex_layout = SurfaceVertexPT::vertex_layout().unwrap();
+    let instance_layout = SurfaceInstance::vertex_layout().unwrap();
+    let signature = MaterialMeshSignature::new_instanced(&vertex_layout, &instance_layout);
+    assert_eq!(
+        signature.sources().collect::<Vec<_>>(),
+        vec![("position", 0), ("textureCoord", 1), ("instanceModel", 2)]
+    );
+
+    MaterialLibrary::assert_material_compilation(
+        &vertex_layout.with(instance_layout).unwrap(),
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &default_surface_flat_texture_2d_material_graph(),
+    );
+}
+
+#[test]
+fn test_virtual_image_frame() {
+    let frame = VirtualImageFrame::default();
+    assert_eq!(frame.region_coord(vec2(0.25, 0.75)), vec2(0.25, 0.75));
+
+    let frame = VirtualImageFrame {
+        rect: rect(0.25, 0.5, 0.5, 0.25),
+        rotated: false,
+    };
+    assert_eq!(frame.region_coord(vec2(0.25, 0.5)), vec2(0.0, 0.0));
+    assert_eq!(frame.region_coord(vec2(0.75, 0.75)), vec2(1.0, 1.0));
+    assert_eq!(frame.region_coord(vec2(0.5, 0.625)), vec2(0.5, 0.5));
+
+    let frame = VirtualImageFrame {
+        rect: rect(0.0, 0.0, 1.0, 1.0),
+        rotated: true,
+    };
+    assert_eq!(frame.region_coord(vec2(0.0, 0.0)), vec2(1.0, 0.0));
+    assert_eq!(frame.region_coord(vec2(1.0, 0.0)), vec2(1.0, 1.0));
+    assert_eq!(frame.region_coord(vec2(1.0, 1.0)), vec2(0.0, 1.0));
+    assert_eq!(frame.region_coord(vec2(0.0, 1.0)), vec2(0.0, 0.0));
+
+    MaterialLibrary::assert_material_compilation(
+        &SurfaceVertexPT::vertex_layout().unwrap(),
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &default_surface_flat_virtual_uniform_texture_2d_material_graph(),
+    );
+}
+
 #[test]
 fn test_compound_vertex_type() {
     println!(
@@ -0,0 +1,183 @@
+use crate::pipeline::{AssetDiagnostic, AssetInput, AssetPhase, AssetStep, Pipeline};
+use serde_json::Value;
+use std::{
+    collections::BTreeSet,
+    io::{Error, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Validates asset descriptors without touching output directories and network:
+/// parses descriptors, checks sources, plugins and their params and detects target collisions.
+pub fn check_pipeline(
+    source: &Path,
+    intermediate: &Path,
+    baked: &Path,
+    tags: &[String],
+) -> std::io::Result<()> {
+    let mut steps = vec![];
+    let mut diagnostics = vec![];
+    for (phase, target) in [
+        (AssetPhase::SourceToIntermediate, intermediate),
+        (AssetPhase::SourceToBaked, baked),
+        // Intermediate directory mirrors source one, so these descriptors are checked in place.
+        (AssetPhase::IntermediateToBaked, baked),
+    ] {
+        AssetInput::collect_steps(
+            phase,
+            source,
+            target,
+            "",
+            tags,
+            &mut steps,
+            &mut diagnostics,
+        )?;
+    }
+
+    for step in &steps {
+        // Sources of intermediate assets might be produced by other steps.
+        for path in &step.source {
+            if step.phase != AssetPhase::IntermediateToBaked && !path.exists() {
+                diagnostics.push(AssetDiagnostic {
+                    path: step.descriptor.to_owned(),
+                    message: format!("Source does not exist: {:?}", path),
+                });
+            }
+        }
+        check_pipeline_definition(&step.pipeline, &step.descriptor, &mut diagnostics);
+    }
+    check_collisions(&steps, &mut diagnostics);
+
+    println!("* Assets that would be produced:");
+    for step in &steps {
+        println!("  - {:?} {:?}:", step.phase, step.descriptor);
+        for path in step.outputs() {
+            println!("    - {:?}", path);
+        }
+    }
+
+    let diagnostics = diagnostics.into_iter().collect::<BTreeSet<_>>();
+    if diagnostics.is_empty() {
+        println!("* Checked {} steps. No problems found", steps.len());
+        return Ok(());
+    }
+    println!("* Problems found:");
+    for diagnostic in &diagnostics {
+        println!("  - {}", diagnostic);
+    }
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!("Asset pipeline check found problems: {}", diagnostics.len()),
+    ))
+}
+
+fn check_pipeline_definition(
+    pipeline: &Pipeline,
+    descriptor: &Path,
+    diagnostics: &mut Vec<AssetDiagnostic>,
+) {
+    match pipeline {
+        Pipeline::Generate(asset) => {
+            check_pipeline_definition(&asset.pipeline, descriptor, diagnostics)
+        }
+        Pipeline::Plugin {
+            name,
+            params,
+            do_not_verify,
+        } => {
+            if which::which(name).is_err() {
+                if !do_not_verify {
+                    diagnostics.push(AssetDiagnostic {
+                        path: descriptor.to_owned(),
+                        message: format!("Plugin not found: {}", name),
+                    });
+                }
+                return;
+            }
+            if let Err(error) = validate_plugin_params(name, params) {
+                diagnostics.push(AssetDiagnostic {
+                    path: descriptor.to_owned(),
+                    message: format!("Invalid params for plugin: {}. {}", name, error),
+                });
+            }
+        }
+        Pipeline::Shell { command } => {
+            let program = command
+                .split(char::is_whitespace)
+                .next()
+                .unwrap_or_default();
+            if which::which(program).is_err() {
+                diagnostics.push(AssetDiagnostic {
+                    path: descriptor.to_owned(),
+                    message: format!("Shell command program not found: {}", program),
+                });
+            }
+        }
+        Pipeline::Copy | Pipeline::Pack { .. } => {}
+    }
+}
+
+/// Asks plugin to deserialize params into its own params type.
+fn validate_plugin_params(name: &str, params: &Value) -> Result<(), String> {
+    let mut child = Command::new(name)
+        .args(["--", "--", "--", "validate"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| format!("Could not run plugin: {}", error))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let params = serde_json::to_string(params)
+            .map_err(|error| format!("Could not serialize params: {}", error))?;
+        stdin
+            .write_all(params.as_bytes())
+            .map_err(|error| format!("Could not send params: {}", error))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|error| format!("Could not wait for plugin: {}", error))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Plugin does not support params validation or failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    serde_json::from_slice::<Result<(), String>>(&output.stdout)
+        .map_err(|error| format!("Could not read validation result: {}", error))?
+}
+
+/// Targets collide when they are the same path or when one of them is placed inside directory
+/// owned entirely by plugin or shell command.
+fn check_collisions(steps: &[AssetStep], diagnostics: &mut Vec<AssetDiagnostic>) {
+    let outputs = steps
+        .iter()
+        .flat_map(|step| {
+            let exclusive = matches!(
+                step.pipeline,
+                Pipeline::Plugin { .. } | Pipeline::Shell { .. }
+            );
+            step.outputs()
+                .into_iter()
+                .map(move |path| (path, &step.descriptor, exclusive))
+        })
+        .collect::<Vec<(PathBuf, &PathBuf, bool)>>();
+    for (index, (path, descriptor, exclusive)) in outputs.iter().enumerate() {
+        for (other_path, other_descriptor, other_exclusive) in &outputs[(index + 1)..] {
+            if descriptor == other_descriptor {
+                continue;
+            }
+            if path == other_path
+                || (*exclusive && other_path.starts_with(path))
+                || (*other_exclusive && path.starts_with(other_path))
+            {
+                diagnostics.push(AssetDiagnostic {
+                    path: (*descriptor).to_owned(),
+                    message: format!(
+                        "Target {:?} collides with target {:?} of: {:?}",
+                        path, other_path, other_descriptor
+                    ),
+                });
+            }
+        }
+    }
+}
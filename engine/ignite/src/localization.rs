@@ -0,0 +1,189 @@
+use oxygengine_core::{
+    assets::protocols::localization::{LocalizationAsset, LocalizationTableAsset},
+    localization::Localization,
+};
+use std::{
+    collections::BTreeSet,
+    fs::{create_dir_all, read_to_string, write},
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+pub enum LocalizationTemplateFormat {
+    #[default]
+    Po,
+    Csv,
+}
+
+fn read_localization_file(path: &Path) -> Result<Vec<LocalizationAsset>, Error> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+    let result = match extension.as_deref() {
+        // Other JSON assets can live next to localization files, so these are only skipped.
+        Some("json") => match serde_json::from_str::<LocalizationAsset>(&read_to_string(path)?) {
+            Ok(asset) => Ok(vec![asset]),
+            Err(error) => {
+                println!(
+                    "* Skip JSON file that is not localization asset: {:?}: {}",
+                    path, error
+                );
+                Ok(vec![])
+            }
+        },
+        Some("po") => LocalizationAsset::from_po(&read_to_string(path)?).map(|asset| vec![asset]),
+        Some("csv") => {
+            LocalizationTableAsset::from_csv(&read_to_string(path)?).map(|table| table.languages)
+        }
+        _ => return Ok(vec![]),
+    };
+    result.map_err(|error| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Could not read localization file {:?}: {}", path, error),
+        )
+    })
+}
+
+pub fn read_localization<P: AsRef<Path>>(paths: &[P]) -> Result<Localization, Error> {
+    let mut result = Localization::default();
+    for path in paths {
+        for entry in ignore::WalkBuilder::new(path.as_ref()).build() {
+            let path = match entry {
+                Ok(entry) if entry.path().is_file() => entry.into_path(),
+                _ => continue,
+            };
+            for asset in read_localization_file(&path)? {
+                println!(
+                    "* Read {} texts of language: {:?} from: {:?}",
+                    asset.dictionary.len(),
+                    asset.language,
+                    path
+                );
+                for (id, text) in &asset.dictionary {
+                    result.add_text(id, &asset.language, text);
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Writes translation templates with texts missing in languages, using `source_language` texts
+/// (or language with most texts if not set) as translation source.
+pub fn write_localization_templates(
+    localization: &Localization,
+    output: impl AsRef<Path>,
+    format: LocalizationTemplateFormat,
+    source_language: Option<&str>,
+    languages: &[String],
+) -> Result<(), Error> {
+    let output = output.as_ref();
+    let source_language = match source_language {
+        Some(language) => language.to_owned(),
+        None => localization
+            .languages()
+            .into_iter()
+            .max_by_key(|language| localization.language_asset(language).dictionary.len())
+            .map(|language| language.to_owned())
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "There are no localization texts"))?,
+    };
+    let languages = if languages.is_empty() {
+        localization
+            .languages()
+            .into_iter()
+            .filter(|language| *language != source_language)
+            .map(|language| language.to_owned())
+            .collect::<Vec<_>>()
+    } else {
+        languages.to_vec()
+    };
+    let source = localization.language_asset(&source_language);
+    create_dir_all(output)?;
+    match format {
+        LocalizationTemplateFormat::Po => {
+            for language in &languages {
+                let missing = localization.missing_text_ids(language);
+                let path = output.join(format!("{}.po", language));
+                let asset = LocalizationAsset {
+                    language: language.to_owned(),
+                    ..Default::default()
+                };
+                write(&path, asset.to_po(missing.iter().copied(), Some(&source)))?;
+                println!(
+                    "* Write {} missing texts of language: {:?} to: {:?}",
+                    missing.len(),
+                    language,
+                    path
+                );
+            }
+        }
+        LocalizationTemplateFormat::Csv => {
+            let missing = languages
+                .iter()
+                .flat_map(|language| localization.missing_text_ids(language))
+                .collect::<BTreeSet<_>>();
+            let table = LocalizationTableAsset {
+                languages: std::iter::once(source_language.as_str())
+                    .chain(languages.iter().map(|language| language.as_str()))
+                    .map(|language| {
+                        let mut asset = localization.language_asset(language);
+                        asset
+                            .dictionary
+                            .retain(|id, _| missing.contains(id.as_str()));
+                        if language == source_language {
+                            for id in &missing {
+                                asset.dictionary.entry(id.to_string()).or_default();
+                            }
+                        }
+                        asset
+                    })
+                    .collect(),
+            };
+            let path = output.join("missing.csv");
+            write(&path, table.to_csv())?;
+            println!(
+                "* Write {} missing texts of languages: {:?} to: {:?}",
+                missing.len(),
+                languages,
+                path
+            );
+        }
+    }
+    println!("Done! localization templates written to: {:?}", output);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::remove_dir_all;
+
+    #[test]
+    fn test_read_localization() {
+        let root = std::env::temp_dir().join(format!(
+            "oxygengine-ignite-localization-{}",
+            std::process::id()
+        ));
+        let _ = remove_dir_all(&root);
+        create_dir_all(&root).unwrap();
+        write(
+            root.join("en.json"),
+            r#"{"language":"en","dictionary":{"hello":"Hello"}}"#,
+        )
+        .unwrap();
+        write(root.join("level.json"), r#"{"width":10}"#).unwrap();
+        write(root.join("image.png"), [0x89, 0x50, 0x4E, 0x47, 0xFF, 0xFE]).unwrap();
+
+        let localization = read_localization(&[&root]).unwrap();
+        assert_eq!(localization.text("hello", "en"), Some("Hello"));
+        assert_eq!(
+            localization.languages().into_iter().collect::<Vec<_>>(),
+            vec!["en"]
+        );
+
+        let _ = remove_dir_all(&root);
+    }
+}
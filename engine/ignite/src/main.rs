@@ -1,15 +1,21 @@
 mod build;
+mod check;
+mod localization;
 mod pack;
 mod pipeline;
 
 use crate::{
-    build::build_project, pack::pack_assets_and_write_to_file, pipeline::AssetInput,
-    pipeline::AssetPhase,
+    build::build_project,
+    check::check_pipeline,
+    localization::{read_localization, write_localization_templates, LocalizationTemplateFormat},
+    pack::{pack_assets_and_write_to_file, parse_pack_compression, PackCompression},
+    pipeline::execute_pipeline,
 };
 use cargo_metadata::MetadataCommand;
 use clap::{Parser, Subcommand};
 use dirs::home_dir;
 use hotwatch::{Event, Hotwatch};
+use oxygengine_core::assets::protocols::pack::PackArchive;
 use serde::Deserialize;
 use std::{
     collections::HashMap,
@@ -19,7 +25,7 @@ use std::{
     path::{Path, PathBuf},
     process::Command,
     sync::{Arc, Condvar, Mutex},
-    thread::spawn,
+    thread::{available_parallelism, spawn},
     time::Instant,
 };
 
@@ -52,6 +58,32 @@ enum Commands {
         /// Assets pack output file.
         #[arg(short, long, value_name = "PATH")]
         output: PathBuf,
+        /// Compression applied to every packed file.
+        #[arg(short, long, value_parser = parse_pack_compression, default_value = "none")]
+        compression: PackCompression,
+    },
+    /// Extract texts missing in languages and write translation templates.
+    Localization {
+        /// Localization asset files (`.json`, `.po` or `.csv`) or folders that contain them.
+        #[arg(short, long, value_name = "PATH")]
+        input: Vec<PathBuf>,
+        /// Translation templates output folder.
+        #[arg(
+            short,
+            long,
+            value_name = "PATH",
+            default_value = "./localization-templates/"
+        )]
+        output: PathBuf,
+        /// Translation templates format.
+        #[arg(short, long, value_enum, default_value_t = LocalizationTemplateFormat::Po)]
+        format: LocalizationTemplateFormat,
+        /// Language used as translation source, language with most texts if not set.
+        #[arg(short, long, value_name = "NAME")]
+        source_language: Option<String>,
+        /// Languages to write templates for, all found languages if not set.
+        #[arg(short, long, value_name = "NAME")]
+        language: Vec<String>,
     },
     /// Execute asset pipeline.
     Pipeline {
@@ -67,6 +99,15 @@ enum Commands {
         /// Tags to use for assets being processed.
         #[arg(short, long, value_name = "NAME")]
         tag: Vec<String>,
+        /// Ignore cached results and rebuild all assets from scratch.
+        #[arg(long)]
+        clean: bool,
+        /// Number of steps executed in parallel. Defaults to available CPU cores.
+        #[arg(short, long, value_name = "NUMBER")]
+        jobs: Option<usize>,
+        /// Only validate assets descriptors and list what would be produced.
+        #[arg(long)]
+        check: bool,
     },
     /// Build project.
     Build {
@@ -275,10 +316,17 @@ async fn main() -> Result<()> {
                 .unwrap_or_else(|_| panic!("Could not get bytes from {:?} response", url));
             bytes.as_ref().to_owned()
         };
-        let files = bincode::deserialize::<HashMap<String, Vec<u8>>>(&bytes)
-            .unwrap_or_else(|_| panic!("Could not unpack files from presets pack"));
+        let archive = PackArchive::from_bytes(bytes).unwrap_or_else(|error| {
+            panic!("Could not unpack files from presets pack: {:?}", error)
+        });
         let _ = create_dir_all(&presets_path);
-        for (fname, bytes) in files {
+        for fname in archive.paths() {
+            let bytes = archive.read(fname).unwrap_or_else(|error| {
+                panic!(
+                    "Could not unpack file: {:?} from presets pack: {:?}",
+                    fname, error
+                )
+            });
             let path = presets_path.join(fname);
             let mut dir_path = path.clone();
             dir_path.pop();
@@ -344,33 +392,47 @@ async fn main() -> Result<()> {
             copy_dir(&preset_path, &destination, &id, true)?;
             println!("Done!");
         }
-        Commands::Pack { input, output } => {
-            pack_assets_and_write_to_file(&input, output)?;
+        Commands::Pack {
+            input,
+            output,
+            compression,
+        } => {
+            pack_assets_and_write_to_file(&input, output, compression)?;
+        }
+        Commands::Localization {
+            input,
+            output,
+            format,
+            source_language,
+            language,
+        } => {
+            let localization = read_localization(&input)?;
+            write_localization_templates(
+                &localization,
+                output,
+                format,
+                source_language.as_deref(),
+                &language,
+            )?;
         }
         Commands::Pipeline {
             source,
             intermediate,
             baked,
             tag,
+            clean,
+            jobs,
+            check,
         } => {
-            let _ = remove_dir_all(&intermediate);
-            let _ = remove_dir_all(&baked);
-            AssetInput::load_and_execute(
-                AssetPhase::SourceToIntermediate,
-                &source,
-                &intermediate,
-                "",
-                &tag,
-            )?;
-            AssetInput::bake_assets_list(&intermediate, "")?;
-            AssetInput::load_and_execute(
-                AssetPhase::IntermediateToBaked,
-                &intermediate,
-                &baked,
-                "",
-                &tag,
-            )?;
-            AssetInput::load_and_execute(AssetPhase::SourceToBaked, &source, &baked, "", &tag)?;
+            if check {
+                return check_pipeline(&source, &intermediate, &baked, &tag);
+            }
+            let jobs = jobs.unwrap_or_else(|| {
+                available_parallelism()
+                    .map(|count| count.get())
+                    .unwrap_or(1)
+            });
+            execute_pipeline(&source, &intermediate, &baked, &tag, clean, jobs)?;
         }
         Commands::Build {
             profile,
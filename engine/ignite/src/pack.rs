@@ -1,11 +1,33 @@
+pub use oxygengine_core::assets::protocols::pack::PackCompression;
+use oxygengine_core::assets::protocols::pack::{PackError, PackWriter};
 use std::{
-    collections::HashMap,
-    fs::{read, write},
-    io::{Error, ErrorKind},
+    collections::BTreeMap,
+    fs::{read, File},
+    io::{BufWriter, Error, ErrorKind},
     path::Path,
 };
 
-pub fn pack_assets<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<u8>, Error> {
+/// Parses pack compression from command line argument.
+pub fn parse_pack_compression(value: &str) -> Result<PackCompression, String> {
+    match value.to_lowercase().as_str() {
+        "none" => Ok(PackCompression::None),
+        "lz4" => Ok(PackCompression::Lz4),
+        "zstd" => Ok(PackCompression::Zstd),
+        _ => Err(format!(
+            "Unknown pack compression: {:?}, expected one of: none, lz4, zstd",
+            value
+        )),
+    }
+}
+
+fn pack_error(error: PackError) -> Error {
+    Error::new(ErrorKind::Other, format!("{:?}", error))
+}
+
+pub fn pack_assets<P: AsRef<Path>>(
+    paths: &[P],
+    compression: PackCompression,
+) -> Result<PackWriter, Error> {
     let files = paths
         .iter()
         .flat_map(|path| {
@@ -17,29 +39,76 @@ pub fn pack_assets<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<u8>, Error> {
                     .ok()
                     .map(|entry| entry.path().to_path_buf())
                     .filter(|p| p.is_file())
-                    .and_then(|p| read(&p).ok().map(|c| (p, c)))
-                    .and_then(|(p, c)| pathdiff::diff_paths(p, path).map(|p| (p, c)))
-                    .and_then(|(p, c)| p.to_str().map(|p| (p.to_owned(), c)))
-                    .map(|(p, c)| {
-                        let name = p.replace("\\\\", "/").replace('\\', "/");
-                        println!("* Include file: {:?} as: {:?}", path.join(p), name);
-                        (name, c)
-                    })
+                    .and_then(|p| pathdiff::diff_paths(&p, path).map(|n| (n, p)))
+                    .and_then(|(n, p)| n.to_str().map(|n| (n.to_owned(), p)))
+                    .map(|(n, p)| (n.replace("\\\\", "/").replace('\\', "/"), p))
             })
         })
-        .collect::<HashMap<String, Vec<u8>>>();
-    match bincode::serialize(&files) {
-        Ok(contents) => Ok(contents),
-        Err(error) => Err(Error::new(ErrorKind::Other, error.to_string())),
+        .collect::<BTreeMap<_, _>>();
+    let mut writer = PackWriter::default();
+    for (name, path) in files {
+        println!("* Include file: {:?} as: {:?}", path, name);
+        let contents = read(&path)?;
+        // Zstd compression is not available at runtime, so we compress it here.
+        if compression == PackCompression::Zstd {
+            let stored = zstd::encode_all(contents.as_slice(), 0)?;
+            writer.add_compressed(name, &contents, stored, compression);
+        } else {
+            writer
+                .add(name, &contents, compression)
+                .map_err(pack_error)?;
+        }
     }
+    Ok(writer)
 }
 
 pub fn pack_assets_and_write_to_file(
     paths: &[impl AsRef<Path>],
     output: impl AsRef<Path>,
+    compression: PackCompression,
 ) -> Result<(), Error> {
-    let contents = pack_assets(paths)?;
-    write(output.as_ref(), contents)?;
+    let writer = pack_assets(paths, compression)?;
+    let mut file = BufWriter::new(File::create(output.as_ref())?);
+    writer.write_into(&mut file).map_err(pack_error)?;
     println!("Done! packed to file: {:?}", output.as_ref());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxygengine_core::assets::protocols::pack::PackArchive;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    #[test]
+    fn test_pack() {
+        let root =
+            std::env::temp_dir().join(format!("oxygengine-ignite-pack-{}", std::process::id()));
+        let _ = remove_dir_all(&root);
+        create_dir_all(root.join("assets/sub")).unwrap();
+        write(root.join("assets/a.txt"), "a").unwrap();
+        write(root.join("assets/sub/b.txt"), "b".repeat(1024)).unwrap();
+
+        for compression in [
+            PackCompression::None,
+            PackCompression::Lz4,
+            PackCompression::Zstd,
+        ] {
+            let output = root.join("assets.pack");
+            pack_assets_and_write_to_file(&[root.join("assets")], &output, compression).unwrap();
+            let archive = PackArchive::from_reader(File::open(&output).unwrap()).unwrap();
+            assert_eq!(
+                archive.paths().collect::<Vec<_>>(),
+                vec!["a.txt", "sub/b.txt"]
+            );
+            assert_eq!(archive.read("a.txt").unwrap().as_ref(), b"a");
+            assert_eq!(
+                archive.read("sub/b.txt").unwrap().as_ref(),
+                "b".repeat(1024).as_bytes()
+            );
+            assert_eq!(archive.entry("sub/b.txt").unwrap().compression, compression);
+        }
+
+        let _ = remove_dir_all(&root);
+    }
+}
@@ -1,10 +1,20 @@
+use crate::pack::PackCompression;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
-    fs::{create_dir_all, read_dir, read_to_string, write},
+    collections::{BTreeMap, HashSet},
+    fs::{
+        create_dir_all, metadata, read, read_dir, read_to_string, remove_dir_all, remove_file,
+        write,
+    },
+    hash::Hasher,
     io::{Error, ErrorKind, Write},
+    panic::{catch_unwind, AssertUnwindSafe},
     path::{Path, PathBuf},
     process::{Command, Stdio},
+    sync::Mutex,
+    thread::scope,
+    time::{Duration, Instant, UNIX_EPOCH},
 };
 
 #[allow(clippy::trivially_copy_pass_by_ref)]
@@ -109,22 +119,24 @@ impl AssetInput {
         Ok(())
     }
 
-    pub fn load_and_execute(
+    pub fn collect_steps(
         phase: AssetPhase,
         source: impl AsRef<Path>,
         target: impl AsRef<Path>,
         assets: &str,
         tags: &[String],
+        result: &mut Vec<AssetStep>,
+        diagnostics: &mut Vec<AssetDiagnostic>,
     ) -> std::io::Result<()> {
-        let mut source = source.as_ref().to_owned();
+        let descriptor = source.as_ref().to_owned();
         let target = target.as_ref().to_owned();
-        if source.is_file()
-            && source
+        if descriptor.is_file()
+            && descriptor
                 .extension()
                 .map(|extension| extension == "asset")
                 .unwrap_or_default()
         {
-            let contents = read_to_string(&source)?;
+            let contents = read_to_string(&descriptor)?;
             match serde_json::from_str::<AssetInput>(&contents) {
                 Ok(asset) => {
                     if !asset.target.is_empty() || asset.ignore || asset.phase != phase {
@@ -139,39 +151,29 @@ impl AssetInput {
                             return Ok(());
                         }
                     }
-                    asset.pipeline.verify_used_plugins();
-                    if source.is_file() {
-                        source.pop();
-                    }
+                    let mut directory = descriptor.to_owned();
+                    directory.pop();
                     let source = asset
                         .source
                         .iter()
-                        .map(|path| source.join(path))
+                        .map(|path| directory.join(path))
                         .collect::<Vec<_>>();
-                    let directory = target.with_extension("");
-                    let assets = assets.strip_suffix(".asset").unwrap_or(assets);
-                    let result = asset.pipeline.execute(&source, directory, assets)?;
-                    if !result.is_empty() {
-                        match serde_json::to_string_pretty(&AssetOutput { target: result }) {
-                            Ok(contents) => {
-                                write(target, contents)?;
-                            }
-                            Err(error) => {
-                                println!(
-                                    "Could not serialize meta asset JSON config: {:?}. Error: {:?}",
-                                    source, error
-                                );
-                            }
-                        }
-                    }
+                    result.push(AssetStep {
+                        phase,
+                        descriptor,
+                        source,
+                        target,
+                        assets: assets.strip_suffix(".asset").unwrap_or(assets).to_owned(),
+                        pipeline: asset.pipeline,
+                    });
                 }
-                Err(error) => println!(
-                    "Could not parse pipeline asset JSON config: {:?}. Error: {:?}",
-                    source, error
-                ),
+                Err(error) => diagnostics.push(AssetDiagnostic {
+                    path: descriptor,
+                    message: format!("Could not parse pipeline asset JSON config: {}", error),
+                }),
             }
-        } else if source.is_dir() {
-            for entry in read_dir(source)? {
+        } else if descriptor.is_dir() {
+            for entry in read_dir(descriptor)? {
                 let entry = entry?;
                 let source = entry.path();
                 let target = target.join(entry.file_name());
@@ -184,13 +186,466 @@ impl AssetInput {
                         entry.file_name().to_string_lossy().as_ref()
                     )
                 };
-                Self::load_and_execute(phase, source, target, &assets, tags)?;
+                Self::collect_steps(phase, source, target, &assets, tags, result, diagnostics)?;
             }
         }
         Ok(())
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AssetDiagnostic {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl std::fmt::Display for AssetDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.path, self.message)
+    }
+}
+
+/// Single pipeline run of asset descriptor.
+#[derive(Debug, Clone)]
+pub struct AssetStep {
+    pub phase: AssetPhase,
+    pub descriptor: PathBuf,
+    pub source: Vec<PathBuf>,
+    pub target: PathBuf,
+    pub assets: String,
+    pub pipeline: Pipeline,
+}
+
+impl AssetStep {
+    pub fn key(&self) -> String {
+        format!("{:?}:{}", self.phase, self.descriptor.to_string_lossy())
+    }
+
+    /// Hash of everything that affects step results: descriptor (with pipeline params), source
+    /// files contents and plugin executable.
+    pub fn content_hash(&self) -> std::io::Result<u64> {
+        let mut hasher = ContentHasher::default();
+        hasher.write(&read(&self.descriptor)?);
+        hasher.write(self.assets.as_bytes());
+        for path in &self.source {
+            hasher.write(path.to_string_lossy().as_bytes());
+            hash_path_contents(path, &mut hasher)?;
+        }
+        if let Pipeline::Plugin { name, .. } = &self.pipeline {
+            if let Some(metadata) = which::which(name).ok().and_then(|path| metadata(path).ok()) {
+                hasher.write_u64(metadata.len());
+                if let Ok(duration) = metadata
+                    .modified()
+                    .map(|time| time.duration_since(UNIX_EPOCH).unwrap_or_default())
+                {
+                    hasher.write_u128(duration.as_nanos());
+                }
+            }
+        }
+        Ok(hasher.finish())
+    }
+
+    /// Files and directories produced by this step. Plugins might additionally write assets
+    /// list to step target file.
+    pub fn outputs(&self) -> Vec<PathBuf> {
+        let mut parent = self.target.to_owned();
+        parent.pop();
+        match &self.pipeline {
+            Pipeline::Copy => self
+                .source
+                .iter()
+                .filter_map(|path| path.file_name().map(|name| parent.join(name)))
+                .collect(),
+            Pipeline::Generate(_) => vec![self.target.to_owned()],
+            Pipeline::Pack { name, .. } => vec![parent.join(name).with_extension("pack")],
+            Pipeline::Plugin { .. } | Pipeline::Shell { .. } => {
+                vec![self.target.with_extension("")]
+            }
+        }
+    }
+
+    /// Same as `outputs` but also includes assets list file if step produced one.
+    pub fn outputs_with_assets_list(&self, assets: &[String]) -> Vec<PathBuf> {
+        let mut result = self.outputs();
+        if !assets.is_empty() {
+            result.push(self.target.to_owned());
+        }
+        result
+    }
+
+    /// Tells if results of previous run of this step are still present.
+    pub fn outputs_exist(&self, assets: &[String]) -> bool {
+        self.outputs().iter().all(|path| path.exists())
+            && (assets.is_empty() || self.target.exists())
+    }
+
+    pub fn execute(&self) -> std::io::Result<Vec<String>> {
+        let directory = self.target.with_extension("");
+        let result = self
+            .pipeline
+            .execute(&self.source, directory, &self.assets)?;
+        if !result.is_empty() {
+            match serde_json::to_string_pretty(&AssetOutput {
+                target: result.to_owned(),
+            }) {
+                Ok(contents) => {
+                    write(&self.target, contents)?;
+                }
+                Err(error) => {
+                    println!(
+                        "Could not serialize meta asset JSON config: {:?}. Error: {:?}",
+                        self.source, error
+                    );
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AssetStepStatus {
+    Executed,
+    Cached,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct AssetStepReport {
+    pub key: String,
+    pub status: AssetStepStatus,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetPipelineCacheEntry {
+    #[serde(default)]
+    pub phase: AssetPhase,
+    pub hash: u64,
+    #[serde(default)]
+    pub assets: Vec<String>,
+    /// Files and directories produced by step, removed when step gets re-run or removed.
+    #[serde(default)]
+    pub outputs: Vec<PathBuf>,
+}
+
+/// Content hashes of successfully executed steps, stored next to intermediate assets directory.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AssetPipelineCache {
+    #[serde(default)]
+    pub version: String,
+    /// {step key: entry}
+    #[serde(default)]
+    pub steps: BTreeMap<String, AssetPipelineCacheEntry>,
+}
+
+impl AssetPipelineCache {
+    pub fn path(intermediate: impl AsRef<Path>) -> PathBuf {
+        intermediate.as_ref().with_extension("cache.json")
+    }
+
+    /// Returns `None` if cache does not exist or was made by different ignite version.
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let contents = read_to_string(path).ok()?;
+        serde_json::from_str::<Self>(&contents)
+            .ok()
+            .filter(|cache| cache.version == env!("CARGO_PKG_VERSION"))
+    }
+
+    /// Removes entries of steps of given phases that are not part of pipeline anymore, together
+    /// with files they have produced.
+    pub fn remove_stale_steps(&mut self, phases: &[AssetPhase], steps: &[AssetStep]) {
+        let keys = steps.iter().map(|step| step.key()).collect::<HashSet<_>>();
+        let stale = self
+            .steps
+            .iter()
+            .filter(|(key, entry)| phases.contains(&entry.phase) && !keys.contains(*key))
+            .map(|(key, _)| key.to_owned())
+            .collect::<Vec<_>>();
+        for key in stale {
+            if let Some(entry) = self.steps.remove(&key) {
+                remove_paths(&entry.outputs);
+            }
+        }
+    }
+
+    pub fn save(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        self.version = env!("CARGO_PKG_VERSION").to_owned();
+        match serde_json::to_string_pretty(self) {
+            Ok(contents) => write(path, contents),
+            Err(error) => Err(Error::new(ErrorKind::Other, error.to_string())),
+        }
+    }
+}
+
+/// Executes steps in parallel (they must not depend on each other), skipping ones with results
+/// matching cached content hash.
+pub fn execute_steps(
+    steps: Vec<AssetStep>,
+    cache: &Mutex<AssetPipelineCache>,
+    jobs: usize,
+) -> Vec<AssetStepReport> {
+    let queue = Mutex::new(steps.into_iter());
+    let reports = Mutex::new(Vec::default());
+    scope(|scope| {
+        for _ in 0..jobs.max(1) {
+            scope.spawn(|| loop {
+                let step = match queue.lock().ok().and_then(|mut queue| queue.next()) {
+                    Some(step) => step,
+                    None => break,
+                };
+                let report = execute_step(step, cache);
+                if let Ok(mut reports) = reports.lock() {
+                    reports.push(report);
+                }
+            });
+        }
+    });
+    let mut result = reports.into_inner().unwrap_or_default();
+    result.sort_by(|a, b| a.key.cmp(&b.key));
+    result
+}
+
+fn execute_step(step: AssetStep, cache: &Mutex<AssetPipelineCache>) -> AssetStepReport {
+    let timer = Instant::now();
+    let key = step.key();
+    let hash = step.content_hash().ok();
+    let cached = hash
+        .and_then(|hash| {
+            let cache = cache.lock().ok()?;
+            let entry = cache.steps.get(&key)?;
+            Some(entry.hash == hash && step.outputs_exist(&entry.assets))
+        })
+        .unwrap_or_default();
+    if cached {
+        return AssetStepReport {
+            key,
+            status: AssetStepStatus::Cached,
+            duration: timer.elapsed(),
+        };
+    }
+    // Results of previous run are removed so files no longer produced by step do not linger.
+    let previous = cache
+        .lock()
+        .ok()
+        .and_then(|mut cache| cache.steps.remove(&key));
+    if let Some(entry) = previous {
+        remove_paths(&entry.outputs);
+    }
+    remove_paths(&step.outputs());
+    let status = match catch_unwind(AssertUnwindSafe(|| step.execute())) {
+        Ok(Ok(assets)) => {
+            if let (Some(hash), Ok(mut cache)) = (hash, cache.lock()) {
+                let outputs = step.outputs_with_assets_list(&assets);
+                cache.steps.insert(
+                    key.to_owned(),
+                    AssetPipelineCacheEntry {
+                        phase: step.phase,
+                        hash,
+                        assets,
+                        outputs,
+                    },
+                );
+            }
+            AssetStepStatus::Executed
+        }
+        Ok(Err(error)) => AssetStepStatus::Failed(error.to_string()),
+        Err(error) => AssetStepStatus::Failed(
+            error
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| error.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "Step panicked".to_owned()),
+        ),
+    };
+    AssetStepReport {
+        key,
+        status,
+        duration: timer.elapsed(),
+    }
+}
+
+/// Runs `SourceToIntermediate` and `SourceToBaked` steps first, then `IntermediateToBaked` steps
+/// which depend on intermediate assets produced by the former.
+pub fn execute_pipeline(
+    source: &Path,
+    intermediate: &Path,
+    baked: &Path,
+    tags: &[String],
+    clean: bool,
+    jobs: usize,
+) -> std::io::Result<()> {
+    let timer = Instant::now();
+    let cache_path = AssetPipelineCache::path(intermediate);
+    let cache = match AssetPipelineCache::load(&cache_path).filter(|_| !clean) {
+        Some(cache) => cache,
+        None => {
+            let _ = remove_dir_all(intermediate);
+            let _ = remove_dir_all(baked);
+            AssetPipelineCache::default()
+        }
+    };
+    let cache = Mutex::new(cache);
+
+    let mut steps = vec![];
+    let mut diagnostics = vec![];
+    AssetInput::collect_steps(
+        AssetPhase::SourceToIntermediate,
+        source,
+        intermediate,
+        "",
+        tags,
+        &mut steps,
+        &mut diagnostics,
+    )?;
+    AssetInput::collect_steps(
+        AssetPhase::SourceToBaked,
+        source,
+        baked,
+        "",
+        tags,
+        &mut steps,
+        &mut diagnostics,
+    )?;
+    report_diagnostics(&mut diagnostics);
+    for step in &steps {
+        step.pipeline.verify_used_plugins();
+    }
+    if let Ok(mut cache) = cache.lock() {
+        cache.remove_stale_steps(
+            &[AssetPhase::SourceToIntermediate, AssetPhase::SourceToBaked],
+            &steps,
+        );
+    }
+    let mut reports = execute_steps(steps, &cache, jobs);
+
+    if reports
+        .iter()
+        .any(|report| matches!(report.status, AssetStepStatus::Failed(_)))
+    {
+        println!("* Skipping IntermediateToBaked phase because of failed steps");
+    } else {
+        AssetInput::bake_assets_list(intermediate, "")?;
+        let mut steps = vec![];
+        AssetInput::collect_steps(
+            AssetPhase::IntermediateToBaked,
+            intermediate,
+            baked,
+            "",
+            tags,
+            &mut steps,
+            &mut diagnostics,
+        )?;
+        report_diagnostics(&mut diagnostics);
+        for step in &steps {
+            step.pipeline.verify_used_plugins();
+        }
+        if let Ok(mut cache) = cache.lock() {
+            cache.remove_stale_steps(&[AssetPhase::IntermediateToBaked], &steps);
+        }
+        reports.extend(execute_steps(steps, &cache, jobs));
+    }
+
+    let mut cache = cache.into_inner().unwrap_or_default();
+    if let Some(parent) = cache_path.parent() {
+        create_dir_all(parent)?;
+    }
+    cache.save(&cache_path)?;
+
+    println!("* Asset pipeline summary:");
+    let mut executed = 0;
+    let mut cached = 0;
+    let mut failed = 0;
+    for report in &reports {
+        match &report.status {
+            AssetStepStatus::Executed => {
+                executed += 1;
+                println!("  - Executed in {:?}: {}", report.duration, report.key);
+            }
+            AssetStepStatus::Cached => {
+                cached += 1;
+                println!("  - Cached: {}", report.key);
+            }
+            AssetStepStatus::Failed(error) => {
+                failed += 1;
+                println!(
+                    "  - Failed in {:?}: {}. Error: {}",
+                    report.duration, report.key, error
+                );
+            }
+        }
+    }
+    println!(
+        "* Executed: {}, cached: {}, failed: {}. Done in: {:?}",
+        executed,
+        cached,
+        failed,
+        timer.elapsed()
+    );
+    if failed > 0 {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("Asset pipeline steps failed: {}", failed),
+        ));
+    }
+    Ok(())
+}
+
+fn remove_paths(paths: &[PathBuf]) {
+    for path in paths {
+        if path.is_dir() {
+            let _ = remove_dir_all(path);
+        } else if path.exists() {
+            let _ = remove_file(path);
+        }
+    }
+}
+
+fn report_diagnostics(diagnostics: &mut Vec<AssetDiagnostic>) {
+    for diagnostic in diagnostics.drain(..) {
+        println!("{}", diagnostic);
+    }
+}
+
+/// FNV-1a, stable between runs unlike `DefaultHasher`.
+struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for ContentHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+fn hash_path_contents(path: &Path, hasher: &mut ContentHasher) -> std::io::Result<()> {
+    if path.is_dir() {
+        let mut entries = read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+        for entry in entries {
+            if let Some(name) = entry.file_name() {
+                hasher.write(name.to_string_lossy().as_bytes());
+            }
+            hash_path_contents(&entry, hasher)?;
+        }
+    } else if path.is_file() {
+        hasher.write(&read(path)?);
+    }
+    Ok(())
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct AssetOutput {
     #[serde(default)]
@@ -206,6 +661,8 @@ pub enum Pipeline {
     Pack {
         #[serde(default = "Pipeline::default_pack_name")]
         name: String,
+        #[serde(default)]
+        compression: PackCompression,
     },
     Plugin {
         #[serde(default)]
@@ -287,13 +744,14 @@ impl Pipeline {
                 }
                 Ok(vec![])
             }
-            Self::Pack { name } => {
+            Self::Pack { name, compression } => {
                 let mut target = target.as_ref().to_owned();
                 target.pop();
                 create_dir_all(&target)?;
                 crate::pack::pack_assets_and_write_to_file(
                     source,
                     target.join(name).with_extension("pack"),
+                    *compression,
                 )?;
                 Ok(vec![])
             }
@@ -367,3 +825,116 @@ impl Pipeline {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_directory(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("oxygengine-ignite-{}-{}", name, std::process::id()));
+        let _ = remove_dir_all(&path);
+        create_dir_all(path.join("source")).unwrap();
+        path
+    }
+
+    fn collect(root: &Path) -> Vec<AssetStep> {
+        let mut steps = vec![];
+        let mut diagnostics = vec![];
+        AssetInput::collect_steps(
+            AssetPhase::SourceToIntermediate,
+            root.join("source"),
+            root.join("intermediate"),
+            "",
+            &[],
+            &mut steps,
+            &mut diagnostics,
+        )
+        .unwrap();
+        assert!(diagnostics.is_empty());
+        steps
+    }
+
+    fn statuses(reports: &[AssetStepReport]) -> Vec<&'static str> {
+        reports
+            .iter()
+            .map(|report| match report.status {
+                AssetStepStatus::Executed => "executed",
+                AssetStepStatus::Cached => "cached",
+                AssetStepStatus::Failed(_) => "failed",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pipeline_cache() {
+        let root = make_directory("cache");
+        write(root.join("source/a.txt"), "a").unwrap();
+        write(root.join("source/a.asset"), r#"{ "source": ["a.txt"] }"#).unwrap();
+        let cache = Mutex::new(AssetPipelineCache::default());
+
+        let reports = execute_steps(collect(&root), &cache, 1);
+        assert_eq!(statuses(&reports), vec!["executed"]);
+        assert_eq!(
+            read_to_string(root.join("intermediate/a.txt")).unwrap(),
+            "a"
+        );
+        {
+            let cache = cache.lock().unwrap();
+            let entry = cache.steps.values().next().unwrap();
+            assert_eq!(entry.outputs, vec![root.join("intermediate/a.txt")]);
+        }
+
+        // cache hit.
+        let reports = execute_steps(collect(&root), &cache, 1);
+        assert_eq!(statuses(&reports), vec!["cached"]);
+
+        // cache miss because of changed source contents.
+        write(root.join("source/a.txt"), "b").unwrap();
+        let reports = execute_steps(collect(&root), &cache, 1);
+        assert_eq!(statuses(&reports), vec!["executed"]);
+        assert_eq!(
+            read_to_string(root.join("intermediate/a.txt")).unwrap(),
+            "b"
+        );
+
+        // cache miss because of removed outputs.
+        remove_file(root.join("intermediate/a.txt")).unwrap();
+        let reports = execute_steps(collect(&root), &cache, 1);
+        assert_eq!(statuses(&reports), vec!["executed"]);
+        assert!(root.join("intermediate/a.txt").exists());
+
+        let _ = remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_pipeline_cache_invalidation() {
+        let root = make_directory("invalidation");
+        create_dir_all(root.join("source/data")).unwrap();
+        write(root.join("source/data/x.txt"), "x").unwrap();
+        write(root.join("source/data/y.txt"), "y").unwrap();
+        write(root.join("source/data.asset"), r#"{ "source": ["data"] }"#).unwrap();
+        write(root.join("source/a.txt"), "a").unwrap();
+        write(root.join("source/a.asset"), r#"{ "source": ["a.txt"] }"#).unwrap();
+        let intermediate = root.join("intermediate");
+        let baked = root.join("baked");
+
+        execute_pipeline(&root.join("source"), &intermediate, &baked, &[], false, 1).unwrap();
+        assert!(intermediate.join("a.txt").exists());
+        assert!(intermediate.join("data/x.txt").exists());
+        assert!(intermediate.join("data/y.txt").exists());
+
+        // re-run step starts with clean outputs.
+        remove_file(root.join("source/data/y.txt")).unwrap();
+        // removed step has its outputs deleted.
+        remove_file(root.join("source/a.asset")).unwrap();
+        execute_pipeline(&root.join("source"), &intermediate, &baked, &[], false, 1).unwrap();
+        assert!(!intermediate.join("a.txt").exists());
+        assert!(intermediate.join("data/x.txt").exists());
+        assert!(!intermediate.join("data/y.txt").exists());
+        let cache = AssetPipelineCache::load(AssetPipelineCache::path(&intermediate)).unwrap();
+        assert_eq!(cache.steps.len(), 1);
+
+        let _ = remove_dir_all(&root);
+    }
+}
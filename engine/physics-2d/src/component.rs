@@ -439,4 +439,23 @@ impl PrefabProxy<Collider2dBodyPrefabProxy> for Collider2dBody {
             }
         }
     }
+
+    fn into_proxy_with_extras(
+        &self,
+        entity_names: &HashMap<Entity, String>,
+    ) -> Result<Collider2dBodyPrefabProxy, PrefabError> {
+        match self {
+            Self::Me => Ok(Collider2dBodyPrefabProxy::Me),
+            Self::Entity(entity) => {
+                if let Some(name) = entity_names.get(entity) {
+                    Ok(Collider2dBodyPrefabProxy::Entity(name.to_owned()))
+                } else {
+                    Err(PrefabError::CouldNotSerialize(format!(
+                        "Could not find name of entity: {:?}",
+                        entity
+                    )))
+                }
+            }
+        }
+    }
 }
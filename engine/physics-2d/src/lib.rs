@@ -54,3 +54,58 @@ pub fn prefabs_installer(prefabs: &mut PrefabManager) {
         "Collider2dBody",
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collide::shape::*, physics::object::*};
+    use core::{
+        ecs::{components::Name, life_cycle::EntityChanges, World},
+        prefab::{PrefabSceneEntity, PrefabValue},
+        state::StateToken,
+    };
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_snapshot_physics_entity() {
+        let mut prefabs = PrefabManager::default();
+        prefabs.register_component_factory::<Name>("Name");
+        prefabs_installer(&mut prefabs);
+
+        let mut world = World::new();
+        let entity = world.spawn((
+            Name(Cow::Borrowed("ball")),
+            RigidBody2d::new(RigidBodyDesc::new()),
+            Collider2d::new(ColliderDesc::new(ShapeHandle::new(Ball::new(1.0)))),
+            Collider2dBody::Me,
+        ));
+        // Bodies and colliders state lives in physics world so they are omitted from snapshot.
+        let scene = prefabs.snapshot_scene_direct(&[entity], &world).unwrap();
+        assert_eq!(scene.entities.len(), 1);
+        if let PrefabSceneEntity::Data(data) = &scene.entities[0] {
+            assert_eq!(data.components.len(), 2);
+            assert_eq!(
+                data.components.get("Name"),
+                Some(&PrefabValue::from("ball"))
+            );
+            assert_eq!(
+                data.components.get("Collider2dBody"),
+                Some(&PrefabValue::from("Me"))
+            );
+        } else {
+            unreachable!();
+        }
+
+        let mut world = World::new();
+        let mut changes = EntityChanges::default();
+        let entities = prefabs
+            .load_scene_from_prefab_direct(&scene, &mut world, &mut changes, StateToken::new())
+            .unwrap();
+        assert_eq!(entities.len(), 1);
+        assert_eq!(world.get::<&Name>(entities[0]).unwrap().0, "ball");
+        assert!(matches!(
+            *world.get::<&Collider2dBody>(entities[0]).unwrap(),
+            Collider2dBody::Me
+        ));
+    }
+}
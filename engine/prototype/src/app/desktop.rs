@@ -1,5 +1,5 @@
 use crate::{app::*, materials::*, systems::render_prototype_stage::*};
-// use oxygengine_audio_backend_desktop::prelude::*;
+use oxygengine_audio_backend_desktop::prelude::*;
 use oxygengine_backend_desktop::prelude::*;
 use oxygengine_core::{prelude::*, scripting::intuicio::prelude::*};
 use oxygengine_ha_renderer::prelude::*;
@@ -106,8 +106,8 @@ impl PrototypeApp for DesktopPrototypeApp {
                 make_renderer(runner.context_wrapper(), self.clear_color),
             )
             .unwrap()
-            // .with_bundle(oxygengine_audio::bundle_installer, DesktopAudio::default())
-            // .unwrap()
+            .with_bundle(oxygengine_audio::bundle_installer, DesktopAudio::default())
+            .unwrap()
             .with_bundle(oxygengine_nodes::bundle_installer, self.nodes)
             .unwrap()
             .with_bundle(crate::bundle_installer, |renderables, camera| {